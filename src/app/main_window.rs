@@ -1,5 +1,7 @@
 use eframe::egui;
-use crate::tabs::{StreamTab, AudioTab, VideoTab, BannerTab, CommentTab, StreamStatus, StatusTab};
+use crate::tabs::{StreamTab, AudioTab, VideoTab, BannerTab, CommentTab, StreamStatus, StatusTab, HotkeyTab};
+use crate::models::hotkey::HotkeyAction;
+use crate::models::comment::chat_channel;
 
 pub struct MainWindow {
     selected_tab: Tab,
@@ -7,25 +9,43 @@ pub struct MainWindow {
     audio_tab: AudioTab,
     video_tab: VideoTab,
     banner_tab: BannerTab,
-    #[allow(dead_code)]
     comment_tab: CommentTab,
     status_tab: StatusTab,
+    hotkey_tab: HotkeyTab,
     show_exit_confirmation: bool,
     show_stream_settings: bool,
+    /// 配信開始前に確認する権利未確認・使用不可の曲
+    license_warnings: Option<Vec<String>>,
+    was_streaming: bool,
 }
 
 impl Default for MainWindow {
     fn default() -> Self {
+        // コメント取得元（ライブ・リプレイ）とコメント欄をつなぐチャネル
+        let (chat_sender, chat_receiver) = chat_channel();
+        let audio_tab = AudioTab::new();
+        let comment_tab = CommentTab::new(chat_sender, chat_receiver, audio_tab.engine_inputs().tts);
+        let status_tab = StatusTab::new(
+            comment_tab.comment_feed(),
+            audio_tab.level_meters(),
+            audio_tab.now_playing(),
+            audio_tab.mic_indicator(),
+        );
+        let banner_tab = BannerTab::new(audio_tab.now_playing());
+
         Self {
             selected_tab: Tab::default(),
             stream_tab: StreamTab::default(),
-            audio_tab: AudioTab::default(),
+            audio_tab,
             video_tab: VideoTab::default(),
-            banner_tab: BannerTab::default(),
-            comment_tab: CommentTab::default(),
-            status_tab: StatusTab::default(),
+            banner_tab,
+            comment_tab,
+            status_tab,
+            hotkey_tab: HotkeyTab::new(),
             show_exit_confirmation: false,
             show_stream_settings: false,
+            license_warnings: None,
+            was_streaming: false,
         }
     }
 }
@@ -38,6 +58,8 @@ pub enum Tab {
     Audio,
     Video,
     Banner,
+    Comment,
+    Hotkey,
 }
 
 impl eframe::App for MainWindow {
@@ -45,6 +67,43 @@ impl eframe::App for MainWindow {
         // 継続的な更新を要求
         ctx.request_repaint();
 
+        // コメントの取り込みはどのタブを表示中でも行う
+        self.comment_tab.update();
+        self.audio_tab.update();
+        for comment in self.comment_tab.take_new_comments() {
+            self.audio_tab.handle_comment(&comment);
+        }
+        // ホットキーもどのタブを表示中でも受け付ける
+        let effects = self.audio_tab.effect_hotkeys();
+        let suspend = self.audio_tab.is_recording_hotkey();
+        for action in self.hotkey_tab.update(ctx, effects, suspend) {
+            self.run_hotkey_action(action);
+        }
+        self.audio_tab.update_mic_control(self.hotkey_tab.is_held(HotkeyAction::PushToTalk));
+
+        if self.status_tab.take_start_request() {
+            let warnings = self.audio_tab.license_warnings();
+            if warnings.is_empty() {
+                self.status_tab.set_streaming(true);
+            } else {
+                self.license_warnings = Some(warnings);
+            }
+        }
+        if self.status_tab.is_streaming && !self.was_streaming {
+            self.comment_tab.begin_stream();
+            self.status_tab.start_sync(self.audio_tab.subscribe_master());
+        } else if !self.status_tab.is_streaming && self.was_streaming {
+            self.comment_tab.end_stream();
+            self.status_tab.stop_sync();
+        }
+        self.was_streaming = self.status_tab.is_streaming;
+        self.audio_tab.set_live(self.status_tab.is_streaming);
+        self.status_tab.set_screen_sync_offset_ms(self.video_tab.video_config.screen_capture.sync_offset_ms);
+        for camera in &self.video_tab.video_config.camera_settings {
+            self.status_tab.set_camera_sync_offset_ms(&camera.device_id, camera.sync_offset_ms());
+        }
+        self.status_tab.update();
+
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.add_space(8.0);  // 左マージン
@@ -57,6 +116,8 @@ impl eframe::App for MainWindow {
                     (Tab::Audio, "音声設定"),
                     (Tab::Video, "映像設定"),
                     (Tab::Banner, "バナー設定"),
+                    (Tab::Comment, "コメント設定"),
+                    (Tab::Hotkey, "ホットキー"),
                 ] {
                     let is_selected = self.selected_tab == tab;
                     let response = ui.add(
@@ -105,6 +166,37 @@ impl eframe::App for MainWindow {
                     });
             }
 
+            // 権利を確認していない曲の警告
+            if let Some(warnings) = &self.license_warnings {
+                let mut close = false;
+                egui::Window::new("BGM の権利の確認")
+                    .collapsible(false)
+                    .resizable(false)
+                    .show(ctx, |ui| {
+                        ui.label("プレイリストに権利が未確認・使用不可の曲があります。");
+                        for warning in warnings {
+                            ui.label(format!("・{}", warning));
+                        }
+                        ui.separator();
+                        let mut skip = self.audio_tab.skip_unsafe_when_live();
+                        if ui.checkbox(&mut skip, "配信中はこれらの曲を飛ばす").changed() {
+                            self.audio_tab.set_skip_unsafe_when_live(skip);
+                        }
+                        ui.horizontal(|ui| {
+                            if ui.button("配信を開始").clicked() {
+                                self.status_tab.set_streaming(true);
+                                close = true;
+                            }
+                            if ui.button("キャンセル").clicked() {
+                                close = true;
+                            }
+                        });
+                    });
+                if close {
+                    self.license_warnings = None;
+                }
+            }
+
             self.show_tab_content(ui);
 
             // 配信設定ウィンドウ
@@ -127,6 +219,18 @@ impl eframe::App for MainWindow {
 }
 
 impl MainWindow {
+    fn run_hotkey_action(&mut self, action: HotkeyAction) {
+        match action {
+            HotkeyAction::PlayEffect(index) => self.audio_tab.play_effect(index),
+            HotkeyAction::StopEffects => self.audio_tab.stop_effects(),
+            HotkeyAction::SwitchScene => self.status_tab.toggle_screen_share(),
+            HotkeyAction::ToggleMicMute => self.audio_tab.toggle_mic_mute(),
+            // 押している間の状態は毎フレーム `update_mic_control` で反映する
+            HotkeyAction::PushToTalk => {}
+            HotkeyAction::ToggleStream => self.status_tab.toggle_streaming(),
+        }
+    }
+
     fn show_tab_content(&mut self, ui: &mut egui::Ui) {
         let transition = ui.ctx().animate_bool_with_time(
             egui::Id::new("tab_transition"),
@@ -144,6 +248,8 @@ impl MainWindow {
                 Tab::Audio => self.audio_tab.ui(ui),
                 Tab::Video => self.video_tab.ui(ui),
                 Tab::Banner => self.banner_tab.ui(ui),
+                Tab::Comment => self.comment_tab.ui(ui),
+                Tab::Hotkey => self.hotkey_tab.ui(ui),
             }
         });
     }
@@ -1,3 +1,5 @@
+use super::now_playing::TrackMetadata;
+
 #[allow(dead_code)]
 pub struct AudioConfig {
     pub master_volume: f32,
@@ -7,9 +9,30 @@ pub struct AudioConfig {
 
 #[allow(dead_code)]
 pub struct BGMTrack {
+    /// 再生エンジン上でトラックを識別する番号
+    pub id: u64,
     pub name: String,
     pub file_path: String,
     pub volume: f32,
+    /// ファイルのタグ（追加したときに読む）
+    pub metadata: TrackMetadata,
+}
+
+impl BGMTrack {
+    /// ファイル名（拡張子なし）をトラック名にする
+    pub fn from_path(id: u64, file_path: &str) -> Self {
+        let name = std::path::Path::new(file_path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| file_path.to_string());
+        Self {
+            id,
+            name,
+            file_path: file_path.to_string(),
+            volume: 0.5,
+            metadata: TrackMetadata::default(),
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+use super::audio_engine::MonitorSettings;
+use super::audio_recorder::RecorderSettings;
+use super::effects_chain::EffectsChainSettings;
+use super::loudness::{LoudnessSettings, MasterLimiterSettings};
+use super::mic_control::MicControlSettings;
+use super::mic_processing::MicProcessingSettings;
+use super::mixer::RoutingSettings;
+use super::now_playing::NowPlayingSettings;
+
+pub const AUDIO_CONFIG_PATH: &str = "config/audio.json";
+
+/// config/audio.json に保存する音声の設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioConfig {
+    /// `BusKind::ALL` の順のエフェクトチェーン
+    pub bus_effects: [EffectsChainSettings; 4],
+    pub recorder: RecorderSettings,
+    pub routing: RoutingSettings,
+    pub monitor: MonitorSettings,
+    pub now_playing: NowPlayingSettings,
+    pub mic_control: MicControlSettings,
+    /// マイクの処理チェーン
+    pub mic_processing: MicProcessingSettings,
+    pub loudness: LoudnessSettings,
+    pub master_limiter: MasterLimiterSettings,
+}
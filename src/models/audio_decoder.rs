@@ -0,0 +1,205 @@
+use std::fs::File;
+use std::path::Path;
+use std::time::Duration;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use symphonia::core::units::{Time, TimeBase};
+
+/// 音声ファイル（WAV/MP3/OGG/FLAC）を少しずつデコードする
+pub struct AudioDecoder {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    sample_rate: u32,
+    channels: usize,
+    time_base: Option<TimeBase>,
+    n_frames: Option<u64>,
+    /// シーク先がパケットの途中の場合に、パケットの先頭から捨てるフレーム数
+    skip_frames: u64,
+}
+
+impl AudioDecoder {
+    pub fn open(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("ファイルを開けません ({}): {}", path, e))?;
+        let stream = MediaSourceStream::new(Box::new(file), Default::default());
+
+        let mut hint = Hint::new();
+        if let Some(extension) = Path::new(path).extension().and_then(|e| e.to_str()) {
+            hint.with_extension(extension);
+        }
+        let probed = symphonia::default::get_probe()
+            .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+            .map_err(|e| format!("対応していない形式です ({}): {}", path, e))?;
+        let format = probed.format;
+
+        let track = format.tracks().iter()
+            .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or_else(|| format!("音声トラックがありません: {}", path))?;
+        let params = &track.codec_params;
+        let decoder = symphonia::default::get_codecs()
+            .make(params, &DecoderOptions::default())
+            .map_err(|e| format!("デコーダーを作成できません ({}): {}", path, e))?;
+
+        Ok(Self {
+            track_id: track.id,
+            sample_rate: params.sample_rate.unwrap_or(44_100),
+            channels: params.channels.map(|c| c.count()).unwrap_or(2),
+            time_base: params.time_base,
+            n_frames: params.n_frames,
+            skip_frames: 0,
+            format,
+            decoder,
+        })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// 曲の長さ（ヘッダーから分かる場合）
+    pub fn duration(&self) -> Option<Duration> {
+        let frames = self.n_frames?;
+        match self.time_base {
+            Some(time_base) => Some(to_duration(time_base.calc_time(frames))),
+            None => Some(Duration::from_secs_f64(frames as f64 / self.sample_rate as f64)),
+        }
+    }
+
+    /// 次のパケットをデコードし、インターリーブされた PCM を返す。終端では `None`
+    pub fn next_samples(&mut self) -> Result<Option<Vec<f32>>, String> {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(SymphoniaError::ResetRequired) => return Ok(None),
+                Err(e) => return Err(format!("読み込みに失敗: {}", e)),
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            match self.decoder.decode(&packet) {
+                Ok(decoded) => {
+                    let channels = decoded.spec().channels.count();
+                    let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, *decoded.spec());
+                    buffer.copy_interleaved_ref(decoded);
+                    let samples = buffer.samples();
+                    let skip = (self.skip_frames as usize).min(samples.len() / channels.max(1));
+                    self.skip_frames -= skip as u64;
+                    if skip * channels >= samples.len() {
+                        continue;
+                    }
+                    return Ok(Some(samples[skip * channels..].to_vec()));
+                }
+                // 壊れたパケットは読み飛ばす
+                Err(SymphoniaError::DecodeError(_)) => continue,
+                Err(e) => return Err(format!("デコードに失敗: {}", e)),
+            }
+        }
+    }
+
+    /// 指定位置へ移動し、実際に移動した位置を返す。パケットの途中の位置はデコード時に手前を捨てて合わせる
+    pub fn seek(&mut self, position: Duration) -> Result<Duration, String> {
+        let seeked = self.format
+            .seek(SeekMode::Accurate, SeekTo::Time {
+                time: Time::from(position.as_secs_f64()),
+                track_id: Some(self.track_id),
+            })
+            .map_err(|e| format!("シークに失敗: {}", e))?;
+        self.decoder.reset();
+
+        Ok(match self.time_base {
+            Some(time_base) => {
+                let actual = to_duration(time_base.calc_time(seeked.actual_ts));
+                let required = to_duration(time_base.calc_time(seeked.required_ts)).max(actual);
+                self.skip_frames = ((required - actual).as_secs_f64() * self.sample_rate as f64).round() as u64;
+                required
+            }
+            None => {
+                self.skip_frames = 0;
+                position
+            }
+        })
+    }
+}
+
+fn to_duration(time: Time) -> Duration {
+    Duration::from_secs(time.seconds) + Duration::from_secs_f64(time.frac)
+}
+
+/// ストリーミング用の線形補間リサンプラー（チャンネル数の変換を含む）
+///
+/// パケット境界をまたいで補間できるよう、直前のフレームと位相を保持する。
+pub struct Resampler {
+    in_rate: u32,
+    in_channels: usize,
+    out_rate: u32,
+    out_channels: usize,
+    /// 次に出力するフレームの入力上の位置（`previous` を 0 とする）
+    position: f64,
+    previous: Vec<f32>,
+}
+
+impl Resampler {
+    pub fn new(in_rate: u32, in_channels: usize, out_rate: u32, out_channels: usize) -> Self {
+        Self {
+            in_rate,
+            in_channels: in_channels.max(1),
+            out_rate,
+            out_channels,
+            position: 1.0,
+            previous: Vec::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.position = 1.0;
+        self.previous.clear();
+    }
+
+    /// 入力を変換して `output` の末尾に追加する
+    pub fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        let frames = input.len() / self.in_channels;
+        if frames == 0 {
+            return;
+        }
+        let step = self.in_rate as f64 / self.out_rate as f64;
+        let frame = |index: usize, previous: &[f32], channel: usize| -> f32 {
+            // index 0 は直前のパケットの最後のフレーム（初回は position が 1 から始まるため参照しない）
+            match index {
+                0 => previous[channel],
+                i => input[(i - 1) * self.in_channels + channel],
+            }
+        };
+
+        while self.position <= frames as f64 {
+            let index = self.position.floor() as usize;
+            let fraction = (self.position - index as f64) as f32;
+            let next = (index + 1).min(frames);
+            for channel in 0..self.out_channels {
+                let source = self.map_channel(channel);
+                let a = frame(index, &self.previous, source);
+                let b = frame(next, &self.previous, source);
+                output.push(a + (b - a) * fraction);
+            }
+            self.position += step;
+        }
+
+        self.position -= frames as f64;
+        self.previous = input[(frames - 1) * self.in_channels..frames * self.in_channels].to_vec();
+    }
+
+    /// モノラルは全チャンネルへ複製し、多チャンネルは先頭から割り当てる
+    fn map_channel(&self, channel: usize) -> usize {
+        channel.min(self.in_channels - 1)
+    }
+}
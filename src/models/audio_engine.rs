@@ -0,0 +1,634 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufWriter;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+use cpal::traits::{DeviceTrait, StreamTrait};
+use log::{info, error};
+use serde::{Deserialize, Serialize};
+use super::audio_decoder::Resampler;
+use super::audio_output;
+use super::bgm_player::BgmPlayer;
+use super::media_clock;
+use super::mixer::{BusKind, InputQueue, Mixer};
+use super::soundboard::EffectVoice;
+
+/// エンジン内部の共通フォーマット（48kHz ステレオ f32 インターリーブ）
+pub const ENGINE_SAMPLE_RATE: u32 = 48_000;
+pub const ENGINE_CHANNELS: usize = 2;
+
+/// 1 回のレンダリングで処理するフレーム数（10ms）
+pub const BLOCK_FRAMES: usize = 480;
+
+/// 出力デバイスにためておく最大の長さ
+const DEVICE_BUFFER: Duration = Duration::from_millis(100);
+
+/// エンジンの出力先
+pub trait AudioSink {
+    fn name(&self) -> String;
+
+    /// エンジン形式の PCM を書き込む。実時間より速く進まないよう必要に応じて待機する
+    fn write(&mut self, samples: &[f32]) -> Result<(), String>;
+}
+
+/// サウンドカードを使わない出力で、実時間に合わせて待機する
+struct Pacer {
+    started: Instant,
+    frames: u64,
+}
+
+impl Pacer {
+    fn new() -> Self {
+        Self { started: Instant::now(), frames: 0 }
+    }
+
+    fn wait(&mut self, samples: usize) {
+        self.frames += (samples / ENGINE_CHANNELS) as u64;
+        let due = self.started + Duration::from_secs_f64(self.frames as f64 / ENGINE_SAMPLE_RATE as f64);
+        if let Some(wait) = due.checked_duration_since(Instant::now()) {
+            thread::sleep(wait);
+        }
+    }
+}
+
+/// 音を出さずに捨てる出力（ヘッドレス環境・テスト用）
+pub struct NullSink {
+    pacer: Pacer,
+}
+
+impl NullSink {
+    pub fn new() -> Self {
+        Self { pacer: Pacer::new() }
+    }
+}
+
+impl AudioSink for NullSink {
+    fn name(&self) -> String {
+        "なし".to_string()
+    }
+
+    fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        self.pacer.wait(samples.len());
+        Ok(())
+    }
+}
+
+/// WAV ファイルに書き出す出力（サウンドカードなしでの動作確認用）
+pub struct WavFileSink {
+    path: String,
+    writer: hound::WavWriter<BufWriter<File>>,
+    pacer: Pacer,
+}
+
+impl WavFileSink {
+    pub fn create(path: &str) -> Result<Self, String> {
+        let spec = hound::WavSpec {
+            channels: ENGINE_CHANNELS as u16,
+            sample_rate: ENGINE_SAMPLE_RATE,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let writer = hound::WavWriter::create(path, spec)
+            .map_err(|e| format!("WAV ファイルを作成できません ({}): {}", path, e))?;
+        Ok(Self { path: path.to_string(), writer, pacer: Pacer::new() })
+    }
+}
+
+impl AudioSink for WavFileSink {
+    fn name(&self) -> String {
+        format!("ファイル ({})", self.path)
+    }
+
+    fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        for &sample in samples {
+            self.writer.write_sample(sample).map_err(|e| format!("WAV の書き込みに失敗: {}", e))?;
+        }
+        self.pacer.wait(samples.len());
+        Ok(())
+    }
+}
+
+/// サウンドカードへの出力
+pub struct DeviceSink {
+    name: String,
+    buffer: Arc<Mutex<VecDeque<f32>>>,
+    resampler: Resampler,
+    converted: Vec<f32>,
+    max_buffered: usize,
+    _stream: cpal::Stream,
+}
+
+impl DeviceSink {
+    /// `None` の場合は既定のデバイス。`buffer` はデバイスにためておく最大の長さ
+    pub fn open(device_name: Option<&str>, buffer: Duration) -> Result<Self, String> {
+        let device = audio_output::output_device(device_name)?;
+        let name = device.name().unwrap_or_else(|_| "不明なデバイス".to_string());
+        let config = device.default_output_config()
+            .map_err(|e| format!("出力設定の取得に失敗: {}", e))?;
+        let rate = config.sample_rate().0;
+        let channels = config.channels() as usize;
+
+        let max_buffered = (buffer.as_secs_f64() * rate as f64) as usize * channels;
+        let buffer = Arc::new(Mutex::new(VecDeque::new()));
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config.into(), Arc::clone(&buffer)),
+            cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config.into(), Arc::clone(&buffer)),
+            cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config.into(), Arc::clone(&buffer)),
+            format => return Err(format!("未対応のサンプル形式です: {:?}", format)),
+        }?;
+        stream.play().map_err(|e| format!("再生に失敗: {}", e))?;
+        info!("音声出力デバイス: {} ({}Hz, {}ch)", name, rate, channels);
+
+        Ok(Self {
+            name,
+            buffer,
+            resampler: Resampler::new(ENGINE_SAMPLE_RATE, ENGINE_CHANNELS, rate, channels),
+            converted: Vec::new(),
+            max_buffered,
+            _stream: stream,
+        })
+    }
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    buffer: Arc<Mutex<VecDeque<f32>>>,
+) -> Result<cpal::Stream, String>
+where
+    T: cpal::SizedSample + cpal::FromSample<f32>,
+{
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _| {
+            let mut buffer = buffer.lock().unwrap();
+            for sample in data.iter_mut() {
+                *sample = T::from_sample(buffer.pop_front().unwrap_or(0.0));
+            }
+        },
+        |e| error!("音声出力エラー: {}", e),
+        None,
+    ).map_err(|e| format!("出力ストリームの作成に失敗: {}", e))
+}
+
+impl AudioSink for DeviceSink {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        self.converted.clear();
+        self.resampler.process(samples, &mut self.converted);
+        self.buffer.lock().unwrap().extend(self.converted.iter().copied());
+
+        // デバイスが消費するまで待つことで実時間に合わせる
+        while self.buffer.lock().unwrap().len() > self.max_buffered {
+            thread::sleep(Duration::from_millis(2));
+        }
+        Ok(())
+    }
+}
+
+/// エンジンの出力先の指定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OutputTarget {
+    /// サウンドカード（`None` は既定のデバイス）
+    Device(Option<String>),
+    Null,
+    File(String),
+}
+
+impl Default for OutputTarget {
+    fn default() -> Self {
+        OutputTarget::Device(None)
+    }
+}
+
+/// ヘッドホンなどで確認するためのモニター出力
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonitorSettings {
+    pub enabled: bool,
+    pub target: OutputTarget,
+    /// 出力デバイスにためておく長さ。短いほど遅延が小さいが途切れやすい
+    pub latency_ms: u32,
+}
+
+impl Default for MonitorSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target: OutputTarget::Device(None),
+            latency_ms: 50,
+        }
+    }
+}
+
+fn open_sink(target: &OutputTarget, buffer: Duration) -> Result<Box<dyn AudioSink>, String> {
+    Ok(match target {
+        OutputTarget::Device(name) => Box::new(DeviceSink::open(name.as_deref(), buffer)?),
+        OutputTarget::Null => Box::new(NullSink::new()),
+        OutputTarget::File(path) => Box::new(WavFileSink::create(path)?),
+    })
+}
+
+/// レンダリングスレッドと UI で共有する状態
+#[derive(Default)]
+pub struct EngineState {
+    pub bgm: BgmPlayer,
+    /// 再生中の効果音（再生が終わったものは取り除く）
+    pub effects: Vec<EffectVoice>,
+    pub mixer: Mixer,
+    /// 効果音を出力先ごとに振り分けるための作業用バッファ
+    scratch: Vec<f32>,
+}
+
+/// 購読する位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TapPoint {
+    Master,
+    /// フェーダー・パンの後のバス
+    Bus(BusKind),
+    /// モニター用の出力（配信には流さない）
+    Monitor,
+}
+
+/// 出力 1 ブロック分（エンコーダー・録音などの購読者に渡す）
+#[derive(Clone)]
+pub struct AudioBlock {
+    /// 共有の時計でのブロック先頭の時刻（開始時刻 + レンダリングしたサンプル数から求める）
+    pub timestamp: Duration,
+    pub samples: Arc<[f32]>,
+}
+
+/// エンジンの外から PCM を流し込む入力
+#[derive(Clone, Default)]
+pub struct EngineInputs {
+    pub mic: InputQueue,
+    pub tts: InputQueue,
+}
+
+fn render(state: &mut EngineState, inputs: &EngineInputs, block: &mut [f32]) {
+    let mixer = &mut state.mixer;
+    mixer.begin(block.len());
+    state.bgm.mix_into(mixer.bus_buffer(BusKind::Bgm));
+    for voice in &mut state.effects {
+        state.scratch.clear();
+        state.scratch.resize(block.len(), 0.0);
+        voice.mix_into(&mut state.scratch);
+        if voice.output.to_stream() {
+            add_into(mixer.bus_buffer(BusKind::Effects), &state.scratch);
+        }
+        if voice.output.to_monitor() {
+            add_into(mixer.monitor_effects_buffer(), &state.scratch);
+        }
+    }
+    state.effects.retain(EffectVoice::is_playing);
+    inputs.mic.mix_into(mixer.bus_buffer(BusKind::Mic));
+    inputs.tts.mix_into(mixer.bus_buffer(BusKind::Tts));
+    mixer.mix_down(block);
+}
+
+fn add_into(output: &mut [f32], samples: &[f32]) {
+    for (out, sample) in output.iter_mut().zip(samples) {
+        *out += sample;
+    }
+}
+
+/// 購読する位置と送り先
+type Taps = Mutex<Vec<(TapPoint, SyncSender<AudioBlock>)>>;
+
+/// 購読者にブロックを配る。受け取りが追いつかない購読者の分は捨てる
+fn publish(taps: &Taps, timestamp: Duration, master: &[f32], mixer: &Mixer) {
+    taps.lock().unwrap().retain(|(point, tap)| {
+        let samples = match point {
+            TapPoint::Master => master,
+            TapPoint::Bus(kind) => mixer.bus_output(*kind),
+            TapPoint::Monitor => mixer.monitor_output(),
+        };
+        match tap.try_send(AudioBlock { timestamp, samples: samples.into() }) {
+            Ok(()) | Err(TrySendError::Full(_)) => true,
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    });
+}
+
+/// 出力の状況（UI 表示用）
+#[derive(Debug, Clone, Default)]
+pub struct EngineStatus {
+    pub output: String,
+    pub error: Option<String>,
+}
+
+/// 音源をまとめて出力先へ送るオーディオエンジン
+pub struct AudioEngine {
+    state: Arc<Mutex<EngineState>>,
+    inputs: EngineInputs,
+    taps: Arc<Taps>,
+    status: Arc<Mutex<EngineStatus>>,
+    running: Arc<AtomicBool>,
+    render_thread: Option<thread::JoinHandle<()>>,
+    monitor_status: Arc<Mutex<EngineStatus>>,
+    monitor_running: Arc<AtomicBool>,
+    monitor_thread: Option<thread::JoinHandle<()>>,
+}
+
+impl AudioEngine {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(EngineState::default())),
+            inputs: EngineInputs::default(),
+            taps: Arc::new(Mutex::new(Vec::new())),
+            status: Arc::new(Mutex::new(EngineStatus::default())),
+            running: Arc::new(AtomicBool::new(false)),
+            render_thread: None,
+            monitor_status: Arc::new(Mutex::new(EngineStatus::default())),
+            monitor_running: Arc::new(AtomicBool::new(false)),
+            monitor_thread: None,
+        }
+    }
+
+    /// 出力先を開いてレンダリングを開始する。開けない場合は出力なしで動かし続ける
+    pub fn start(&mut self, target: OutputTarget) {
+        self.stop();
+
+        *self.status.lock().unwrap() = EngineStatus::default();
+        let state = Arc::clone(&self.state);
+        let inputs = self.inputs.clone();
+        let taps = Arc::clone(&self.taps);
+        let status = Arc::clone(&self.status);
+        let running = Arc::clone(&self.running);
+        running.store(true, Ordering::SeqCst);
+
+        let handle = thread::spawn(move || {
+            // cpal のストリームはスレッドをまたげないため、このスレッドで開く
+            let mut sink = open_sink(&target, DEVICE_BUFFER).unwrap_or_else(|e| {
+                error!("音声出力を開けません: {}", e);
+                status.lock().unwrap().error = Some(e);
+                Box::new(NullSink::new())
+            });
+            status.lock().unwrap().output = sink.name();
+
+            let mut block = vec![0.0; BLOCK_FRAMES * ENGINE_CHANNELS];
+            // サンプル数から時刻を求めることで、ブロックごとの揺れが出ないようにする
+            let started = media_clock::now();
+            let mut rendered_frames: u64 = 0;
+            while running.load(Ordering::SeqCst) {
+                {
+                    let mut state = state.lock().unwrap();
+                    render(&mut state, &inputs, &mut block);
+                    let timestamp = started + Duration::from_secs_f64(rendered_frames as f64 / ENGINE_SAMPLE_RATE as f64);
+                    publish(&taps, timestamp, &block, &state.mixer);
+                }
+                rendered_frames += BLOCK_FRAMES as u64;
+
+                if let Err(e) = sink.write(&block) {
+                    error!("音声出力に失敗: {}", e);
+                    let mut status = status.lock().unwrap();
+                    status.error = Some(e);
+                    sink = Box::new(NullSink::new());
+                    status.output = sink.name();
+                }
+            }
+        });
+
+        self.render_thread = Some(handle);
+    }
+
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.render_thread.take() {
+            let _ = handle.join();
+        }
+    }
+
+    /// モニター用の出力を開始する。レンダリングとは別のスレッドで書き込むため、モニターが詰まっても配信側は止まらない
+    pub fn start_monitor(&mut self, settings: &MonitorSettings) {
+        self.stop_monitor();
+
+        *self.monitor_status.lock().unwrap() = EngineStatus::default();
+        let latency = Duration::from_millis(settings.latency_ms.max(1) as u64);
+        // 遅延の設定より古いブロックはためずに捨てる
+        let capacity = (settings.latency_ms as usize * ENGINE_SAMPLE_RATE as usize / (BLOCK_FRAMES * 1000)).max(2);
+        let receiver = self.subscribe(TapPoint::Monitor, capacity);
+        let target = settings.target.clone();
+        let status = Arc::clone(&self.monitor_status);
+        let running = Arc::clone(&self.monitor_running);
+        running.store(true, Ordering::SeqCst);
+
+        let handle = thread::spawn(move || {
+            let mut sink = match open_sink(&target, latency) {
+                Ok(sink) => sink,
+                Err(e) => {
+                    error!("モニター出力を開けません: {}", e);
+                    status.lock().unwrap().error = Some(e);
+                    return;
+                }
+            };
+            status.lock().unwrap().output = sink.name();
+
+            while running.load(Ordering::SeqCst) {
+                let block = match receiver.recv_timeout(Duration::from_millis(100)) {
+                    Ok(block) => block,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
+                if let Err(e) = sink.write(&block.samples) {
+                    error!("モニター出力に失敗: {}", e);
+                    status.lock().unwrap().error = Some(e);
+                    break;
+                }
+            }
+        });
+        self.monitor_thread = Some(handle);
+    }
+
+    pub fn stop_monitor(&mut self) {
+        self.monitor_running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.monitor_thread.take() {
+            let _ = handle.join();
+        }
+        *self.monitor_status.lock().unwrap() = EngineStatus::default();
+    }
+
+    pub fn monitor_status(&self) -> EngineStatus {
+        self.monitor_status.lock().unwrap().clone()
+    }
+
+    /// 共有状態をロックする。レンダリングを止めないよう重い処理はロック外で行う
+    pub fn state(&self) -> MutexGuard<'_, EngineState> {
+        self.state.lock().unwrap()
+    }
+
+    /// マイク・読み上げの入力
+    pub fn inputs(&self) -> EngineInputs {
+        self.inputs.clone()
+    }
+
+    /// マスターまたはバスの出力を購読する。`capacity` ブロックを超えて滞留した分は捨てられる
+    pub fn subscribe(&self, point: TapPoint, capacity: usize) -> Receiver<AudioBlock> {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        self.taps.lock().unwrap().push((point, sender));
+        receiver
+    }
+
+    pub fn status(&self) -> EngineStatus {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+impl Drop for AudioEngine {
+    fn drop(&mut self) {
+        self.stop_monitor();
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::{Path, PathBuf};
+    use crate::models::bgm_player::{PlaybackState, TrackPlayer};
+
+    /// 左は一定の 0.25、右は 0 から 0.5 へ上がり続ける 1 秒の WAV（右の値から再生位置が分かる）
+    fn write_test_wav(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("audio_engine_{}_{}.wav", std::process::id(), name));
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: ENGINE_SAMPLE_RATE,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        let frames = ENGINE_SAMPLE_RATE;
+        for frame in 0..frames {
+            writer.write_sample(0.25f32).unwrap();
+            writer.write_sample(ramp(frame as u64)).unwrap();
+        }
+        writer.finalize().unwrap();
+        path
+    }
+
+    fn ramp(frame: u64) -> f32 {
+        0.5 * frame as f32 / ENGINE_SAMPLE_RATE as f32
+    }
+
+    /// BGM トラックを 1 つ登録した状態。遅延が出ないようマスターリミッターは外す
+    fn engine_with_track(path: &Path, volume: f32) -> EngineState {
+        let mut state = EngineState::default();
+        state.mixer.settings.master_limiter.enabled = false;
+        let mut player = TrackPlayer::open(path.to_str().unwrap(), volume).unwrap();
+        player.play();
+        state.bgm.insert(1, player);
+        state
+    }
+
+    /// `blocks` ブロック分レンダリングして出力先に書き込み、書き込んだサンプルを返す
+    fn render_blocks(state: &mut EngineState, sink: &mut dyn AudioSink, blocks: usize) -> Vec<f32> {
+        let inputs = EngineInputs::default();
+        let mut block = vec![0.0; BLOCK_FRAMES * ENGINE_CHANNELS];
+        let mut rendered = Vec::new();
+        for _ in 0..blocks {
+            render(state, &inputs, &mut block);
+            sink.write(&block).unwrap();
+            rendered.extend_from_slice(&block);
+        }
+        rendered
+    }
+
+    fn left_channel(samples: &[f32]) -> impl Iterator<Item = f32> + '_ {
+        samples.chunks(ENGINE_CHANNELS).map(|frame| frame[0])
+    }
+
+    fn assert_left_level(samples: &[f32], expected: f32) {
+        for sample in left_channel(samples) {
+            assert!((sample - expected).abs() < 1e-4, "{} != {}", sample, expected);
+        }
+    }
+
+    #[test]
+    fn track_and_bus_volume_scale_output_written_to_file() {
+        let input = write_test_wav("volume");
+        let output = std::env::temp_dir().join(format!("audio_engine_{}_volume_out.wav", std::process::id()));
+        let mut state = engine_with_track(&input, 1.0);
+        let mut sink = WavFileSink::create(output.to_str().unwrap()).unwrap();
+
+        let mut rendered = render_blocks(&mut state, &mut sink, 5);
+        assert_left_level(&rendered, 0.25);
+
+        // 曲ごとの音量
+        state.bgm.track_mut(1).unwrap().set_volume(0.5);
+        let half = render_blocks(&mut state, &mut sink, 5);
+        assert_left_level(&half, 0.125);
+        rendered.extend(half);
+
+        // BGM 音量（BGM バスのゲイン）は曲の音量に掛け合わされる
+        state.mixer.settings.bus_mut(BusKind::Bgm).gain = 0.5;
+        let quarter = render_blocks(&mut state, &mut sink, 5);
+        assert_left_level(&quarter, 0.0625);
+        rendered.extend(quarter);
+
+        state.mixer.settings.bus_mut(BusKind::Bgm).mute = true;
+        let muted = render_blocks(&mut state, &mut sink, 1);
+        assert_left_level(&muted, 0.0);
+        rendered.extend(muted);
+
+        // ファイルには出力したとおりに書き込まれている
+        drop(sink);
+        let written: Vec<f32> = hound::WavReader::open(&output).unwrap().samples::<f32>().map(Result::unwrap).collect();
+        assert_eq!(written, rendered);
+
+        let _ = std::fs::remove_file(input);
+        let _ = std::fs::remove_file(output);
+    }
+
+    #[test]
+    fn play_pause_seek_and_position() {
+        let input = write_test_wav("transport");
+        let mut state = engine_with_track(&input, 1.0);
+        let mut sink = NullSink::new();
+        let block = Duration::from_millis(10);
+
+        // 再生した分だけ位置が進み、右チャンネルは先頭から続いている
+        let rendered = render_blocks(&mut state, &mut sink, 10);
+        assert_eq!(state.bgm.track_mut(1).unwrap().position(), block * 10);
+        for (frame, samples) in rendered.chunks(ENGINE_CHANNELS).enumerate() {
+            assert!((samples[1] - ramp(frame as u64)).abs() < 1e-4);
+        }
+
+        // 一時停止中は無音（既定のフラットな EQ の丸め誤差は残る）で、位置も進まない
+        state.bgm.track_mut(1).unwrap().pause();
+        let paused = render_blocks(&mut state, &mut sink, 5);
+        assert!(paused.iter().all(|sample| sample.abs() < 1e-4));
+        let player = state.bgm.track_mut(1).unwrap();
+        assert_eq!(player.status().state, PlaybackState::Paused);
+        assert_eq!(player.position(), block * 10);
+
+        // 一時停止した位置から再開する
+        player.play();
+        let resumed = render_blocks(&mut state, &mut sink, 1);
+        assert!((resumed[1] - ramp(10 * BLOCK_FRAMES as u64)).abs() < 1e-4);
+
+        // シークした位置から再生する
+        let player = state.bgm.track_mut(1).unwrap();
+        player.seek(Duration::from_millis(500));
+        let position = player.position();
+        assert!(position.abs_diff(Duration::from_millis(500)) < block);
+        let frame = (position.as_secs_f64() * ENGINE_SAMPLE_RATE as f64).round() as u64;
+        let seeked = render_blocks(&mut state, &mut sink, 1);
+        assert!((seeked[1] - ramp(frame)).abs() < 1e-4, "{} != {}", seeked[1], ramp(frame));
+        assert_eq!(state.bgm.track_mut(1).unwrap().position(), position + block);
+
+        // 終端まで再生すると止まる
+        render_blocks(&mut state, &mut sink, 60);
+        let player = state.bgm.track_mut(1).unwrap();
+        assert!(player.is_finished());
+        assert_eq!(player.status().state, PlaybackState::Stopped);
+        assert_eq!(player.status().duration, Some(Duration::from_secs(1)));
+
+        let _ = std::fs::remove_file(input);
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::VecDeque;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+/// 出力デバイス名の一覧
+pub fn output_device_names() -> Vec<String> {
+    cpal::default_host()
+        .output_devices()
+        .map(|devices| devices.filter_map(|device| device.name().ok()).collect())
+        .unwrap_or_default()
+}
+
+/// 名前で出力デバイスを探す。`None` の場合は既定のデバイス
+pub(crate) fn output_device(name: Option<&str>) -> Result<cpal::Device, String> {
+    let host = cpal::default_host();
+    match name {
+        None => host.default_output_device()
+            .ok_or_else(|| "出力デバイスが見つかりません".to_string()),
+        Some(name) => host.output_devices()
+            .map_err(|e| format!("出力デバイスの列挙に失敗: {}", e))?
+            .find(|device| device.name().map(|n| n == name).unwrap_or(false))
+            .ok_or_else(|| format!("出力デバイスが見つかりません: {}", name)),
+    }
+}
+
+/// WAV データを指定した出力デバイスで再生し終わるまで待機する
+pub fn play_wav_on(device: Option<&str>, wav: &[u8], volume: f32) -> Result<(), String> {
+    let (samples, sample_rate, channels) = decode_wav(wav)?;
+    play_samples(device, &samples, sample_rate, channels, volume)
+}
+
+/// WAV データを f32 のインターリーブ PCM にデコードする（サンプルレート・チャンネル数も返す）
+pub fn decode_wav(wav: &[u8]) -> Result<(Vec<f32>, u32, u16), String> {
+    let mut reader = hound::WavReader::new(Cursor::new(wav))
+        .map_err(|e| format!("WAVの解析に失敗: {}", e))?;
+    let spec = reader.spec();
+
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().filter_map(Result::ok).collect(),
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader.samples::<i32>().filter_map(Result::ok).map(|s| s as f32 / scale).collect()
+        }
+    };
+    Ok((samples, spec.sample_rate, spec.channels))
+}
+
+/// インターリーブされた PCM を出力デバイスで再生する
+pub fn play_samples(
+    device: Option<&str>,
+    samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
+    volume: f32,
+) -> Result<(), String> {
+    let device = output_device(device)?;
+    let config = device.default_output_config()
+        .map_err(|e| format!("出力設定の取得に失敗: {}", e))?;
+    if config.sample_format() != cpal::SampleFormat::F32 {
+        return Err(format!("未対応のサンプル形式です: {:?}", config.sample_format()));
+    }
+
+    let out_rate = config.sample_rate().0;
+    let out_channels = config.channels() as usize;
+    let buffer = Arc::new(Mutex::new(convert(samples, sample_rate, channels as usize, out_rate, out_channels, volume)));
+
+    let callback_buffer = Arc::clone(&buffer);
+    let stream = device.build_output_stream(
+        &config.into(),
+        move |data: &mut [f32], _| {
+            let mut buffer = callback_buffer.lock().unwrap();
+            for sample in data.iter_mut() {
+                *sample = buffer.pop_front().unwrap_or(0.0);
+            }
+        },
+        |e| log::error!("音声出力エラー: {}", e),
+        None,
+    ).map_err(|e| format!("出力ストリームの作成に失敗: {}", e))?;
+    stream.play().map_err(|e| format!("再生に失敗: {}", e))?;
+
+    while !buffer.lock().unwrap().is_empty() {
+        thread::sleep(Duration::from_millis(20));
+    }
+    // デバイス側に残ったバッファを出し切る
+    thread::sleep(Duration::from_millis(100));
+    Ok(())
+}
+
+/// 線形補間によるサンプルレート・チャンネル数の変換
+fn convert(
+    samples: &[f32],
+    in_rate: u32,
+    in_channels: usize,
+    out_rate: u32,
+    out_channels: usize,
+    volume: f32,
+) -> VecDeque<f32> {
+    let in_frames = samples.len() / in_channels.max(1);
+    if in_frames == 0 {
+        return VecDeque::new();
+    }
+    let out_frames = (in_frames as u64 * out_rate as u64 / in_rate.max(1) as u64) as usize;
+    let step = in_rate as f64 / out_rate as f64;
+
+    let mut output = VecDeque::with_capacity(out_frames * out_channels);
+    for frame in 0..out_frames {
+        let position = frame as f64 * step;
+        let index = position as usize;
+        let fraction = (position - index as f64) as f32;
+        let next = (index + 1).min(in_frames - 1);
+        for channel in 0..out_channels {
+            let source = channel.min(in_channels - 1);
+            let a = samples[index * in_channels + source];
+            let b = samples[next * in_channels + source];
+            output.push_back((a + (b - a) * fraction) * volume);
+        }
+    }
+    output
+}
@@ -0,0 +1,282 @@
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use log::{info, error};
+use serde::{Deserialize, Serialize};
+use super::audio_engine::{AudioBlock, AudioEngine, TapPoint, ENGINE_CHANNELS, ENGINE_SAMPLE_RATE};
+use super::flac_encoder::FlacEncoder;
+use super::mixer::BusKind;
+
+/// 書き込みが追いつかないときにためておくブロック数（約 2 秒）
+const TAP_CAPACITY: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecordingFormat {
+    Wav,
+    Flac,
+}
+
+impl RecordingFormat {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RecordingFormat::Wav => "WAV",
+            RecordingFormat::Flac => "FLAC",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            RecordingFormat::Wav => "wav",
+            RecordingFormat::Flac => "flac",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecorderSettings {
+    pub directory: String,
+    /// `{date}`・`{time}`・`{source}` を置き換える（拡張子は形式から付ける）
+    pub file_template: String,
+    pub format: RecordingFormat,
+    /// 16 または 24
+    pub bits_per_sample: u16,
+    /// `BusKind::ALL` の順。マスターとは別のファイルに書き出すバス
+    pub buses: [bool; 4],
+}
+
+impl Default for RecorderSettings {
+    fn default() -> Self {
+        Self {
+            directory: "recordings".to_string(),
+            file_template: "{date}_{time}_{source}".to_string(),
+            format: RecordingFormat::Wav,
+            bits_per_sample: 24,
+            buses: [false; 4],
+        }
+    }
+}
+
+impl RecorderSettings {
+    /// テンプレートからファイル名を作る。`{source}` がない場合、バスのファイルは末尾に付けて区別する
+    pub fn file_path(&self, started: &chrono::DateTime<chrono::Local>, source: &str) -> PathBuf {
+        let mut template = self.file_template.clone();
+        if !template.contains("{source}") && source != source_name(TapPoint::Master) {
+            template.push_str("_{source}");
+        }
+        let name = template
+            .replace("{date}", &started.format("%Y%m%d").to_string())
+            .replace("{time}", &started.format("%H%M%S").to_string())
+            .replace("{source}", source);
+        // パス区切りなどファイル名に使えない文字は置き換える
+        let name: String = name
+            .chars()
+            .map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') { '_' } else { c })
+            .collect();
+        Path::new(&self.directory).join(format!("{}.{}", name, self.format.extension()))
+    }
+}
+
+fn source_name(point: TapPoint) -> &'static str {
+    match point {
+        TapPoint::Master => "master",
+        TapPoint::Bus(BusKind::Bgm) => "bgm",
+        TapPoint::Bus(BusKind::Effects) => "effects",
+        TapPoint::Bus(BusKind::Mic) => "mic",
+        TapPoint::Bus(BusKind::Tts) => "tts",
+        TapPoint::Monitor => "monitor",
+    }
+}
+
+/// 整数 PCM でファイルに書き込む
+enum PcmWriter {
+    Wav(hound::WavWriter<BufWriter<File>>, u16),
+    Flac(FlacEncoder<BufWriter<File>>),
+}
+
+impl PcmWriter {
+    /// `bits_per_sample` は 16 または 24
+    fn create(path: &Path, format: RecordingFormat, bits_per_sample: u16) -> Result<Self, String> {
+        if !matches!(bits_per_sample, 16 | 24) {
+            return Err(format!("対応していないビット深度です: {}", bits_per_sample));
+        }
+        match format {
+            RecordingFormat::Wav => {
+                let spec = hound::WavSpec {
+                    channels: ENGINE_CHANNELS as u16,
+                    sample_rate: ENGINE_SAMPLE_RATE,
+                    bits_per_sample,
+                    sample_format: hound::SampleFormat::Int,
+                };
+                let writer = hound::WavWriter::create(path, spec)
+                    .map_err(|e| format!("WAV ファイルを作成できません ({}): {}", path.display(), e))?;
+                Ok(PcmWriter::Wav(writer, bits_per_sample))
+            }
+            RecordingFormat::Flac => {
+                let file = File::create(path).map_err(|e| format!("FLAC ファイルを作成できません ({}): {}", path.display(), e))?;
+                let encoder = FlacEncoder::new(BufWriter::new(file), ENGINE_SAMPLE_RATE, ENGINE_CHANNELS, bits_per_sample as u32)?;
+                Ok(PcmWriter::Flac(encoder))
+            }
+        }
+    }
+
+    fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        match self {
+            PcmWriter::Wav(writer, bits) => {
+                let max = ((1i32 << (*bits - 1)) - 1) as f32;
+                for &sample in samples {
+                    writer
+                        .write_sample((sample.clamp(-1.0, 1.0) * max).round() as i32)
+                        .map_err(|e| format!("WAV の書き込みに失敗: {}", e))?;
+                }
+                Ok(())
+            }
+            PcmWriter::Flac(encoder) => encoder.write(samples),
+        }
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self {
+            PcmWriter::Wav(writer, _) => writer.finalize().map_err(|e| format!("WAV の書き込みに失敗: {}", e)),
+            PcmWriter::Flac(encoder) => encoder.finish().map(|_| ()),
+        }
+    }
+}
+
+/// 録音の状況（UI 表示用）
+#[derive(Debug, Clone, Default)]
+pub struct RecorderStatus {
+    pub files: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// ミキサーの出力を映像とは別に音声ファイルへ書き出す
+pub struct AudioRecorder {
+    running: Arc<AtomicBool>,
+    threads: Vec<thread::JoinHandle<()>>,
+    started: Option<Instant>,
+    status: Arc<Mutex<RecorderStatus>>,
+}
+
+impl AudioRecorder {
+    pub fn new() -> Self {
+        Self {
+            running: Arc::new(AtomicBool::new(false)),
+            threads: Vec::new(),
+            started: None,
+            status: Arc::new(Mutex::new(RecorderStatus::default())),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.started.is_some()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.map_or(Duration::ZERO, |started| started.elapsed())
+    }
+
+    pub fn status(&self) -> RecorderStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// マスターと選択したバスの録音を始める。ファイルを作れない場合は何も録音しない
+    pub fn start(&mut self, engine: &AudioEngine, settings: &RecorderSettings) -> Result<(), String> {
+        self.stop();
+        fs::create_dir_all(&settings.directory)
+            .map_err(|e| format!("録音先のフォルダーを作成できません ({}): {}", settings.directory, e))?;
+
+        let now = chrono::Local::now();
+        let points: Vec<TapPoint> = std::iter::once(TapPoint::Master)
+            .chain(BusKind::ALL.into_iter().zip(settings.buses).filter(|(_, enabled)| *enabled).map(|(kind, _)| TapPoint::Bus(kind)))
+            .collect();
+
+        // すべてのファイルを作れてから購読を始める。途中で失敗した場合は作ったファイルを消す
+        let mut writers = Vec::new();
+        for point in &points {
+            let path = settings.file_path(&now, source_name(*point));
+            let writer = if path.exists() {
+                Err(format!("同じ名前のファイルがあります: {}", path.display()))
+            } else {
+                PcmWriter::create(&path, settings.format, settings.bits_per_sample)
+            };
+            match writer {
+                Ok(writer) => writers.push((path, writer)),
+                Err(e) => {
+                    for (path, writer) in writers {
+                        drop(writer);
+                        let _ = fs::remove_file(path);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        *self.status.lock().unwrap() = RecorderStatus {
+            files: writers.iter().map(|(path, _)| path.display().to_string()).collect(),
+            error: None,
+        };
+        self.running.store(true, Ordering::SeqCst);
+        for ((path, writer), point) in writers.into_iter().zip(points) {
+            let receiver = engine.subscribe(point, TAP_CAPACITY);
+            let running = Arc::clone(&self.running);
+            let status = Arc::clone(&self.status);
+            self.threads.push(thread::spawn(move || {
+                info!("録音を開始しました: {}", path.display());
+                if let Err(e) = record(receiver, writer, &running) {
+                    error!("録音に失敗しました ({}): {}", path.display(), e);
+                    status.lock().unwrap().error = Some(e);
+                }
+            }));
+        }
+        self.started = Some(Instant::now());
+        Ok(())
+    }
+
+    /// 録音を止め、ファイルを閉じる
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        for handle in self.threads.drain(..) {
+            let _ = handle.join();
+        }
+        self.started = None;
+    }
+}
+
+impl Drop for AudioRecorder {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// 届いたブロックを書き込む。取りこぼした区間は無音で埋め、バスごとのファイルの長さをそろえる
+fn record(receiver: Receiver<AudioBlock>, mut writer: PcmWriter, running: &AtomicBool) -> Result<(), String> {
+    let mut next: Option<Duration> = None;
+    let mut silence = Vec::new();
+    while running.load(Ordering::SeqCst) {
+        let block = match receiver.recv_timeout(Duration::from_millis(100)) {
+            Ok(block) => block,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        if let Some(expected) = next {
+            if let Some(gap) = block.timestamp.checked_sub(expected) {
+                let frames = (gap.as_secs_f64() * ENGINE_SAMPLE_RATE as f64).round() as usize;
+                if frames > 0 {
+                    silence.clear();
+                    silence.resize(frames * ENGINE_CHANNELS, 0.0);
+                    writer.write(&silence)?;
+                }
+            }
+        }
+        writer.write(&block.samples)?;
+        let frames = block.samples.len() / ENGINE_CHANNELS;
+        next = Some(block.timestamp + Duration::from_secs_f64(frames as f64 / ENGINE_SAMPLE_RATE as f64));
+    }
+    writer.finish()
+}
@@ -0,0 +1,482 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use log::error;
+use rand::seq::SliceRandom;
+use super::audio_decoder::{AudioDecoder, Resampler};
+use super::audio_engine::{ENGINE_CHANNELS, ENGINE_SAMPLE_RATE};
+use super::playlist::{PlaylistOptions, RepeatMode, TransitionMode};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlaybackState {
+    Stopped,
+    Playing,
+    Paused,
+}
+
+/// UI に表示する再生状況
+#[derive(Debug, Clone)]
+pub struct TrackStatus {
+    pub state: PlaybackState,
+    pub position: Duration,
+    pub duration: Option<Duration>,
+    pub error: Option<String>,
+}
+
+/// 1 曲分のデコードと再生位置の管理
+pub struct TrackPlayer {
+    path: String,
+    decoder: AudioDecoder,
+    resampler: Resampler,
+    /// デコード済みでまだミックスしていない PCM（エンジンの形式）
+    pending: Vec<f32>,
+    state: PlaybackState,
+    /// 再生済みのフレーム数（エンジンのサンプルレート基準）
+    position_frames: u64,
+    volume: f32,
+    /// ラウドネス正規化の倍率
+    normalization: f32,
+    /// フェードイン・アウト中の音量変化
+    fade: Option<Fade>,
+    /// 終端まで再生して止まった
+    finished: bool,
+    error: Option<String>,
+}
+
+impl TrackPlayer {
+    /// ファイルを開く。時間がかかるためエンジンのロック外で呼び出す
+    pub fn open(path: &str, volume: f32) -> Result<Self, String> {
+        let decoder = AudioDecoder::open(path)?;
+        let resampler = Resampler::new(decoder.sample_rate(), decoder.channels(), ENGINE_SAMPLE_RATE, ENGINE_CHANNELS);
+        Ok(Self {
+            path: path.to_string(),
+            decoder,
+            resampler,
+            pending: Vec::new(),
+            state: PlaybackState::Stopped,
+            position_frames: 0,
+            volume,
+            normalization: 1.0,
+            fade: None,
+            finished: false,
+            error: None,
+        })
+    }
+
+    pub fn status(&self) -> TrackStatus {
+        TrackStatus {
+            state: self.state,
+            position: self.position(),
+            duration: self.decoder.duration(),
+            error: self.error.clone(),
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.state == PlaybackState::Playing
+    }
+
+    /// 終端までの残り時間（曲の長さが分かる場合）
+    pub fn remaining(&self) -> Option<Duration> {
+        self.decoder.duration().map(|duration| duration.saturating_sub(self.position()))
+    }
+
+    pub fn position(&self) -> Duration {
+        Duration::from_secs_f64(self.position_frames as f64 / ENGINE_SAMPLE_RATE as f64)
+    }
+
+    pub fn play(&mut self) {
+        if self.finished {
+            self.seek(Duration::ZERO);
+        }
+        self.state = PlaybackState::Playing;
+        self.error = None;
+    }
+
+    pub fn pause(&mut self) {
+        if self.state == PlaybackState::Playing {
+            self.state = PlaybackState::Paused;
+        }
+    }
+
+    /// 停止して先頭に戻る
+    pub fn stop(&mut self) {
+        self.state = PlaybackState::Stopped;
+        self.fade = None;
+        self.seek(Duration::ZERO);
+    }
+
+    /// 先頭から再生し直す
+    pub fn restart(&mut self) {
+        self.stop();
+        self.play();
+    }
+
+    /// `position` から再生する。`fade_in` が 0 でなければ無音から徐々に大きくする
+    pub fn play_from(&mut self, position: Duration, fade_in: Duration) {
+        self.state = PlaybackState::Stopped;
+        self.fade = None;
+        self.seek(position);
+        self.play();
+        if !fade_in.is_zero() {
+            self.fade = Some(Fade::new(0.0, 1.0, fade_in));
+        }
+    }
+
+    /// 無音から徐々に大きくしながら再生を始める
+    pub fn fade_in(&mut self, length: Duration) {
+        self.restart();
+        self.fade = Some(Fade::new(0.0, 1.0, length));
+    }
+
+    /// 徐々に小さくし、無音になったら停止する
+    pub fn fade_out(&mut self, length: Duration) {
+        let from = self.fade.as_ref().map_or(1.0, Fade::gain);
+        self.fade = Some(Fade::new(from, 0.0, length));
+    }
+
+    pub fn seek(&mut self, position: Duration) {
+        self.finished = false;
+        match self.decoder.seek(position) {
+            Ok(actual) => {
+                self.position_frames = (actual.as_secs_f64() * ENGINE_SAMPLE_RATE as f64) as u64;
+                self.pending.clear();
+                self.resampler.reset();
+            }
+            Err(e) => {
+                error!("{}: {}", self.path, e);
+                self.error = Some(e);
+            }
+        }
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume;
+    }
+
+    /// ラウドネス正規化の倍率（音量とは別にかける）
+    pub fn set_normalization(&mut self, gain: f32) {
+        self.normalization = gain;
+    }
+
+    /// 曲の終端まで再生した（またはフェードアウトし終えた）か
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// 再生中であれば `output` に加算し、書き込んだサンプル数を返す。終端に達したら停止状態になる
+    pub fn mix_into(&mut self, output: &mut [f32]) -> usize {
+        if self.state != PlaybackState::Playing {
+            return 0;
+        }
+
+        let reached_end = !self.fill(output.len());
+        let available = self.pending.len().min(output.len());
+        let volume = self.volume * self.normalization;
+        let mut gain = volume;
+        for (index, (out, sample)) in output.iter_mut().zip(&self.pending[..available]).enumerate() {
+            if index % ENGINE_CHANNELS == 0 {
+                gain = volume * self.fade.as_mut().map_or(1.0, Fade::advance);
+            }
+            *out += sample * gain;
+        }
+        self.pending.drain(..available);
+        self.position_frames += (available / ENGINE_CHANNELS) as u64;
+
+        if self.fade.as_ref().is_some_and(Fade::is_done) {
+            let faded_out = self.fade.as_ref().is_some_and(|fade| fade.to == 0.0);
+            self.fade = None;
+            if faded_out {
+                self.state = PlaybackState::Stopped;
+                self.finished = true;
+            }
+        }
+        if reached_end && self.pending.is_empty() {
+            self.state = PlaybackState::Stopped;
+            self.finished = true;
+        }
+        available
+    }
+
+    /// `samples` 個以上たまるまでデコードする。終端に達した場合は false
+    fn fill(&mut self, samples: usize) -> bool {
+        while self.pending.len() < samples {
+            match self.decoder.next_samples() {
+                Ok(Some(decoded)) => self.resampler.process(&decoded, &mut self.pending),
+                Ok(None) => return false,
+                Err(e) => {
+                    error!("{}: {}", self.path, e);
+                    self.error = Some(e);
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+/// 音量を直線的に変化させる
+struct Fade {
+    from: f32,
+    to: f32,
+    total_frames: u64,
+    done_frames: u64,
+}
+
+impl Fade {
+    fn new(from: f32, to: f32, length: Duration) -> Self {
+        Self {
+            from,
+            to,
+            total_frames: ((length.as_secs_f64() * ENGINE_SAMPLE_RATE as f64) as u64).max(1),
+            done_frames: 0,
+        }
+    }
+
+    fn gain(&self) -> f32 {
+        let progress = self.done_frames as f32 / self.total_frames as f32;
+        self.from + (self.to - self.from) * progress.min(1.0)
+    }
+
+    /// 1 フレーム進め、そのフレームのゲインを返す
+    fn advance(&mut self) -> f32 {
+        let gain = self.gain();
+        self.done_frames += 1;
+        gain
+    }
+
+    fn is_done(&self) -> bool {
+        self.done_frames >= self.total_frames
+    }
+}
+
+/// プレイリストの自動再生の状態
+struct PlaylistPlayback {
+    /// 再生順（シャッフル時は並べ替え済み）
+    order: Vec<u64>,
+    index: usize,
+    current: u64,
+    options: PlaylistOptions,
+    /// クロスフェードで再生を始めた次の曲の位置
+    incoming: Option<usize>,
+}
+
+impl PlaylistPlayback {
+    /// 次に再生する位置。全曲リピートで一巡した場合はシャッフルし直す
+    fn next_index(&mut self) -> Option<usize> {
+        match self.options.repeat {
+            RepeatMode::One => Some(self.index),
+            _ if self.index + 1 < self.order.len() => Some(self.index + 1),
+            RepeatMode::All => {
+                if self.options.shuffle {
+                    shuffle_order(&mut self.order, Some(self.current));
+                }
+                Some(0)
+            }
+            RepeatMode::Off => None,
+        }
+    }
+
+    /// `next_index` のうち `skipped` の曲を飛ばした位置。一巡しても見つからなければ `None`
+    fn next_playable(&mut self, skipped: &HashSet<u64>) -> Option<usize> {
+        let (index, current) = (self.index, self.current);
+        let mut found = None;
+        for _ in 0..self.order.len() {
+            let Some(next) = self.next_index() else { break };
+            if !skipped.contains(&self.order[next]) {
+                found = Some(next);
+                break;
+            }
+            if self.options.repeat == RepeatMode::One {
+                break;
+            }
+            self.index = next;
+            self.current = self.order[next];
+        }
+        self.index = index;
+        self.current = current;
+        found
+    }
+}
+
+/// 再生順をシャッフルする。`avoid_first` の曲が続けて流れないよう先頭には置かない
+fn shuffle_order(order: &mut [u64], avoid_first: Option<u64>) {
+    order.shuffle(&mut rand::thread_rng());
+    if order.len() > 1 && avoid_first == Some(order[0]) {
+        order.swap(0, 1);
+    }
+}
+
+/// BGM トラックの再生。トラックは `BGMTrack::id` で識別する
+#[derive(Default)]
+pub struct BgmPlayer {
+    tracks: HashMap<u64, TrackPlayer>,
+    playlist: Option<PlaylistPlayback>,
+    /// プレイリストで飛ばす曲
+    skipped: HashSet<u64>,
+}
+
+impl BgmPlayer {
+    pub fn insert(&mut self, id: u64, player: TrackPlayer) {
+        self.tracks.insert(id, player);
+    }
+
+    pub fn remove(&mut self, id: u64) {
+        if self.playlist_current() == Some(id) {
+            self.skip();
+        }
+        if let Some(playlist) = &mut self.playlist {
+            playlist.order.retain(|&track| track != id);
+            playlist.incoming = None;
+            match playlist.order.iter().position(|&track| track == playlist.current) {
+                Some(index) => playlist.index = index,
+                None => self.playlist = None,
+            }
+        }
+        self.tracks.remove(&id);
+    }
+
+    /// プレイリストを `start_at` 番目の曲から再生する（シャッフル時は `start_at` の曲を最初に流す）
+    pub fn start_playlist(&mut self, track_ids: &[u64], options: PlaylistOptions, start_at: usize) {
+        self.stop_playlist();
+        let mut order: Vec<u64> = track_ids.iter().copied().filter(|id| self.tracks.contains_key(id)).collect();
+        let Some(&first) = track_ids.get(start_at).filter(|id| order.contains(id)).or(order.first()) else { return };
+        let first = if self.skipped.contains(&first) {
+            let Some(id) = order.iter().copied().find(|id| !self.skipped.contains(id)) else { return };
+            id
+        } else {
+            first
+        };
+        if options.shuffle {
+            shuffle_order(&mut order, None);
+            let position = order.iter().position(|&id| id == first).unwrap_or(0);
+            order.swap(0, position);
+        }
+        let index = order.iter().position(|&id| id == first).unwrap_or(0);
+
+        if let Some(track) = self.tracks.get_mut(&first) {
+            track.restart();
+        }
+        self.playlist = Some(PlaylistPlayback { order, index, current: first, options, incoming: None });
+    }
+
+    pub fn stop_playlist(&mut self) {
+        if let Some(playlist) = self.playlist.take() {
+            let incoming = playlist.incoming.map(|index| playlist.order[index]);
+            for id in [Some(playlist.current), incoming].into_iter().flatten() {
+                if let Some(track) = self.tracks.get_mut(&id) {
+                    track.stop();
+                }
+            }
+        }
+    }
+
+    /// 次の曲へ進む
+    pub fn skip(&mut self) {
+        if let Some(current) = self.playlist_current() {
+            if let Some(track) = self.tracks.get_mut(&current) {
+                track.stop();
+            }
+            self.advance();
+        }
+    }
+
+    /// プレイリストで飛ばす曲を設定する。再生中の曲が含まれる場合は次の曲へ進む
+    pub fn set_skipped(&mut self, skipped: HashSet<u64>) {
+        self.skipped = skipped;
+        if self.playlist_current().is_some_and(|id| self.skipped.contains(&id)) {
+            self.skip();
+        }
+    }
+
+    pub fn set_playlist_options(&mut self, options: PlaylistOptions) {
+        if let Some(playlist) = &mut self.playlist {
+            playlist.options = options;
+        }
+    }
+
+    /// プレイリストで再生中の曲
+    pub fn playlist_current(&self) -> Option<u64> {
+        self.playlist.as_ref().map(|playlist| playlist.current)
+    }
+
+    /// 現在の曲を終えて次の曲を始める。次の曲がなければプレイリストの再生を終える
+    fn advance(&mut self) {
+        let Some(playlist) = &mut self.playlist else { return };
+        // クロスフェードで既に再生を始めている
+        if let Some(index) = playlist.incoming.take() {
+            playlist.index = index;
+            playlist.current = playlist.order[index];
+            return;
+        }
+
+        match playlist.next_playable(&self.skipped) {
+            Some(index) => {
+                playlist.index = index;
+                playlist.current = playlist.order[index];
+                if let Some(track) = self.tracks.get_mut(&playlist.current) {
+                    track.restart();
+                }
+            }
+            None => self.playlist = None,
+        }
+    }
+
+    /// 現在の曲の残りがクロスフェードの長さを切ったら次の曲をフェードインさせる
+    fn begin_crossfade(&mut self) {
+        let Some(playlist) = &mut self.playlist else { return };
+        if playlist.options.transition != TransitionMode::Crossfade || playlist.incoming.is_some() {
+            return;
+        }
+        let crossfade = playlist.options.crossfade;
+        let Some(remaining) = self.tracks.get(&playlist.current)
+            .filter(|track| track.is_playing())
+            .and_then(TrackPlayer::remaining)
+        else { return };
+        if remaining > crossfade {
+            return;
+        }
+
+        let Some(next) = playlist.next_playable(&self.skipped) else { return };
+        let next_id = playlist.order[next];
+        // 同じ曲どうしはクロスフェードできないため、終端でギャップレスに戻る
+        if next_id == playlist.current {
+            return;
+        }
+        if let Some(track) = self.tracks.get_mut(&next_id) {
+            track.fade_in(crossfade);
+        }
+        if let Some(track) = self.tracks.get_mut(&playlist.current) {
+            track.fade_out(remaining);
+        }
+        playlist.incoming = Some(next);
+    }
+
+    pub fn track_mut(&mut self, id: u64) -> Option<&mut TrackPlayer> {
+        self.tracks.get_mut(&id)
+    }
+
+    /// 再生中のトラックをすべて `output` に加算し、プレイリストの曲送りを行う
+    pub fn mix_into(&mut self, output: &mut [f32]) {
+        self.begin_crossfade();
+
+        let current = self.playlist_current();
+        let mut current_written = None;
+        for (id, track) in &mut self.tracks {
+            let written = track.mix_into(output);
+            if Some(*id) == current && track.is_finished() {
+                current_written = Some(written);
+            }
+        }
+
+        // 曲が終わった位置から次の曲を続けて書き込み、曲間に無音を作らない
+        if let Some(written) = current_written {
+            let crossfading = self.playlist.as_ref().is_some_and(|playlist| playlist.incoming.is_some());
+            self.advance();
+            if !crossfading {
+                if let Some(track) = self.playlist_current().and_then(|id| self.tracks.get_mut(&id)) {
+                    track.mix_into(&mut output[written..]);
+                }
+            }
+        }
+    }
+}
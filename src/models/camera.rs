@@ -1,11 +1,12 @@
 use std::time::{Duration, Instant};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
 use nokhwa::pixel_format::RgbFormat;
 use nokhwa::utils::{CameraIndex, RequestedFormat, RequestedFormatType};
 use nokhwa::{Camera, NokhwaError};
 use image::RgbImage;
+use super::media_clock;
 
 struct ThreadSafeCamera(Camera);
 unsafe impl Send for ThreadSafeCamera {}
@@ -18,8 +19,11 @@ pub struct CameraSettings {
     pub size: (f32, f32),
     pub(crate) frame: Arc<Mutex<Option<RgbImage>>>,
     camera: Arc<Mutex<Option<ThreadSafeCamera>>>,
-    frame_front: Arc<Mutex<Option<RgbImage>>>,
-    frame_back: Arc<Mutex<Option<RgbImage>>>,
+    /// 共有の時計でのタイムスタンプ付きのフレーム
+    frame_front: Arc<Mutex<Option<(RgbImage, Duration)>>>,
+    frame_back: Arc<Mutex<Option<(RgbImage, Duration)>>>,
+    /// カメラの遅延の補正（ms）。この分だけ前に撮られたものとしてタイムスタンプを付ける
+    sync_offset_ms: Arc<AtomicI32>,
     buffer_swap_needed: Arc<AtomicBool>,
     #[allow(dead_code)]
     last_frame_time: Instant,
@@ -60,6 +64,7 @@ impl CameraSettings {
             frame_front: Arc::new(Mutex::new(None)),
             frame_back: Arc::new(Mutex::new(None)),
             buffer_swap_needed: Arc::new(AtomicBool::new(false)),
+            sync_offset_ms: Arc::new(AtomicI32::new(0)),
             last_frame_time: Instant::now(),
             frame_interval,
             running: Arc::new(Mutex::new(true)),
@@ -85,12 +90,21 @@ impl CameraSettings {
         }
     }
 
+    pub fn sync_offset_ms(&self) -> i32 {
+        self.sync_offset_ms.load(Ordering::Relaxed)
+    }
+
+    pub fn set_sync_offset_ms(&mut self, offset_ms: i32) {
+        self.sync_offset_ms.store(offset_ms, Ordering::Relaxed);
+    }
+
     fn start_capture_thread(&mut self) {
         let frame = Arc::clone(&self.frame);
         let frame_back = Arc::clone(&self.frame_back);
         let buffer_swap_needed = Arc::clone(&self.buffer_swap_needed);
         let running = Arc::clone(&self.running);
         let camera = Arc::clone(&self.camera);
+        let sync_offset_ms = Arc::clone(&self.sync_offset_ms);
         let frame_interval = self.frame_interval;
 
         *running.lock().unwrap() = true;
@@ -104,6 +118,8 @@ impl CameraSettings {
                     if let Some(ThreadSafeCamera(ref mut cam)) = camera_guard.as_mut() {
                         match cam.frame() {
                             Ok(frame_data) => {
+                                // デコード前の取得した時点で時刻を付ける
+                                let timestamp = media_clock::stamp(sync_offset_ms.load(Ordering::Relaxed));
                                 if let Ok(decoded) = frame_data.decode_image::<RgbFormat>() {
                                     let raw_data = decoded.into_raw();
                                     if raw_data.len() == buffer.len() {
@@ -118,7 +134,7 @@ impl CameraSettings {
                                             
                                             // バックバッファに書き込み
                                             if let Ok(mut back_guard) = frame_back.lock() {
-                                                *back_guard = Some(image);
+                                                *back_guard = Some((image, timestamp));
                                                 buffer_swap_needed.store(true, Ordering::Release);
                                             }
                                         }
@@ -148,6 +164,16 @@ impl CameraSettings {
     }
 
     pub fn get_frame(&self) -> Option<RgbImage> {
+        self.get_stamped_frame().map(|(image, _)| image)
+    }
+
+    /// 最後に取り込んだフレームのタイムスタンプ（フレームは複製しない）
+    pub fn frame_timestamp(&self) -> Option<Duration> {
+        self.frame_back.lock().ok()?.as_ref().map(|(_, timestamp)| *timestamp)
+    }
+
+    /// 最新フレームと、共有の時計でのタイムスタンプ
+    pub fn get_stamped_frame(&self) -> Option<(RgbImage, Duration)> {
         // バッファのスワップが必要な場合のみスワップを実行
         if self.buffer_swap_needed.load(Ordering::Acquire) {
             if let (Ok(mut front), Ok(back)) = (self.frame_front.lock(), self.frame_back.lock()) {
@@ -180,6 +206,7 @@ impl Default for CameraSettings {
             frame_front: Arc::new(Mutex::new(None)),
             frame_back: Arc::new(Mutex::new(None)),
             buffer_swap_needed: Arc::new(AtomicBool::new(false)),
+            sync_offset_ms: Arc::new(AtomicI32::new(0)),
             last_frame_time: Instant::now(),
             frame_interval: Duration::from_micros(16667), // 60 FPS
             running: Arc::new(Mutex::new(false)),
@@ -0,0 +1,233 @@
+use std::fs;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use log::{info, error};
+use rand::Rng;
+use super::chat_source::wait_while_running;
+use super::comment::{ChatMessage, ChatSender, Platform, SuperChat, now_millis};
+
+/// 合成チャットで使う視聴者の人数
+const SYNTHETIC_VIEWER_COUNT: usize = 40;
+
+const SYNTHETIC_TEXTS: &[&str] = &[
+    "こんばんは！",
+    "待ってました",
+    "888888",
+    "草",
+    "今日のBGMいいね",
+    "初見です",
+    "かわいい",
+    "それなｗｗｗ",
+    "おつかれさまです",
+    "音量ちょうどいいです",
+];
+
+/// スーパーチャットの金額（円）
+const SUPER_CHAT_TIERS: &[f64] = &[100.0, 200.0, 500.0, 1000.0, 2000.0, 5000.0, 10000.0];
+
+#[derive(Default, PartialEq, Clone, Copy)]
+pub enum ReplayMode {
+    /// 録画済みの JSONL チャットログを再生
+    #[default]
+    Recorded,
+    /// ランダムなチャットを生成
+    Synthetic,
+}
+
+#[derive(Clone)]
+pub struct SyntheticChatSettings {
+    pub messages_per_minute: f32,
+    pub super_chat_ratio: f32,
+    pub member_ratio: f32,
+}
+
+impl Default for SyntheticChatSettings {
+    fn default() -> Self {
+        Self {
+            messages_per_minute: 30.0,
+            super_chat_ratio: 0.05,
+            member_ratio: 0.2,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ChatReplaySettings {
+    pub mode: ReplayMode,
+    pub log_path: String,
+    /// 再生速度の倍率（1.0 で元のタイミング）
+    pub speed: f32,
+    pub synthetic: SyntheticChatSettings,
+}
+
+impl Default for ChatReplaySettings {
+    fn default() -> Self {
+        Self {
+            mode: ReplayMode::default(),
+            log_path: String::new(),
+            speed: 1.0,
+            synthetic: SyntheticChatSettings::default(),
+        }
+    }
+}
+
+/// 配信せずにチャットを流すためのリプレイ／シミュレーター
+///
+/// ライブ取得と同じ `ChatSender` に書き込むため、下流の表示・フィルター・
+/// 読み上げは本番と同じ経路で動作する。
+pub struct ChatReplay {
+    running: Arc<AtomicBool>,
+    replay_thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Default for ChatReplay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChatReplay {
+    pub fn new() -> Self {
+        Self {
+            running: Arc::new(AtomicBool::new(false)),
+            replay_thread: None,
+        }
+    }
+
+    /// JSONL 形式のチャットログを読み込む（空行は無視）
+    pub fn load_log(path: &str) -> Result<Vec<ChatMessage>, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("チャットログを読み込めません: {}", e))?;
+
+        let mut messages = Vec::new();
+        for (line_no, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let message: ChatMessage = serde_json::from_str(line)
+                .map_err(|e| format!("{}行目の解析に失敗しました: {}", line_no + 1, e))?;
+            messages.push(message);
+        }
+        messages.sort_by_key(|m| m.timestamp_ms);
+        Ok(messages)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn start(&mut self, settings: &ChatReplaySettings, sender: ChatSender) -> Result<(), String> {
+        self.stop();
+
+        let speed = settings.speed.max(0.01);
+        let messages = match settings.mode {
+            ReplayMode::Recorded => Some(Self::load_log(&settings.log_path)?),
+            ReplayMode::Synthetic => None,
+        };
+        let synthetic = settings.synthetic.clone();
+        let running = Arc::clone(&self.running);
+        running.store(true, Ordering::SeqCst);
+
+        let handle = thread::spawn(move || {
+            match messages {
+                Some(messages) => {
+                    info!("チャットログのリプレイを開始: {}件 (x{})", messages.len(), speed);
+                    replay_recorded(messages, speed, &sender, &running);
+                }
+                None => {
+                    info!("合成チャットを開始: {}件/分 (x{})", synthetic.messages_per_minute, speed);
+                    replay_synthetic(&synthetic, speed, &sender, &running);
+                }
+            }
+            running.store(false, Ordering::SeqCst);
+            info!("チャットリプレイを終了しました");
+        });
+
+        self.replay_thread = Some(handle);
+        Ok(())
+    }
+
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.replay_thread.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for ChatReplay {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn replay_recorded(messages: Vec<ChatMessage>, speed: f32, sender: &ChatSender, running: &AtomicBool) {
+    let mut previous = messages.first().map(|m| m.timestamp_ms).unwrap_or(0);
+
+    for mut message in messages {
+        let gap = message.timestamp_ms.saturating_sub(previous);
+        previous = message.timestamp_ms;
+        if !wait_while_running(Duration::from_millis(gap).div_f32(speed), running) {
+            return;
+        }
+
+        // ライブのコメントと同じ流れで扱えるよう現在時刻に置き換える。視聴者統計には記録させない
+        message.timestamp_ms = now_millis();
+        message.simulated = true;
+        if sender.send(message).is_err() {
+            error!("チャットの受信側が閉じられています");
+            return;
+        }
+    }
+}
+
+fn replay_synthetic(settings: &SyntheticChatSettings, speed: f32, sender: &ChatSender, running: &AtomicBool) {
+    let mut rng = rand::thread_rng();
+    let mean_interval = 60.0 / settings.messages_per_minute.max(0.1) as f64;
+
+    // メンバーかどうかは視聴者ごとに固定する
+    let viewers: Vec<(String, String, bool)> = (0..SYNTHETIC_VIEWER_COUNT)
+        .map(|i| (
+            format!("synthetic-{}", i),
+            format!("テスト視聴者{}", i + 1),
+            rng.gen::<f32>() < settings.member_ratio,
+        ))
+        .collect();
+
+    let mut sequence = 0u64;
+    loop {
+        // ポアソン過程として投稿間隔を決める
+        let interval = -(1.0 - rng.gen::<f64>()).ln() * mean_interval;
+        if !wait_while_running(Duration::from_secs_f64(interval).div_f32(speed), running) {
+            return;
+        }
+
+        let (author_id, author_name, is_member) = &viewers[rng.gen_range(0..viewers.len())];
+        let super_chat = (rng.gen::<f32>() < settings.super_chat_ratio).then(|| SuperChat {
+            amount: SUPER_CHAT_TIERS[rng.gen_range(0..SUPER_CHAT_TIERS.len())],
+            currency: "JPY".to_string(),
+        });
+
+        sequence += 1;
+        let message = ChatMessage {
+            platform: Platform::YouTube,
+            id: format!("synthetic-msg-{}", sequence),
+            author_id: author_id.clone(),
+            author_name: author_name.clone(),
+            text: SYNTHETIC_TEXTS[rng.gen_range(0..SYNTHETIC_TEXTS.len())].to_string(),
+            timestamp_ms: now_millis(),
+            is_member: *is_member,
+            is_moderator: false,
+            super_chat,
+            membership: None,
+            simulated: true,
+        };
+
+        if sender.send(message).is_err() {
+            error!("チャットの受信側が閉じられています");
+            return;
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use super::comment::{ChatSender, Platform};
+
+/// 停止要求を確認する間隔
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SourceState {
+    Stopped,
+    Connecting,
+    Connected,
+    /// 切断され再接続を待っている（直前のエラー）
+    Reconnecting(String),
+    Error(String),
+}
+
+impl SourceState {
+    pub fn label(&self) -> String {
+        match self {
+            SourceState::Stopped => "停止中".to_string(),
+            SourceState::Connecting => "接続中...".to_string(),
+            SourceState::Connected => "接続済み".to_string(),
+            SourceState::Reconnecting(e) => format!("再接続待ち: {}", e),
+            SourceState::Error(e) => format!("エラー: {}", e),
+        }
+    }
+}
+
+/// コメントの取得元。取得したコメントは共通モデルに変換して `ChatSender` に流す
+pub trait ChatSource {
+    fn platform(&self) -> Platform;
+
+    /// UI に表示する取得元の名前（チャンネル名など）
+    fn name(&self) -> String;
+
+    fn start(&mut self, sender: ChatSender) -> Result<(), String>;
+
+    fn stop(&mut self);
+
+    fn state(&self) -> SourceState;
+}
+
+/// 停止要求を確認しながら待機する。停止された場合は false を返す
+pub(crate) fn wait_while_running(duration: Duration, running: &AtomicBool) -> bool {
+    let mut remaining = duration;
+    while remaining > Duration::ZERO {
+        if !running.load(Ordering::SeqCst) {
+            return false;
+        }
+        let step = remaining.min(POLL_INTERVAL);
+        thread::sleep(step);
+        remaining -= step;
+    }
+    running.load(Ordering::SeqCst)
+}
@@ -0,0 +1,269 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use super::comment::{ChatMessage, Platform};
+
+pub const CHAT_TRIGGERS_PATH: &str = "config/chat_triggers.json";
+/// 発動・拒否の記録（1 行 1 件の JSON）
+pub const TRIGGER_LOG_PATH: &str = "log/chat_triggers.jsonl";
+
+/// UI に表示する最近の記録の件数
+const RECENT_LOG_LEN: usize = 100;
+
+/// コメントのどこに反応するか
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TriggerKind {
+    /// コメントに含まれる
+    #[default]
+    Keyword,
+    /// 最初の単語が一致する（`!pon` など）
+    Command,
+    /// 一定額以上のスーパーチャット・Bits
+    SuperChat,
+}
+
+impl TriggerKind {
+    pub const ALL: [TriggerKind; 3] = [TriggerKind::Keyword, TriggerKind::Command, TriggerKind::SuperChat];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TriggerKind::Keyword => "キーワード",
+            TriggerKind::Command => "コマンド",
+            TriggerKind::SuperChat => "スーパーチャット",
+        }
+    }
+}
+
+/// コメントと効果音の対応
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatTrigger {
+    pub enabled: bool,
+    pub kind: TriggerKind,
+    /// キーワード・コマンド（大文字・小文字は区別しない）
+    pub pattern: String,
+    /// スーパーチャットの最低額
+    pub min_amount: f64,
+    /// 通貨コード（空欄はすべての通貨）
+    pub currency: String,
+    /// `Soundboard::effects` の番号
+    pub effect: usize,
+    pub members_only: bool,
+}
+
+impl Default for ChatTrigger {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            kind: TriggerKind::default(),
+            pattern: String::new(),
+            min_amount: 0.0,
+            currency: String::new(),
+            effect: 0,
+            members_only: false,
+        }
+    }
+}
+
+impl ChatTrigger {
+    pub fn matches(&self, message: &ChatMessage) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let pattern = self.pattern.trim().to_lowercase();
+        match self.kind {
+            TriggerKind::Keyword => !pattern.is_empty() && message.text.to_lowercase().contains(&pattern),
+            TriggerKind::Command => {
+                !pattern.is_empty() && message.text.split_whitespace().next().is_some_and(|word| word.to_lowercase() == pattern)
+            }
+            TriggerKind::SuperChat => message.super_chat.as_ref().is_some_and(|super_chat| {
+                let currency = self.currency.trim();
+                super_chat.amount >= self.min_amount && (currency.is_empty() || super_chat.currency.eq_ignore_ascii_case(currency))
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatTriggerSettings {
+    pub enabled: bool,
+    pub triggers: Vec<ChatTrigger>,
+    /// 誰かが鳴らしてから、次に誰かが鳴らせるまでの秒数
+    pub global_cooldown_secs: u32,
+    /// 同じ視聴者が次に鳴らせるまでの秒数
+    pub user_cooldown_secs: u32,
+    /// 同時に鳴らせる効果音の数（配信者が鳴らしたものも数える）
+    pub max_concurrent: usize,
+    /// スーパーチャットはクールダウンを無視する
+    pub super_chat_bypasses_cooldown: bool,
+}
+
+impl Default for ChatTriggerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            triggers: Vec::new(),
+            global_cooldown_secs: 10,
+            user_cooldown_secs: 60,
+            max_concurrent: 3,
+            super_chat_bypasses_cooldown: true,
+        }
+    }
+}
+
+impl ChatTriggerSettings {
+    /// コメントに一致するトリガー。スーパーチャットは最も高い段階を、それ以外は先に登録したものを選ぶ
+    pub fn find(&self, message: &ChatMessage) -> Option<&ChatTrigger> {
+        let mut matched = self.triggers.iter().filter(|trigger| trigger.matches(message));
+        let first = matched.next()?;
+        Some(matched.fold(first, |best, trigger| {
+            let rank = |trigger: &ChatTrigger| (trigger.kind == TriggerKind::SuperChat, trigger.min_amount);
+            if rank(trigger) > rank(best) { trigger } else { best }
+        }))
+    }
+
+    /// 削除した効果音を指すトリガーを外し、後ろの番号を詰める
+    pub fn effect_removed(&mut self, index: usize) {
+        self.triggers.retain(|trigger| trigger.effect != index);
+        for trigger in &mut self.triggers {
+            if trigger.effect > index {
+                trigger.effect -= 1;
+            }
+        }
+    }
+}
+
+/// トリガーに一致したコメントの扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TriggerOutcome {
+    Played,
+    GlobalCooldown,
+    UserCooldown,
+    MembersOnly,
+    TooManySounds,
+    /// スパム判定で読み上げを止めたコメント
+    Spam,
+    /// ファイルを開けないなど
+    Failed,
+}
+
+impl TriggerOutcome {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TriggerOutcome::Played => "再生",
+            TriggerOutcome::GlobalCooldown => "全体クールダウン中",
+            TriggerOutcome::UserCooldown => "ユーザークールダウン中",
+            TriggerOutcome::MembersOnly => "メンバー限定",
+            TriggerOutcome::TooManySounds => "同時再生数の上限",
+            TriggerOutcome::Spam => "スパム",
+            TriggerOutcome::Failed => "再生失敗",
+        }
+    }
+}
+
+/// 配信後に荒らしを確認するための記録
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerLogEntry {
+    /// ローカル時刻（RFC 3339）
+    pub time: String,
+    pub platform: Platform,
+    pub author_id: String,
+    pub author_name: String,
+    pub text: String,
+    pub effect: String,
+    pub outcome: TriggerOutcome,
+}
+
+/// クールダウンの状態と記録
+pub struct ChatTriggerState {
+    last_global: Option<Instant>,
+    last_by_user: HashMap<(Platform, String), Instant>,
+    recent: VecDeque<TriggerLogEntry>,
+}
+
+impl ChatTriggerState {
+    pub fn new() -> Self {
+        Self {
+            last_global: None,
+            last_by_user: HashMap::new(),
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// 一致したトリガーで鳴らしてよいかを判定する。`Played` の場合はクールダウンを始める
+    pub fn check(
+        &mut self,
+        message: &ChatMessage,
+        trigger: &ChatTrigger,
+        settings: &ChatTriggerSettings,
+        is_spam: bool,
+        playing: usize,
+    ) -> TriggerOutcome {
+        let now = Instant::now();
+        let user = (message.platform, message.author_id.clone());
+        let cooling = |last: Option<&Instant>, secs: u32| last.is_some_and(|last| now.duration_since(*last) < Duration::from_secs(secs as u64));
+        let bypass = settings.super_chat_bypasses_cooldown && message.super_chat.is_some();
+
+        let outcome = if is_spam {
+            TriggerOutcome::Spam
+        } else if trigger.members_only && !message.is_member {
+            TriggerOutcome::MembersOnly
+        } else if !bypass && cooling(self.last_global.as_ref(), settings.global_cooldown_secs) {
+            TriggerOutcome::GlobalCooldown
+        } else if !bypass && cooling(self.last_by_user.get(&user), settings.user_cooldown_secs) {
+            TriggerOutcome::UserCooldown
+        } else if playing >= settings.max_concurrent {
+            TriggerOutcome::TooManySounds
+        } else {
+            TriggerOutcome::Played
+        };
+
+        if outcome == TriggerOutcome::Played {
+            self.last_global = Some(now);
+            self.last_by_user.insert(user, now);
+        }
+        outcome
+    }
+
+    /// 記録をファイルに追記し、UI 用に保持する
+    pub fn record(&mut self, message: &ChatMessage, effect: &str, outcome: TriggerOutcome) {
+        let entry = TriggerLogEntry {
+            time: chrono::Local::now().to_rfc3339(),
+            platform: message.platform,
+            author_id: message.author_id.clone(),
+            author_name: message.author_name.clone(),
+            text: message.text.clone(),
+            effect: effect.to_string(),
+            outcome,
+        };
+        if let Err(e) = append_log(&entry) {
+            log::error!("効果音の記録を書き込めません: {}", e);
+        }
+        self.recent.push_back(entry);
+        if self.recent.len() > RECENT_LOG_LEN {
+            self.recent.pop_front();
+        }
+    }
+
+    /// 最近の記録（古い順）
+    pub fn recent(&self) -> &VecDeque<TriggerLogEntry> {
+        &self.recent
+    }
+}
+
+fn append_log(entry: &TriggerLogEntry) -> Result<(), String> {
+    if let Some(parent) = Path::new(TRIGGER_LOG_PATH).parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("ディレクトリの作成に失敗: {}", e))?;
+    }
+    let line = serde_json::to_string(entry).map_err(|e| format!("記録のシリアライズに失敗: {}", e))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(TRIGGER_LOG_PATH)
+        .map_err(|e| format!("{} を開けません: {}", TRIGGER_LOG_PATH, e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("{} への書き込みに失敗: {}", TRIGGER_LOG_PATH, e))
+}
@@ -1,3 +1,9 @@
+use std::collections::VecDeque;
+use std::sync::{mpsc, Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use super::spam_filter::SpamVerdict;
+use super::viewer_stats::ViewerVisit;
+
 #[allow(dead_code)]
 pub struct CommentConfig {
     pub filter: FilterSettings,
@@ -29,4 +35,102 @@ pub struct DisplaySettings {
     pub show_username: bool,
     pub show_member_icon: bool,
     pub color_member_names: bool,
-} 
\ No newline at end of file
+}
+
+/// コメントの取得元プラットフォーム
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Platform {
+    #[default]
+    YouTube,
+    Twitch,
+}
+
+impl Platform {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Platform::YouTube => "YouTube",
+            Platform::Twitch => "Twitch",
+        }
+    }
+}
+
+/// チャット取得元に依存しない共通のコメントモデル
+///
+/// 録画したチャットログ（JSONL）も 1 行 1 件このまま保存する。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    #[serde(default)]
+    pub platform: Platform,
+    pub id: String,
+    pub author_id: String,
+    pub author_name: String,
+    pub text: String,
+    /// 投稿時刻（UNIX エポックからのミリ秒）
+    pub timestamp_ms: u64,
+    #[serde(default)]
+    pub is_member: bool,
+    #[serde(default)]
+    pub is_moderator: bool,
+    /// スーパーチャット（Twitch の Bits もここに入る）
+    #[serde(default)]
+    pub super_chat: Option<SuperChat>,
+    /// メンバーシップ加入・継続（Twitch のサブスクもここに入る）
+    #[serde(default)]
+    pub membership: Option<Membership>,
+    /// チャットリプレイ・合成チャットのコメント（視聴者統計に記録しない）。ログには保存しない
+    #[serde(default, skip_serializing)]
+    pub simulated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuperChat {
+    pub amount: f64,
+    /// 通貨コード。Twitch の Bits は "BITS"
+    pub currency: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Membership {
+    /// 継続月数（新規加入は 1）
+    pub months: u32,
+    /// ギフトによる加入かどうか
+    #[serde(default)]
+    pub is_gift: bool,
+}
+
+/// ライブ取得・リプレイなど全てのチャット取得元が書き込むチャネル
+pub type ChatSender = mpsc::Sender<ChatMessage>;
+pub type ChatReceiver = mpsc::Receiver<ChatMessage>;
+
+pub fn chat_channel() -> (ChatSender, ChatReceiver) {
+    mpsc::channel()
+}
+
+/// 現在時刻を UNIX エポックからのミリ秒で返す
+pub fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 視聴者統計から付与するバッジ
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViewerBadge {
+    /// 今回の配信が初参加
+    FirstTime,
+    /// 常連（参加回数がしきい値以上）
+    Regular,
+}
+
+/// 取り込み処理を終えてコメント欄に表示するコメント
+#[derive(Debug, Clone)]
+pub struct ReceivedComment {
+    pub message: ChatMessage,
+    pub visit: Option<ViewerVisit>,
+    pub badge: Option<ViewerBadge>,
+    pub spam: SpamVerdict,
+}
+
+/// コメント欄に表示するコメントの共有バッファ
+pub type CommentFeed = Arc<Mutex<VecDeque<ReceivedComment>>>;
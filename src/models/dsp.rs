@@ -0,0 +1,152 @@
+/// デシベルを振幅の倍率に変換する
+pub fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// 振幅をデシベルに変換する（無音は -120dB）
+pub fn gain_to_db(gain: f32) -> f32 {
+    if gain <= 1e-6 {
+        -120.0
+    } else {
+        20.0 * gain.log10()
+    }
+}
+
+/// 時定数 `ms` の一次平滑化の係数（1 サンプルごとに残る割合）
+pub fn smoothing_coefficient(ms: f32, sample_rate: u32) -> f32 {
+    if ms <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / (ms * 0.001 * sample_rate as f32)).exp()
+    }
+}
+
+/// 2 次の IIR フィルター（Audio EQ Cookbook の係数）
+#[derive(Debug, Clone, Default)]
+pub struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Biquad {
+    /// a0 で正規化済みの係数から作る
+    pub fn from_coefficients(b0: f32, b1: f32, b2: f32, a1: f32, a2: f32) -> Self {
+        Self { b0, b1, b2, a1, a2, ..Default::default() }
+    }
+
+    pub fn high_pass(cutoff: f32, q: f32, sample_rate: u32) -> Self {
+        let (cos, alpha) = Self::prewarp(cutoff, q, sample_rate);
+        Self::normalized(
+            (1.0 + cos) / 2.0,
+            -(1.0 + cos),
+            (1.0 + cos) / 2.0,
+            1.0 + alpha,
+            -2.0 * cos,
+            1.0 - alpha,
+        )
+    }
+
+    pub fn low_pass(cutoff: f32, q: f32, sample_rate: u32) -> Self {
+        let (cos, alpha) = Self::prewarp(cutoff, q, sample_rate);
+        Self::normalized(
+            (1.0 - cos) / 2.0,
+            1.0 - cos,
+            (1.0 - cos) / 2.0,
+            1.0 + alpha,
+            -2.0 * cos,
+            1.0 - alpha,
+        )
+    }
+
+    /// ピーキング（ベル型）
+    pub fn peaking(frequency: f32, gain_db: f32, q: f32, sample_rate: u32) -> Self {
+        let (cos, alpha) = Self::prewarp(frequency, q, sample_rate);
+        let a = 10f32.powf(gain_db / 40.0);
+        Self::normalized(
+            1.0 + alpha * a,
+            -2.0 * cos,
+            1.0 - alpha * a,
+            1.0 + alpha / a,
+            -2.0 * cos,
+            1.0 - alpha / a,
+        )
+    }
+
+    pub fn low_shelf(frequency: f32, gain_db: f32, q: f32, sample_rate: u32) -> Self {
+        let (cos, alpha) = Self::prewarp(frequency, q, sample_rate);
+        let a = 10f32.powf(gain_db / 40.0);
+        let beta = 2.0 * a.sqrt() * alpha;
+        Self::normalized(
+            a * ((a + 1.0) - (a - 1.0) * cos + beta),
+            2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+            a * ((a + 1.0) - (a - 1.0) * cos - beta),
+            (a + 1.0) + (a - 1.0) * cos + beta,
+            -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+            (a + 1.0) + (a - 1.0) * cos - beta,
+        )
+    }
+
+    pub fn high_shelf(frequency: f32, gain_db: f32, q: f32, sample_rate: u32) -> Self {
+        let (cos, alpha) = Self::prewarp(frequency, q, sample_rate);
+        let a = 10f32.powf(gain_db / 40.0);
+        let beta = 2.0 * a.sqrt() * alpha;
+        Self::normalized(
+            a * ((a + 1.0) + (a - 1.0) * cos + beta),
+            -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+            a * ((a + 1.0) + (a - 1.0) * cos - beta),
+            (a + 1.0) - (a - 1.0) * cos + beta,
+            2.0 * ((a - 1.0) - (a + 1.0) * cos),
+            (a + 1.0) - (a - 1.0) * cos - beta,
+        )
+    }
+
+    fn prewarp(frequency: f32, q: f32, sample_rate: u32) -> (f32, f32) {
+        let nyquist = sample_rate as f32 / 2.0;
+        let omega = 2.0 * std::f32::consts::PI * frequency.clamp(10.0, nyquist * 0.99) / sample_rate as f32;
+        (omega.cos(), omega.sin() / (2.0 * q.max(0.1)))
+    }
+
+    fn normalized(b0: f32, b1: f32, b2: f32, a0: f32, a1: f32, a2: f32) -> Self {
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+            ..Default::default()
+        }
+    }
+
+    /// 内部状態を保ったまま係数だけを差し替える（パラメーター変更時のノイズを防ぐ）
+    pub fn set_coefficients(&mut self, other: &Biquad) {
+        self.b0 = other.b0;
+        self.b1 = other.b1;
+        self.b2 = other.b2;
+        self.a1 = other.a1;
+        self.a2 = other.a2;
+    }
+
+    /// 内部状態を無音にする
+    pub fn reset(&mut self) {
+        self.x1 = 0.0;
+        self.x2 = 0.0;
+        self.y1 = 0.0;
+        self.y2 = 0.0;
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        let output = self.b0 * input + self.b1 * self.x1 + self.b2 * self.x2 - self.a1 * self.y1 - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = input;
+        self.y2 = self.y1;
+        self.y1 = output;
+        output
+    }
+}
@@ -0,0 +1,350 @@
+use serde::{Deserialize, Serialize};
+use super::audio_engine::{ENGINE_CHANNELS, ENGINE_SAMPLE_RATE};
+use super::dsp::Biquad;
+
+/// EQ の帯域の最大数
+pub const MAX_EQ_BANDS: usize = 8;
+/// ディレイの最大の長さ
+pub const MAX_DELAY_MS: f32 = 2000.0;
+
+/// Freeverb のコムフィルター・オールパスフィルターの長さ（44.1kHz でのサンプル数）
+const COMB_TUNING: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+const ALLPASS_TUNING: [usize; 4] = [556, 441, 341, 225];
+/// 右チャンネルの長さのずれ（左右の響きを無相関にする）
+const STEREO_SPREAD: usize = 23;
+const REVERB_INPUT_GAIN: f32 = 0.015;
+const REVERB_WET_GAIN: f32 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EqBandKind {
+    Peaking,
+    LowShelf,
+    HighShelf,
+    LowPass,
+    HighPass,
+}
+
+impl EqBandKind {
+    pub const ALL: [EqBandKind; 5] = [
+        EqBandKind::Peaking,
+        EqBandKind::LowShelf,
+        EqBandKind::HighShelf,
+        EqBandKind::LowPass,
+        EqBandKind::HighPass,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EqBandKind::Peaking => "ピーキング",
+            EqBandKind::LowShelf => "ローシェルフ",
+            EqBandKind::HighShelf => "ハイシェルフ",
+            EqBandKind::LowPass => "ローパス",
+            EqBandKind::HighPass => "ハイパス",
+        }
+    }
+
+    /// ゲインを使う種類か（ローパス・ハイパスはゲインを使わない）
+    pub fn has_gain(&self) -> bool {
+        !matches!(self, EqBandKind::LowPass | EqBandKind::HighPass)
+    }
+}
+
+/// パラメトリック EQ の 1 帯域
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EqBand {
+    pub enabled: bool,
+    pub kind: EqBandKind,
+    pub frequency_hz: f32,
+    pub gain_db: f32,
+    pub q: f32,
+}
+
+impl EqBand {
+    pub fn new(kind: EqBandKind, frequency_hz: f32) -> Self {
+        Self {
+            enabled: true,
+            kind,
+            frequency_hz,
+            gain_db: 0.0,
+            q: std::f32::consts::FRAC_1_SQRT_2,
+        }
+    }
+
+    fn filter(&self) -> Biquad {
+        match self.kind {
+            EqBandKind::Peaking => Biquad::peaking(self.frequency_hz, self.gain_db, self.q, ENGINE_SAMPLE_RATE),
+            EqBandKind::LowShelf => Biquad::low_shelf(self.frequency_hz, self.gain_db, self.q, ENGINE_SAMPLE_RATE),
+            EqBandKind::HighShelf => Biquad::high_shelf(self.frequency_hz, self.gain_db, self.q, ENGINE_SAMPLE_RATE),
+            EqBandKind::LowPass => Biquad::low_pass(self.frequency_hz, self.q, ENGINE_SAMPLE_RATE),
+            EqBandKind::HighPass => Biquad::high_pass(self.frequency_hz, self.q, ENGINE_SAMPLE_RATE),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EqSettings {
+    pub enabled: bool,
+    pub bands: Vec<EqBand>,
+}
+
+impl Default for EqSettings {
+    /// 低域・中域・高域の 3 帯域（初期状態はフラット）
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            bands: vec![
+                EqBand::new(EqBandKind::LowShelf, 120.0),
+                EqBand::new(EqBandKind::Peaking, 1000.0),
+                EqBand::new(EqBandKind::HighShelf, 8000.0),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DelaySettings {
+    pub enabled: bool,
+    pub time_ms: f32,
+    /// 遅らせた音を入力に戻す割合（0.0 〜 0.95）
+    pub feedback: f32,
+    /// 0.0 は原音のみ、1.0 はエフェクト音のみ
+    pub mix: f32,
+}
+
+impl Default for DelaySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            time_ms: 350.0,
+            feedback: 0.35,
+            mix: 0.25,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReverbSettings {
+    pub enabled: bool,
+    /// 0.0 〜 1.0。大きいほど残響が長い
+    pub room_size: f32,
+    /// 0.0 〜 1.0。大きいほど残響の高域が早く減衰する
+    pub damping: f32,
+    /// 0.0 は原音のみ、1.0 はエフェクト音のみ
+    pub mix: f32,
+}
+
+impl Default for ReverbSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            room_size: 0.6,
+            damping: 0.4,
+            mix: 0.2,
+        }
+    }
+}
+
+/// バスごとのエフェクトチェーンの設定。EQ → ディレイ → リバーブの順に処理する
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EffectsChainSettings {
+    /// チェーン全体を素通しにする（かけた音と原音の聴き比べ用）
+    pub bypass: bool,
+    pub eq: EqSettings,
+    pub delay: DelaySettings,
+    pub reverb: ReverbSettings,
+}
+
+/// フィードバック付きのステレオディレイ
+#[derive(Default)]
+struct StereoDelay {
+    /// 最大の長さの分だけ確保する（有効にしたときに確保する）
+    buffer: Vec<[f32; 2]>,
+    position: usize,
+}
+
+impl StereoDelay {
+    fn process(&mut self, samples: &mut [f32], settings: &DelaySettings) {
+        if self.buffer.is_empty() {
+            self.buffer = vec![[0.0; 2]; (MAX_DELAY_MS * 0.001 * ENGINE_SAMPLE_RATE as f32) as usize + 1];
+        }
+        let length = self.buffer.len();
+        let delay = ((settings.time_ms.clamp(1.0, MAX_DELAY_MS) * 0.001 * ENGINE_SAMPLE_RATE as f32) as usize).clamp(1, length - 1);
+        let feedback = settings.feedback.clamp(0.0, 0.95);
+        let mix = settings.mix.clamp(0.0, 1.0);
+
+        for frame in samples.chunks_mut(ENGINE_CHANNELS) {
+            let delayed = self.buffer[(self.position + length - delay) % length];
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let channel = channel.min(1);
+                let input = *sample;
+                self.buffer[self.position][channel] = input + delayed[channel] * feedback;
+                *sample = input * (1.0 - mix) + delayed[channel] * mix;
+            }
+            self.position = (self.position + 1) % length;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.position = 0;
+    }
+}
+
+/// 高域を減衰させながら戻すコムフィルター
+struct Comb {
+    buffer: Vec<f32>,
+    position: usize,
+    filter_store: f32,
+}
+
+impl Comb {
+    fn new(length: usize) -> Self {
+        Self { buffer: vec![0.0; length], position: 0, filter_store: 0.0 }
+    }
+
+    fn process(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+        let output = self.buffer[self.position];
+        self.filter_store = output * (1.0 - damping) + self.filter_store * damping;
+        self.buffer[self.position] = input + self.filter_store * feedback;
+        self.position = (self.position + 1) % self.buffer.len();
+        output
+    }
+}
+
+struct Allpass {
+    buffer: Vec<f32>,
+    position: usize,
+}
+
+impl Allpass {
+    fn new(length: usize) -> Self {
+        Self { buffer: vec![0.0; length], position: 0 }
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let buffered = self.buffer[self.position];
+        self.buffer[self.position] = input + buffered * 0.5;
+        self.position = (self.position + 1) % self.buffer.len();
+        buffered - input
+    }
+}
+
+/// Freeverb 方式の簡易リバーブ（並列のコムフィルターと直列のオールパスフィルター）
+#[derive(Default)]
+struct Reverb {
+    /// 左右それぞれのフィルター（有効にしたときに作る）
+    channels: Vec<(Vec<Comb>, Vec<Allpass>)>,
+}
+
+impl Reverb {
+    fn build(&mut self) {
+        let scale = |length: usize| length * ENGINE_SAMPLE_RATE as usize / 44_100;
+        self.channels = [0, STEREO_SPREAD]
+            .into_iter()
+            .map(|spread| {
+                (
+                    COMB_TUNING.iter().map(|&length| Comb::new(scale(length + spread))).collect(),
+                    ALLPASS_TUNING.iter().map(|&length| Allpass::new(scale(length + spread))).collect(),
+                )
+            })
+            .collect();
+    }
+
+    fn process(&mut self, samples: &mut [f32], settings: &ReverbSettings) {
+        if self.channels.is_empty() {
+            self.build();
+        }
+        let feedback = 0.7 + settings.room_size.clamp(0.0, 1.0) * 0.28;
+        let damping = settings.damping.clamp(0.0, 1.0) * 0.4;
+        let mix = settings.mix.clamp(0.0, 1.0);
+
+        for frame in samples.chunks_mut(ENGINE_CHANNELS) {
+            let input = (frame[0] + frame[ENGINE_CHANNELS - 1]) * REVERB_INPUT_GAIN;
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let (combs, allpasses) = &mut self.channels[channel.min(1)];
+                let mut wet: f32 = combs.iter_mut().map(|comb| comb.process(input, feedback, damping)).sum();
+                for allpass in allpasses.iter_mut() {
+                    wet = allpass.process(wet);
+                }
+                *sample = *sample * (1.0 - mix) + wet * REVERB_WET_GAIN * mix;
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.channels.clear();
+    }
+}
+
+/// バスにかけるエフェクトチェーン（エンジン形式のステレオ）
+///
+/// 無効なエフェクトはバッファを解放し、再び有効にしたときに前の残響が鳴らないようにする
+#[derive(Default)]
+pub struct EffectsChain {
+    /// 帯域ごとの左右のフィルター
+    eq: Vec<[Biquad; 2]>,
+    /// `eq` の係数を計算したときの帯域の設定
+    eq_bands: Vec<EqBand>,
+    delay: StereoDelay,
+    reverb: Reverb,
+}
+
+impl EffectsChain {
+    pub fn process(&mut self, samples: &mut [f32], settings: &EffectsChainSettings) {
+        let eq_active = !settings.bypass && settings.eq.enabled;
+        if eq_active {
+            self.update_eq(&settings.eq.bands);
+            for (band, filters) in settings.eq.bands.iter().zip(self.eq.iter_mut()) {
+                if !band.enabled {
+                    continue;
+                }
+                for frame in samples.chunks_mut(ENGINE_CHANNELS) {
+                    for (channel, sample) in frame.iter_mut().enumerate() {
+                        *sample = filters[channel.min(1)].process(*sample);
+                    }
+                }
+            }
+        } else if !self.eq.is_empty() {
+            self.eq.clear();
+            self.eq_bands.clear();
+        }
+
+        if !settings.bypass && settings.delay.enabled {
+            self.delay.process(samples, &settings.delay);
+        } else {
+            self.delay.reset();
+        }
+
+        if !settings.bypass && settings.reverb.enabled {
+            self.reverb.process(samples, &settings.reverb);
+        } else {
+            self.reverb.reset();
+        }
+    }
+
+    /// 変わった帯域だけ係数を計算し直す。帯域の数が変わった場合は作り直す
+    fn update_eq(&mut self, bands: &[EqBand]) {
+        if self.eq_bands.len() != bands.len() {
+            self.eq = bands.iter().map(|band| [band.filter(), band.filter()]).collect();
+            self.eq_bands = bands.to_vec();
+            return;
+        }
+        for ((band, previous), filters) in bands.iter().zip(self.eq_bands.iter_mut()).zip(self.eq.iter_mut()) {
+            if band == previous {
+                continue;
+            }
+            let coefficients = band.filter();
+            for filter in filters.iter_mut() {
+                if band.kind != previous.kind || !previous.enabled {
+                    filter.reset();
+                }
+                filter.set_coefficients(&coefficients);
+            }
+            *previous = band.clone();
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use std::io::{Seek, SeekFrom, Write};
+
+/// 1 フレームのサンプル数（チャンネルあたり）
+const BLOCK_SIZE: usize = 4096;
+/// 4 ビットの Rice パラメーターで使える最大値（15 はエスケープ）
+const MAX_RICE_PARAMETER: u32 = 14;
+/// STREAMINFO のサンプルレート〜総サンプル数の位置（"fLaC" 4 バイト + ブロックヘッダー 4 バイト + 10 バイト）
+const STREAMINFO_TOTAL_OFFSET: u64 = 18;
+
+/// ビット単位で書き込むバッファ（MSB から詰める）
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    accumulator: u64,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u64, bits: u32) {
+        for shift in (0..bits).rev() {
+            self.accumulator = (self.accumulator << 1) | ((value >> shift) & 1);
+            self.bits += 1;
+            if self.bits == 8 {
+                self.bytes.push(self.accumulator as u8);
+                self.accumulator = 0;
+                self.bits = 0;
+            }
+        }
+    }
+
+    fn write_signed(&mut self, value: i64, bits: u32) {
+        self.write(value as u64 & ((1u64 << bits) - 1), bits);
+    }
+
+    /// `quotient` 個の 0 と終端の 1 を書く
+    fn write_unary(&mut self, quotient: u32) {
+        for _ in 0..quotient {
+            self.write(0, 1);
+        }
+        self.write(1, 1);
+    }
+
+    fn align(&mut self) {
+        if self.bits > 0 {
+            self.write(0, 8 - self.bits);
+        }
+    }
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |mut crc, &byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |mut crc, &byte| {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x8005 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+/// 固定予測（0〜4 次）の残差
+fn fixed_residual(samples: &[i64], order: usize) -> Vec<i64> {
+    (order..samples.len())
+        .map(|i| {
+            let s = |back: usize| samples[i - back];
+            match order {
+                0 => s(0),
+                1 => s(0) - s(1),
+                2 => s(0) - 2 * s(1) + s(2),
+                3 => s(0) - 3 * s(1) + 3 * s(2) - s(3),
+                _ => s(0) - 4 * s(1) + 6 * s(2) - 4 * s(3) + s(4),
+            }
+        })
+        .collect()
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// 残差を Rice 符号化したときのビット数が最小になるパラメーターと、そのビット数
+fn rice_parameter(residual: &[i64]) -> (u32, u64) {
+    let bits = |k: u32| residual.iter().map(|&r| (zigzag(r) >> k) + 1 + k as u64).sum::<u64>();
+    let mean = residual.iter().map(|&r| zigzag(r)).sum::<u64>() / residual.len().max(1) as u64;
+    // 平均から見積もった値の前後だけを調べる
+    let guess = if mean == 0 { 0 } else { (64 - mean.leading_zeros()).min(MAX_RICE_PARAMETER) };
+    (guess.saturating_sub(1)..=(guess + 1).min(MAX_RICE_PARAMETER))
+        .map(|k| (k, bits(k)))
+        .min_by_key(|&(_, bits)| bits)
+        .unwrap_or((0, u64::MAX))
+}
+
+/// ストリーミングで書き込む FLAC エンコーダー（固定予測・Rice 符号化、ステレオの各チャンネルを独立に符号化）
+///
+/// MD5 は計算せず「不明」として書き、総サンプル数は `finish` でヘッダーに書き戻す
+pub struct FlacEncoder<W: Write + Seek> {
+    writer: W,
+    sample_rate: u32,
+    channels: usize,
+    bits_per_sample: u32,
+    /// チャンネルごとの未符号化のサンプル
+    pending: Vec<Vec<i64>>,
+    frame_number: u64,
+    total_frames: u64,
+}
+
+impl<W: Write + Seek> FlacEncoder<W> {
+    /// `bits_per_sample` は 16 または 24
+    pub fn new(mut writer: W, sample_rate: u32, channels: usize, bits_per_sample: u32) -> Result<Self, String> {
+        if !(1..=8).contains(&channels) || !matches!(bits_per_sample, 16 | 24) {
+            return Err(format!("未対応の形式です ({}ch, {}bit)", channels, bits_per_sample));
+        }
+        let mut header = BitWriter::default();
+        header.bytes.extend_from_slice(b"fLaC");
+        // 最後のメタデータブロック・STREAMINFO・34 バイト
+        header.write(1, 1);
+        header.write(0, 7);
+        header.write(34, 24);
+        header.write(BLOCK_SIZE as u64, 16);
+        header.write(BLOCK_SIZE as u64, 16);
+        header.write(0, 24);
+        header.write(0, 24);
+        header.write(sample_rate as u64, 20);
+        header.write(channels as u64 - 1, 3);
+        header.write(bits_per_sample as u64 - 1, 5);
+        header.write(0, 36);
+        header.write(0, 64);
+        header.write(0, 64);
+        writer.write_all(&header.bytes).map_err(|e| format!("FLAC の書き込みに失敗: {}", e))?;
+
+        Ok(Self {
+            writer,
+            sample_rate,
+            channels,
+            bits_per_sample,
+            pending: vec![Vec::with_capacity(BLOCK_SIZE); channels],
+            frame_number: 0,
+            total_frames: 0,
+        })
+    }
+
+    /// -1.0 〜 1.0 のインターリーブ PCM を追加する。サンプル数はチャンネル数の倍数にする
+    pub fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        if !samples.len().is_multiple_of(self.channels) {
+            return Err(format!("サンプル数 {} が {}ch のフレームに揃っていません", samples.len(), self.channels));
+        }
+        let max = ((1i64 << (self.bits_per_sample - 1)) - 1) as f32;
+        for frame in samples.chunks(self.channels) {
+            for (channel, &sample) in frame.iter().enumerate() {
+                self.pending[channel].push((sample.clamp(-1.0, 1.0) * max).round() as i64);
+            }
+            if self.pending[0].len() == BLOCK_SIZE {
+                self.encode_frame()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 残りを書き出し、総サンプル数をヘッダーに書き戻す
+    pub fn finish(mut self) -> Result<W, String> {
+        if !self.pending[0].is_empty() {
+            self.encode_frame()?;
+        }
+        // サンプルレート（20bit）・チャンネル数（3bit）・ビット数（5bit）・総サンプル数（36bit）
+        let packed = (self.sample_rate as u64) << 44
+            | (self.channels as u64 - 1) << 41
+            | (self.bits_per_sample as u64 - 1) << 36
+            | (self.total_frames & 0xF_FFFF_FFFF);
+        let io = |e: std::io::Error| format!("FLAC の書き込みに失敗: {}", e);
+        self.writer.seek(SeekFrom::Start(STREAMINFO_TOTAL_OFFSET)).map_err(io)?;
+        self.writer.write_all(&packed.to_be_bytes()).map_err(io)?;
+        self.writer.seek(SeekFrom::End(0)).map_err(io)?;
+        self.writer.flush().map_err(io)?;
+        Ok(self.writer)
+    }
+
+    fn encode_frame(&mut self) -> Result<(), String> {
+        let block_size = self.pending[0].len();
+        let mut frame = BitWriter::default();
+
+        frame.write(0b11_1111_1111_1110, 14);
+        frame.write(0, 1);
+        // 固定ブロックサイズ
+        frame.write(0, 1);
+        // 4096 以外（最後の端数）はヘッダーの末尾に 16bit で書く
+        frame.write(if block_size == BLOCK_SIZE { 0b1100 } else { 0b0111 }, 4);
+        // サンプルレートは STREAMINFO を参照する
+        frame.write(0, 4);
+        frame.write(self.channels as u64 - 1, 4);
+        frame.write(if self.bits_per_sample == 16 { 0b100 } else { 0b110 }, 3);
+        frame.write(0, 1);
+        write_utf8_number(&mut frame, self.frame_number);
+        if block_size != BLOCK_SIZE {
+            frame.write(block_size as u64 - 1, 16);
+        }
+        let crc = crc8(&frame.bytes);
+        frame.write(crc as u64, 8);
+
+        for channel in 0..self.channels {
+            let samples = std::mem::take(&mut self.pending[channel]);
+            self.encode_subframe(&mut frame, &samples);
+            self.pending[channel] = samples;
+            self.pending[channel].clear();
+        }
+        frame.align();
+        let crc = crc16(&frame.bytes);
+        frame.write(crc as u64, 16);
+
+        self.writer.write_all(&frame.bytes).map_err(|e| format!("FLAC の書き込みに失敗: {}", e))?;
+        self.frame_number += 1;
+        self.total_frames += block_size as u64;
+        Ok(())
+    }
+
+    fn encode_subframe(&self, frame: &mut BitWriter, samples: &[i64]) {
+        let bps = self.bits_per_sample;
+        // 無音など値が一定のブロック
+        if samples.iter().all(|&sample| sample == samples[0]) {
+            frame.write(0b0000_0000, 8);
+            frame.write_signed(samples[0], bps);
+            return;
+        }
+
+        let verbatim_bits = samples.len() as u64 * bps as u64;
+        let best = (0..=4usize)
+            .filter(|&order| order < samples.len())
+            .map(|order| {
+                let residual = fixed_residual(samples, order);
+                let (parameter, bits) = rice_parameter(&residual);
+                (order, residual, parameter, bits + order as u64 * bps as u64)
+            })
+            .min_by_key(|(_, _, _, bits)| *bits);
+
+        match best {
+            Some((order, residual, parameter, bits)) if bits < verbatim_bits => {
+                frame.write(0b0001_0000 | (order as u64) << 1, 8);
+                for &sample in &samples[..order] {
+                    frame.write_signed(sample, bps);
+                }
+                // Rice（4bit パラメーター）・パーティション 1 つ
+                frame.write(0b00, 2);
+                frame.write(0, 4);
+                frame.write(parameter as u64, 4);
+                for &value in &residual {
+                    let value = zigzag(value);
+                    frame.write_unary((value >> parameter) as u32);
+                    frame.write(value & ((1u64 << parameter) - 1), parameter);
+                }
+            }
+            _ => {
+                frame.write(0b0000_0010, 8);
+                for &sample in samples {
+                    frame.write_signed(sample, bps);
+                }
+            }
+        }
+    }
+}
+
+/// フレーム番号を UTF-8 と同じ可変長の形式で書く
+fn write_utf8_number(writer: &mut BitWriter, value: u64) {
+    if value < 0x80 {
+        writer.write(value, 8);
+        return;
+    }
+    let bits = 64 - value.leading_zeros();
+    // 先頭バイトに入るビット数は続くバイト数で決まる
+    let continuation = (1..=6u32).find(|&n| bits <= 6 - n + 6 * n).unwrap_or(6);
+    let lead = (0xFFu64 << (7 - continuation)) & 0xFF;
+    writer.write(lead | (value >> (6 * continuation)), 8);
+    for index in (0..continuation).rev() {
+        writer.write(0x80 | ((value >> (6 * index)) & 0x3F), 8);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use symphonia::core::audio::SampleBuffer;
+    use symphonia::core::codecs::DecoderOptions;
+    use symphonia::core::errors::Error as SymphoniaError;
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+
+    const SAMPLE_RATE: u32 = 48_000;
+    /// 4096 の倍数にならない長さ（最後のフレームが端数になる）
+    const FRAMES: usize = BLOCK_SIZE * 3 + 1000;
+
+    /// 左はサイン波、右は最初のブロックが無音でその後は疑似乱数（定数・固定予測・そのままの各サブフレームを通す）
+    fn test_signal() -> Vec<f32> {
+        let mut seed: u32 = 12345;
+        let mut samples = Vec::with_capacity(FRAMES * 2);
+        for frame in 0..FRAMES {
+            samples.push((frame as f32 * 440.0 * std::f32::consts::TAU / SAMPLE_RATE as f32).sin() * 0.5);
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            samples.push(if frame < BLOCK_SIZE { 0.0 } else { (seed >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0 });
+        }
+        samples
+    }
+
+    fn encode(samples: &[f32], bits_per_sample: u32) -> Vec<u8> {
+        let mut encoder = FlacEncoder::new(Cursor::new(Vec::new()), SAMPLE_RATE, 2, bits_per_sample).unwrap();
+        // エンジンのブロックと同じく小分けにして書く
+        for chunk in samples.chunks(480 * 2) {
+            encoder.write(chunk).unwrap();
+        }
+        encoder.finish().unwrap().into_inner()
+    }
+
+    /// symphonia でデコードし、総フレーム数とインターリーブのサンプル（i32 の最大値に合わせた値）を返す
+    fn decode(bytes: Vec<u8>) -> (Option<u64>, Vec<i32>) {
+        let stream = MediaSourceStream::new(Box::new(Cursor::new(bytes)), Default::default());
+        let mut hint = Hint::new();
+        hint.with_extension("flac");
+        let mut format = symphonia::default::get_probe()
+            .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+            .unwrap()
+            .format;
+        let track = format.default_track().unwrap();
+        let n_frames = track.codec_params.n_frames;
+        let mut decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .unwrap();
+
+        let mut samples = Vec::new();
+        loop {
+            let packet = match format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                Err(e) => panic!("{}", e),
+            };
+            let decoded = decoder.decode(&packet).unwrap();
+            let mut buffer = SampleBuffer::<i32>::new(decoded.capacity() as u64, *decoded.spec());
+            buffer.copy_interleaved_ref(decoded);
+            samples.extend_from_slice(buffer.samples());
+        }
+        (n_frames, samples)
+    }
+
+    fn round_trip(bits_per_sample: u32) {
+        let input = test_signal();
+        let (n_frames, decoded) = decode(encode(&input, bits_per_sample));
+        assert_eq!(n_frames, Some(FRAMES as u64));
+        assert_eq!(decoded.len(), input.len());
+
+        let max = ((1i64 << (bits_per_sample - 1)) - 1) as f32;
+        for (index, (&sample, &output)) in input.iter().zip(&decoded).enumerate() {
+            let expected = ((sample * max).round() as i64) << (32 - bits_per_sample);
+            assert_eq!(output as i64, expected, "{}bit のサンプル {} が一致しません", bits_per_sample, index);
+        }
+    }
+
+    #[test]
+    fn round_trips_16_bit() {
+        round_trip(16);
+    }
+
+    #[test]
+    fn round_trips_24_bit() {
+        round_trip(24);
+    }
+
+    #[test]
+    fn rejects_partial_frames() {
+        let mut encoder = FlacEncoder::new(Cursor::new(Vec::new()), SAMPLE_RATE, 2, 16).unwrap();
+        assert!(encoder.write(&[0.1, 0.2, 0.3]).is_err());
+        assert!(encoder.write(&[0.1, 0.2]).is_ok());
+    }
+}
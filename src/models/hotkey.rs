@@ -0,0 +1,361 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use eframe::egui;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+pub const HOTKEYS_PATH: &str = "config/hotkeys.json";
+
+/// アクセラレーターに使えるキー
+const KEYS: [egui::Key; 73] = {
+    use egui::Key::*;
+    [
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+        Num0, Num1, Num2, Num3, Num4, Num5, Num6, Num7, Num8, Num9,
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15, F16, F17, F18, F19, F20,
+        ArrowDown, ArrowLeft, ArrowRight, ArrowUp, Escape, Tab, Backspace, Enter, Space,
+        Insert, Delete, Home, End, PageUp, PageDown, Minus, PlusEquals,
+    ]
+};
+
+/// `Ctrl+Shift+F1` のようなキーの組み合わせ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Accelerator {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    /// Windows キー / Super キー
+    pub super_key: bool,
+    pub key: egui::Key,
+}
+
+impl Accelerator {
+    /// `Ctrl+Shift+F1` 形式の文字列を解析する。修飾キー名の大文字・小文字は区別しない
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut ctrl = false;
+        let mut shift = false;
+        let mut alt = false;
+        let mut super_key = false;
+        let mut key = None;
+
+        for part in text.split('+').map(str::trim) {
+            if part.is_empty() {
+                return Err(format!("キーが空です: {}", text));
+            }
+            match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => ctrl = true,
+                "shift" => shift = true,
+                "alt" | "option" => alt = true,
+                "super" | "win" | "meta" | "cmd" => super_key = true,
+                name => {
+                    if key.is_some() {
+                        return Err(format!("修飾キー以外のキーは 1 つだけ指定できます: {}", text));
+                    }
+                    key = Some(parse_key(name).ok_or_else(|| format!("不明なキーです: {}", part))?);
+                }
+            }
+        }
+
+        let key = key.ok_or_else(|| format!("修飾キー以外のキーがありません: {}", text))?;
+        Ok(Self { ctrl, shift, alt, super_key, key })
+    }
+
+    pub fn from_egui(key: egui::Key, modifiers: egui::Modifiers) -> Self {
+        Self {
+            ctrl: modifiers.ctrl,
+            shift: modifiers.shift,
+            alt: modifiers.alt,
+            super_key: modifiers.mac_cmd,
+            key,
+        }
+    }
+
+    pub fn egui_modifiers(&self) -> egui::Modifiers {
+        egui::Modifiers {
+            alt: self.alt,
+            ctrl: self.ctrl,
+            shift: self.shift,
+            mac_cmd: self.super_key,
+            command: self.ctrl,
+        }
+    }
+
+    fn has_modifiers(&self) -> bool {
+        self.ctrl || self.shift || self.alt || self.super_key
+    }
+}
+
+impl fmt::Display for Accelerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (enabled, name) in [(self.ctrl, "Ctrl"), (self.shift, "Shift"), (self.alt, "Alt"), (self.super_key, "Super")] {
+            if enabled {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "{}", self.key.name())
+    }
+}
+
+fn parse_key(name: &str) -> Option<egui::Key> {
+    let name = match name {
+        "esc" => "escape",
+        "return" => "enter",
+        "del" => "delete",
+        "pgup" => "pageup",
+        "pgdn" => "pagedown",
+        "arrowup" => "up",
+        "arrowdown" => "down",
+        "arrowleft" => "left",
+        "arrowright" => "right",
+        other => other,
+    };
+    KEYS.iter().copied().find(|key| key.name().eq_ignore_ascii_case(name))
+}
+
+/// ホットキーで実行できる操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HotkeyAction {
+    /// `AudioTab::effects` の番号
+    PlayEffect(usize),
+    StopEffects,
+    /// プレビューのカメラと画面共有を切り替える
+    SwitchScene,
+    ToggleMicMute,
+    /// 押している間だけマイクを有効にする
+    PushToTalk,
+    ToggleStream,
+}
+
+impl HotkeyAction {
+    pub fn label(&self) -> String {
+        match self {
+            HotkeyAction::PlayEffect(index) => format!("効果音 {} を再生", index + 1),
+            HotkeyAction::StopEffects => "効果音をすべて停止".to_string(),
+            HotkeyAction::SwitchScene => "シーン切り替え（カメラ／画面共有）".to_string(),
+            HotkeyAction::ToggleMicMute => "マイクのミュート切り替え".to_string(),
+            HotkeyAction::PushToTalk => "マイクのプッシュトゥトーク".to_string(),
+            HotkeyAction::ToggleStream => "配信の開始／停止".to_string(),
+        }
+    }
+}
+
+/// 効果音以外の操作のホットキー（効果音のホットキーは `SoundEffect::hotkey`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeySettings {
+    pub bindings: Vec<(HotkeyAction, String)>,
+}
+
+impl Default for HotkeySettings {
+    fn default() -> Self {
+        Self {
+            bindings: [
+                HotkeyAction::StopEffects,
+                HotkeyAction::SwitchScene,
+                HotkeyAction::ToggleMicMute,
+                HotkeyAction::PushToTalk,
+                HotkeyAction::ToggleStream,
+            ]
+            .into_iter()
+            .map(|action| (action, String::new()))
+            .collect(),
+        }
+    }
+}
+
+impl HotkeySettings {
+    /// 以前のバージョンの設定ファイルにない操作を空の割り当てで追加する
+    pub fn add_missing_actions(&mut self) {
+        for (action, text) in HotkeySettings::default().bindings {
+            if !self.bindings.iter().any(|(a, _)| *a == action) {
+                self.bindings.push((action, text));
+            }
+        }
+    }
+}
+
+/// 割り当ての状態（UI 表示用）
+#[derive(Debug, Clone, PartialEq)]
+pub enum BindingStatus {
+    /// アプリ外でも有効
+    Global,
+    /// アプリにフォーカスがあるときのみ有効
+    InApp,
+    Invalid(String),
+    /// 同じキーが別の操作に割り当て済み
+    Conflict(HotkeyAction),
+}
+
+impl BindingStatus {
+    pub fn label(&self) -> String {
+        match self {
+            BindingStatus::Global => "有効".to_string(),
+            BindingStatus::InApp => "アプリ内のみ".to_string(),
+            BindingStatus::Invalid(e) => e.clone(),
+            BindingStatus::Conflict(action) => format!("「{}」と重複しています", action.label()),
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self, BindingStatus::Invalid(_) | BindingStatus::Conflict(_))
+    }
+}
+
+/// アプリ外でもキー入力を受け取るバックエンド
+pub trait GlobalHotkeyBackend {
+    fn name(&self) -> &str;
+    /// 割り当てを置き換える。登録できなかったキーはエラーを返す
+    fn register(&mut self, accelerators: &[Accelerator]) -> Vec<Result<(), String>>;
+    /// 前回の呼び出し以降に押された（`true`）・離された（`false`）キー。キーリピートは含めない
+    fn poll(&mut self) -> Vec<(Accelerator, bool)>;
+}
+
+fn global_backend() -> Option<Box<dyn GlobalHotkeyBackend>> {
+    #[cfg(target_os = "linux")]
+    {
+        match super::hotkey_x11::X11Hotkeys::connect() {
+            Ok(backend) => return Some(Box::new(backend)),
+            Err(e) => warn!("グローバルホットキーを利用できません: {}", e),
+        }
+    }
+    None
+}
+
+/// ホットキーの割り当てを管理し、押されたキーを操作に変換する
+pub struct HotkeyManager {
+    backend: Option<Box<dyn GlobalHotkeyBackend>>,
+    /// 前回登録した割り当て（変化がなければ登録し直さない）
+    requested: Vec<(HotkeyAction, String)>,
+    active: HashMap<Accelerator, HotkeyAction>,
+    /// グローバルに登録できなかったキー（アプリ内でのみ検出する）
+    in_app: Vec<Accelerator>,
+    statuses: HashMap<HotkeyAction, BindingStatus>,
+    /// 押したままの操作（プッシュトゥトーク用）
+    held: HashSet<HotkeyAction>,
+}
+
+impl HotkeyManager {
+    pub fn new() -> Self {
+        let backend = global_backend();
+        if let Some(backend) = &backend {
+            info!("グローバルホットキー: {}", backend.name());
+        }
+        Self {
+            backend,
+            requested: Vec::new(),
+            active: HashMap::new(),
+            in_app: Vec::new(),
+            statuses: HashMap::new(),
+            held: HashSet::new(),
+        }
+    }
+
+    pub fn backend_name(&self) -> Option<&str> {
+        self.backend.as_ref().map(|backend| backend.name())
+    }
+
+    /// 割り当てを更新する。空文字列の割り当ては無視する
+    pub fn set_bindings(&mut self, bindings: Vec<(HotkeyAction, String)>) {
+        if bindings == self.requested {
+            return;
+        }
+        self.requested = bindings;
+        self.active.clear();
+        self.in_app.clear();
+        self.statuses.clear();
+        self.held.clear();
+
+        let mut accelerators = Vec::new();
+        for (action, text) in &self.requested {
+            if text.trim().is_empty() {
+                continue;
+            }
+            let accelerator = match Accelerator::parse(text) {
+                Ok(accelerator) => accelerator,
+                Err(e) => {
+                    self.statuses.insert(*action, BindingStatus::Invalid(e));
+                    continue;
+                }
+            };
+            if let Some(other) = self.active.get(&accelerator) {
+                self.statuses.insert(*action, BindingStatus::Conflict(*other));
+                continue;
+            }
+            self.active.insert(accelerator, *action);
+            accelerators.push(accelerator);
+        }
+
+        let results = match &mut self.backend {
+            Some(backend) => backend.register(&accelerators),
+            None => accelerators.iter().map(|_| Err("グローバルホットキー非対応".to_string())).collect(),
+        };
+        for (accelerator, result) in accelerators.into_iter().zip(results) {
+            let action = self.active[&accelerator];
+            let status = match result {
+                Ok(()) => BindingStatus::Global,
+                Err(e) => {
+                    warn!("ホットキー {} をグローバルに登録できません: {}", accelerator, e);
+                    self.in_app.push(accelerator);
+                    BindingStatus::InApp
+                }
+            };
+            self.statuses.insert(action, status);
+        }
+    }
+
+    pub fn status(&self, action: HotkeyAction) -> Option<&BindingStatus> {
+        self.statuses.get(&action)
+    }
+
+    /// 割り当てたキーを押したままにしているか
+    pub fn is_held(&self, action: HotkeyAction) -> bool {
+        self.held.contains(&action)
+    }
+
+    /// 押されたホットキーの操作を返す。押したままのキーは最初の 1 回だけ返す。毎フレーム呼び出す
+    pub fn poll(&mut self, ctx: &egui::Context) -> Vec<HotkeyAction> {
+        let mut events = self.backend.as_mut().map(|backend| backend.poll()).unwrap_or_default();
+
+        // グローバルに登録できなかったキーはアプリにフォーカスがあるときだけ検出する
+        // 修飾キーなしの割り当ては文字入力を妨げないよう、テキスト入力中は無視する
+        let typing = ctx.wants_keyboard_input();
+        ctx.input_mut(|input| {
+            for accelerator in &self.in_app {
+                // 修飾キーを先に離しても押したままにならないよう、離したかどうかはキーだけで判定する
+                let released = input.key_released(accelerator.key);
+                if (!typing || accelerator.has_modifiers()) && input.consume_key(accelerator.egui_modifiers(), accelerator.key) {
+                    events.push((*accelerator, true));
+                }
+                if released {
+                    events.push((*accelerator, false));
+                }
+            }
+        });
+
+        let mut actions = Vec::new();
+        for (accelerator, pressed) in events {
+            let Some(&action) = self.active.get(&accelerator) else { continue };
+            if !pressed {
+                self.held.remove(&action);
+            } else if self.held.insert(action) {
+                actions.push(action);
+            }
+        }
+        actions
+    }
+}
+
+/// ホットキー記録中に押されたキーの組み合わせを取り出す。Esc で取り消した場合は `Some(None)` を返す
+pub fn capture_accelerator(ctx: &egui::Context) -> Option<Option<Accelerator>> {
+    ctx.input(|input| {
+        input.events.iter().find_map(|event| match event {
+            egui::Event::Key { key, pressed: true, modifiers, .. } => {
+                if *key == egui::Key::Escape && !modifiers.any() {
+                    Some(None)
+                } else {
+                    Some(Some(Accelerator::from_egui(*key, *modifiers)))
+                }
+            }
+            _ => None,
+        })
+    })
+}
@@ -0,0 +1,203 @@
+use eframe::egui;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{ConnectionExt, GrabMode, KeyReleaseEvent, Keycode, ModMask, Window};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use super::hotkey::{Accelerator, GlobalHotkeyBackend};
+
+/// CapsLock・NumLock の状態に関係なく検出するため、これらを付けた組み合わせも登録する
+fn ignored_modifiers() -> [ModMask; 4] {
+    [ModMask::from(0u16), ModMask::LOCK, ModMask::M2, ModMask::LOCK | ModMask::M2]
+}
+
+/// X11 のルートウィンドウでキーをグラブするバックエンド
+pub struct X11Hotkeys {
+    conn: RustConnection,
+    root: Window,
+    /// キーコードごとのキーシム
+    keysyms: Vec<Vec<u32>>,
+    min_keycode: Keycode,
+    grabbed: Vec<(Keycode, ModMask, Accelerator)>,
+    /// 最後に届いた KeyRelease。キーリピートかどうかは次のイベントを見るまで分からないため保留する
+    pending_release: Option<KeyReleaseEvent>,
+}
+
+impl X11Hotkeys {
+    pub fn connect() -> Result<Self, String> {
+        let (conn, screen) = x11rb::connect(None).map_err(|e| format!("X サーバーに接続できません: {}", e))?;
+        let setup = conn.setup();
+        let root = setup.roots[screen].root;
+        let min_keycode = setup.min_keycode;
+        let count = setup.max_keycode - min_keycode + 1;
+        let mapping = conn
+            .get_keyboard_mapping(min_keycode, count)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| format!("キーボード配列を取得できません: {}", e))?;
+        let keysyms = mapping
+            .keysyms
+            .chunks(mapping.keysyms_per_keycode.max(1) as usize)
+            .map(<[u32]>::to_vec)
+            .collect();
+
+        Ok(Self {
+            conn,
+            root,
+            keysyms,
+            min_keycode,
+            grabbed: Vec::new(),
+            pending_release: None,
+        })
+    }
+
+    fn keycode(&self, keysym: u32) -> Option<Keycode> {
+        self.keysyms
+            .iter()
+            .position(|syms| syms.contains(&keysym))
+            .map(|index| self.min_keycode + index as Keycode)
+    }
+
+    fn ungrab_all(&mut self) {
+        for (keycode, modifiers, _) in self.grabbed.drain(..) {
+            for ignored in ignored_modifiers() {
+                let _ = self.conn.ungrab_key(keycode, self.root, modifiers | ignored);
+            }
+        }
+        let _ = self.conn.flush();
+    }
+
+    fn grab(&mut self, accelerator: Accelerator) -> Result<(), String> {
+        let keysym = keysym(accelerator.key);
+        let keycode = self.keycode(keysym).ok_or_else(|| "キーボードにないキーです".to_string())?;
+        let modifiers = modifier_mask(&accelerator);
+
+        let ignored_modifiers = ignored_modifiers();
+        for (index, ignored) in ignored_modifiers.iter().enumerate() {
+            let result = self
+                .conn
+                .grab_key(false, self.root, modifiers | *ignored, keycode, GrabMode::ASYNC, GrabMode::ASYNC)
+                .map_err(|e| e.to_string())
+                .and_then(|cookie| cookie.check().map_err(|e| e.to_string()));
+            if let Err(e) = result {
+                // 途中まで登録した組み合わせを戻す
+                for ignored in &ignored_modifiers[..index] {
+                    let _ = self.conn.ungrab_key(keycode, self.root, modifiers | *ignored);
+                }
+                return Err(format!("他のアプリケーションが使用中の可能性があります ({})", e));
+            }
+        }
+        self.grabbed.push((keycode, modifiers, accelerator));
+        Ok(())
+    }
+}
+
+impl GlobalHotkeyBackend for X11Hotkeys {
+    fn name(&self) -> &str {
+        "X11"
+    }
+
+    fn register(&mut self, accelerators: &[Accelerator]) -> Vec<Result<(), String>> {
+        self.ungrab_all();
+        let results = accelerators.iter().map(|accelerator| self.grab(*accelerator)).collect();
+        let _ = self.conn.flush();
+        results
+    }
+
+    fn poll(&mut self) -> Vec<(Accelerator, bool)> {
+        let relevant = ModMask::SHIFT | ModMask::CONTROL | ModMask::M1 | ModMask::M4;
+        let mut events: Vec<Event> = self.pending_release.take().map(Event::KeyRelease).into_iter().collect();
+        let carried = events.len();
+        while let Ok(Some(event)) = self.conn.poll_for_event() {
+            events.push(event);
+        }
+        let received = events.len() - carried;
+
+        let mut keys = Vec::new();
+        let mut events = events.into_iter().peekable();
+        while let Some(event) = events.next() {
+            match event {
+                Event::KeyPress(event) => {
+                    let state = ModMask::from(u16::from(event.state)) & relevant;
+                    if let Some((_, _, accelerator)) = self
+                        .grabbed
+                        .iter()
+                        .find(|(keycode, modifiers, _)| *keycode == event.detail && *modifiers == state)
+                    {
+                        keys.push((*accelerator, true));
+                    }
+                }
+                Event::KeyRelease(event) => {
+                    // キーリピートは同じ時刻の KeyRelease と KeyPress の組で届くため、どちらも無視する
+                    match events.peek() {
+                        Some(Event::KeyPress(next)) if next.detail == event.detail && next.time == event.time => {
+                            events.next();
+                            continue;
+                        }
+                        // 組の KeyPress が次の呼び出しで届く場合に備えて 1 回だけ保留する
+                        None if received > 0 => {
+                            self.pending_release = Some(event);
+                            continue;
+                        }
+                        _ => {}
+                    }
+                    // 修飾キーを先に離した場合もあるため、キーコードだけで判定する
+                    for (_, _, accelerator) in self.grabbed.iter().filter(|(keycode, _, _)| *keycode == event.detail) {
+                        keys.push((*accelerator, false));
+                    }
+                }
+                _ => {}
+            }
+        }
+        keys
+    }
+}
+
+impl Drop for X11Hotkeys {
+    fn drop(&mut self) {
+        self.ungrab_all();
+    }
+}
+
+fn modifier_mask(accelerator: &Accelerator) -> ModMask {
+    let mut mask = ModMask::from(0u16);
+    for (enabled, modifier) in [
+        (accelerator.shift, ModMask::SHIFT),
+        (accelerator.ctrl, ModMask::CONTROL),
+        (accelerator.alt, ModMask::M1),
+        (accelerator.super_key, ModMask::M4),
+    ] {
+        if enabled {
+            mask |= modifier;
+        }
+    }
+    mask
+}
+
+/// egui のキーに対応する X11 のキーシム
+fn keysym(key: egui::Key) -> u32 {
+    use egui::Key;
+    let name = key.name();
+    match key {
+        Key::ArrowDown => 0xff54,
+        Key::ArrowLeft => 0xff51,
+        Key::ArrowRight => 0xff53,
+        Key::ArrowUp => 0xff52,
+        Key::Escape => 0xff1b,
+        Key::Tab => 0xff09,
+        Key::Backspace => 0xff08,
+        Key::Enter => 0xff0d,
+        Key::Space => 0x20,
+        Key::Insert => 0xff63,
+        Key::Delete => 0xffff,
+        Key::Home => 0xff50,
+        Key::End => 0xff57,
+        Key::PageUp => 0xff55,
+        Key::PageDown => 0xff56,
+        Key::Minus => 0x2d,
+        Key::PlusEquals => 0x3d,
+        // 英字は小文字、数字はそのままの文字コード
+        _ if name.len() == 1 => name.to_ascii_lowercase().as_bytes()[0] as u32,
+        // F1 は 0xffbe から連番
+        _ => 0xffbe + name[1..].parse::<u32>().unwrap_or(1) - 1,
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::{Arc, Mutex};
+use super::audio_engine::{ENGINE_CHANNELS, ENGINE_SAMPLE_RATE};
+use super::dsp::{db_to_gain, smoothing_coefficient};
+use super::mixer::BusKind;
+
+/// ピーク表示の下がる速さ（dB/秒）
+const PEAK_FALLOFF_DB_PER_SEC: f32 = 20.0;
+/// ピークホールドを保持する時間
+const PEAK_HOLD_SECS: f32 = 1.5;
+/// RMS の平滑化時間
+const RMS_WINDOW_MS: f32 = 300.0;
+
+/// 1 つのバスの左右のレベル（振幅、1.0 = 0dBFS）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MeterReading {
+    pub peak: [f32; 2],
+    pub rms: [f32; 2],
+    pub hold: [f32; 2],
+    /// 0dBFS を超えたことがある（UI でリセットするまで保持する）
+    pub clipped: bool,
+}
+
+/// ブロックごとにレベルを測り、表示用の減衰・ホールドをかける
+#[derive(Default)]
+pub struct LevelMeter {
+    reading: MeterReading,
+    mean_square: [f32; 2],
+    hold_frames: [usize; 2],
+    clipped: bool,
+}
+
+impl LevelMeter {
+    /// エンジン形式（ステレオ）のブロックを測る
+    pub fn measure(&mut self, samples: &[f32]) {
+        let frames = samples.len() / ENGINE_CHANNELS;
+        if frames == 0 {
+            return;
+        }
+        let block_secs = frames as f32 / ENGINE_SAMPLE_RATE as f32;
+        let falloff = db_to_gain(-PEAK_FALLOFF_DB_PER_SEC * block_secs);
+        let rms_coef = smoothing_coefficient(RMS_WINDOW_MS, ENGINE_SAMPLE_RATE).powi(frames as i32);
+        let hold_frames = (PEAK_HOLD_SECS * ENGINE_SAMPLE_RATE as f32) as usize;
+
+        for channel in 0..2 {
+            let mut block_peak = 0.0f32;
+            let mut sum = 0.0f32;
+            for frame in samples.chunks(ENGINE_CHANNELS) {
+                let sample = frame[channel.min(frame.len() - 1)];
+                block_peak = block_peak.max(sample.abs());
+                sum += sample * sample;
+            }
+            self.clipped |= block_peak >= 1.0;
+
+            let reading = &mut self.reading;
+            reading.peak[channel] = block_peak.max(reading.peak[channel] * falloff);
+            self.mean_square[channel] = sum / frames as f32 + rms_coef * (self.mean_square[channel] - sum / frames as f32);
+            reading.rms[channel] = self.mean_square[channel].sqrt();
+
+            if block_peak >= reading.hold[channel] {
+                reading.hold[channel] = block_peak;
+                self.hold_frames[channel] = hold_frames;
+            } else if self.hold_frames[channel] > frames {
+                self.hold_frames[channel] -= frames;
+            } else {
+                reading.hold[channel] = reading.peak[channel];
+            }
+        }
+    }
+
+    /// クリップを記録する（リミッター前の信号で判定する場合など）
+    pub fn mark_clipped(&mut self) {
+        self.clipped = true;
+    }
+
+    fn take_reading(&mut self) -> MeterReading {
+        let mut reading = self.reading;
+        reading.clipped = std::mem::take(&mut self.clipped);
+        reading
+    }
+}
+
+/// ミキサーが書き込み、UI が読み取るメーターの値（各バスとマスター）
+#[derive(Clone, Default)]
+pub struct LevelMeters {
+    readings: Arc<Mutex<[MeterReading; 5]>>,
+}
+
+impl LevelMeters {
+    fn index(bus: Option<BusKind>) -> usize {
+        bus.map_or(4, |kind| kind as usize)
+    }
+
+    /// `None` はマスター
+    pub fn reading(&self, bus: Option<BusKind>) -> MeterReading {
+        self.readings.lock().unwrap()[Self::index(bus)]
+    }
+
+    pub fn reset_clip(&self, bus: Option<BusKind>) {
+        self.readings.lock().unwrap()[Self::index(bus)].clipped = false;
+    }
+
+    /// 測定結果を反映する。クリップはリセットされるまで保持する
+    pub fn update(&self, bus: Option<BusKind>, meter: &mut LevelMeter) {
+        let mut readings = self.readings.lock().unwrap();
+        let reading = &mut readings[Self::index(bus)];
+        let clipped = reading.clipped;
+        *reading = meter.take_reading();
+        reading.clipped |= clipped;
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use log::{info, error};
+use serde::{Deserialize, Serialize};
+use super::audio_decoder::{AudioDecoder, Resampler};
+use super::audio_engine::{ENGINE_CHANNELS, ENGINE_SAMPLE_RATE};
+use super::dsp::{db_to_gain, gain_to_db, smoothing_coefficient, Biquad};
+use super::settings_store;
+
+pub const LOUDNESS_CACHE_PATH: &str = "config/loudness_cache.json";
+
+/// 正規化で変える音量の上限（静かすぎる・無音のファイルを極端に持ち上げない）
+const MAX_NORMALIZATION_DB: f32 = 20.0;
+/// ゲーティングブロック 400ms をこの長さ（100ms）ずつずらして測る
+const SUB_BLOCK_FRAMES: usize = ENGINE_SAMPLE_RATE as usize / 10;
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+const RELATIVE_GATE_LU: f64 = -10.0;
+
+/// ファイルのラウドネス解析結果
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LoudnessInfo {
+    /// 統合ラウドネス（LUFS）。無音のファイルは `None`
+    pub integrated_lufs: Option<f32>,
+    /// サンプルピーク（dBFS）
+    pub peak_db: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoudnessSettings {
+    pub enabled: bool,
+    pub target_lufs: f32,
+}
+
+impl Default for LoudnessSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            target_lufs: -18.0,
+        }
+    }
+}
+
+impl LoudnessSettings {
+    /// 目標のラウドネスに合わせるための倍率
+    pub fn gain_for(&self, info: Option<&LoudnessInfo>) -> f32 {
+        match info.and_then(|info| info.integrated_lufs) {
+            Some(lufs) if self.enabled => {
+                db_to_gain((self.target_lufs - lufs).clamp(-MAX_NORMALIZATION_DB, MAX_NORMALIZATION_DB))
+            }
+            _ => 1.0,
+        }
+    }
+}
+
+/// ITU-R BS.1770 の K 特性フィルター（48kHz の係数）
+fn k_weighting() -> [Biquad; 2] {
+    [
+        Biquad::from_coefficients(1.535_124_9, -2.691_696_2, 1.198_392_8, -1.690_659_3, 0.732_480_8),
+        Biquad::from_coefficients(1.0, -2.0, 1.0, -1.990_047_5, 0.990_072_25),
+    ]
+}
+
+/// ファイル全体をデコードして統合ラウドネス（EBU R128）とピークを求める
+pub fn analyze_file(path: &str) -> Result<LoudnessInfo, String> {
+    let mut decoder = AudioDecoder::open(path)?;
+    let mut resampler = Resampler::new(decoder.sample_rate(), decoder.channels(), ENGINE_SAMPLE_RATE, ENGINE_CHANNELS);
+    let mut filters = [k_weighting(), k_weighting()];
+    let mut converted = Vec::new();
+    let mut sub_blocks = Vec::new();
+    let mut sum = 0.0f64;
+    let mut frames_in_block = 0;
+    let mut peak = 0.0f32;
+
+    while let Some(samples) = decoder.next_samples()? {
+        converted.clear();
+        resampler.process(&samples, &mut converted);
+        for frame in converted.chunks(ENGINE_CHANNELS) {
+            for (channel, &sample) in frame.iter().enumerate() {
+                peak = peak.max(sample.abs());
+                let weighted = filters[channel].iter_mut().fold(sample, |x, filter| filter.process(x));
+                sum += (weighted as f64).powi(2);
+            }
+            frames_in_block += 1;
+            if frames_in_block == SUB_BLOCK_FRAMES {
+                sub_blocks.push(sum / SUB_BLOCK_FRAMES as f64);
+                sum = 0.0;
+                frames_in_block = 0;
+            }
+        }
+    }
+
+    Ok(LoudnessInfo {
+        integrated_lufs: integrated_loudness(&sub_blocks).map(|lufs| lufs as f32),
+        peak_db: gain_to_db(peak),
+    })
+}
+
+fn block_loudness(mean_square: f64) -> f64 {
+    -0.691 + 10.0 * mean_square.log10()
+}
+
+/// 100ms ごとの平均二乗から 400ms ブロック（75% 重複）を作り、絶対・相対ゲートをかけて平均する
+fn integrated_loudness(sub_blocks: &[f64]) -> Option<f64> {
+    let blocks: Vec<f64> = sub_blocks
+        .windows(4)
+        .map(|window| window.iter().sum::<f64>() / 4.0)
+        .filter(|&mean_square| mean_square > 0.0 && block_loudness(mean_square) > ABSOLUTE_GATE_LUFS)
+        .collect();
+    if blocks.is_empty() {
+        return None;
+    }
+    let relative_gate = block_loudness(blocks.iter().sum::<f64>() / blocks.len() as f64) + RELATIVE_GATE_LU;
+    let gated: Vec<f64> = blocks.into_iter().filter(|&mean_square| block_loudness(mean_square) > relative_gate).collect();
+    if gated.is_empty() {
+        return None;
+    }
+    Some(block_loudness(gated.iter().sum::<f64>() / gated.len() as f64))
+}
+
+/// ファイル内容の FNV-1a ハッシュ（解析結果のキャッシュのキー）
+pub fn file_hash(path: &str) -> Result<String, String> {
+    let mut reader = BufReader::new(File::open(path).map_err(|e| format!("ファイルを開けません ({}): {}", path, e))?);
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer).map_err(|e| format!("ファイルの読み込みに失敗: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        for &byte in &buffer[..read] {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    Ok(format!("{:016x}", hash))
+}
+
+/// ファイルを追加するたびにバックグラウンドで解析する。同じ内容のファイルはキャッシュを使う
+pub struct LoudnessAnalyzer {
+    cache: Arc<Mutex<HashMap<String, LoudnessInfo>>>,
+    sender: Sender<(String, Result<LoudnessInfo, String>)>,
+    receiver: Receiver<(String, Result<LoudnessInfo, String>)>,
+}
+
+impl LoudnessAnalyzer {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            cache: Arc::new(Mutex::new(settings_store::load_json(LOUDNESS_CACHE_PATH))),
+            sender,
+            receiver,
+        }
+    }
+
+    /// 解析を依頼する。結果は `poll` で受け取る
+    pub fn request(&self, path: &str) {
+        let path = path.to_string();
+        let cache = Arc::clone(&self.cache);
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            let result = file_hash(&path).and_then(|hash| {
+                if let Some(info) = cache.lock().unwrap().get(&hash) {
+                    return Ok(*info);
+                }
+                let info = analyze_file(&path)?;
+                info!("ラウドネス解析: {} {:?}", path, info);
+                let mut cache = cache.lock().unwrap();
+                cache.insert(hash, info);
+                if let Err(e) = settings_store::save_json(LOUDNESS_CACHE_PATH, &*cache) {
+                    error!("ラウドネスのキャッシュを保存できません: {}", e);
+                }
+                Ok(info)
+            });
+            let _ = sender.send((path, result));
+        });
+    }
+
+    /// 終わった解析結果（ファイルパス, 結果）
+    pub fn poll(&self) -> Vec<(String, Result<LoudnessInfo, String>)> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+/// マスターのトゥルーピークリミッターの設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MasterLimiterSettings {
+    pub enabled: bool,
+    /// 上限（dBTP）
+    pub ceiling_db: f32,
+    pub release_ms: f32,
+}
+
+impl Default for MasterLimiterSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ceiling_db: -1.0,
+            release_ms: 100.0,
+        }
+    }
+}
+
+/// 先読み付きのトゥルーピークリミッター（ステレオ）
+///
+/// サンプル間のピークは 3 次補間で 4 倍にオーバーサンプリングして推定する
+pub struct TruePeakLimiter {
+    /// 先読みの分だけ遅らせた入力
+    delay: VecDeque<[f32; 2]>,
+    /// 先読み区間の各フレームに必要なゲイン
+    required: VecDeque<f32>,
+    /// 補間に使う直前の 2 フレーム
+    history: [[f32; 2]; 2],
+    gain: f32,
+}
+
+/// 先読みの長さ（1.5ms）
+const LOOKAHEAD_FRAMES: usize = ENGINE_SAMPLE_RATE as usize * 3 / 2000;
+
+impl Default for TruePeakLimiter {
+    fn default() -> Self {
+        Self {
+            delay: VecDeque::from(vec![[0.0; 2]; LOOKAHEAD_FRAMES]),
+            required: VecDeque::from(vec![1.0; LOOKAHEAD_FRAMES]),
+            history: [[0.0; 2]; 2],
+            gain: 1.0,
+        }
+    }
+}
+
+impl TruePeakLimiter {
+    /// 現在の減衰量（dB）
+    pub fn reduction_db(&self) -> f32 {
+        -gain_to_db(self.gain)
+    }
+
+    pub fn process(&mut self, samples: &mut [f32], settings: &MasterLimiterSettings) {
+        let ceiling = db_to_gain(settings.ceiling_db);
+        let release = smoothing_coefficient(settings.release_ms, ENGINE_SAMPLE_RATE);
+        // 先読み区間の間にゲインを下げ切る
+        let attack = smoothing_coefficient(LOOKAHEAD_FRAMES as f32 * 1000.0 / ENGINE_SAMPLE_RATE as f32 / 4.0, ENGINE_SAMPLE_RATE);
+
+        for frame in samples.chunks_mut(ENGINE_CHANNELS) {
+            let input = [frame[0], frame[ENGINE_CHANNELS - 1]];
+            let peak = (0..2).map(|channel| self.true_peak(channel, input[channel])).fold(0.0f32, f32::max);
+            self.history = [self.history[1], input];
+
+            self.delay.push_back(input);
+            self.required.push_back(if peak > ceiling { ceiling / peak } else { 1.0 });
+            self.required.pop_front();
+            let delayed = self.delay.pop_front().unwrap_or_default();
+
+            let target = self.required.iter().copied().fold(1.0f32, f32::min);
+            let coef = if target < self.gain { attack } else { release };
+            self.gain = target + coef * (self.gain - target);
+
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                // 平滑化で下げ切れなかった分は上限で切る
+                *sample = (delayed[channel.min(1)] * self.gain).clamp(-ceiling, ceiling);
+            }
+        }
+    }
+
+    /// 直前のフレームとの間のピークを推定する
+    fn true_peak(&self, channel: usize, current: f32) -> f32 {
+        let p0 = self.history[0][channel];
+        let p1 = self.history[1][channel];
+        let p2 = current;
+        let p3 = current;
+        let mut peak = p1.abs().max(p2.abs());
+        for t in [0.25f32, 0.5, 0.75] {
+            // Catmull-Rom 補間
+            let value = 0.5
+                * (2.0 * p1
+                    + (-p0 + p2) * t
+                    + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t
+                    + (-p0 + 3.0 * p1 - 3.0 * p2 + p3) * t * t * t);
+            peak = peak.max(value.abs());
+        }
+        peak
+    }
+}
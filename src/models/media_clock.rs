@@ -0,0 +1,290 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// 映像・音声で共有する時計の起点（最初に参照したとき）
+static ORIGIN: OnceLock<Instant> = OnceLock::new();
+
+/// 共有の時計の現在時刻。単調増加し、プロセス内のすべてのソースで同じ起点を使う
+pub fn now() -> Duration {
+    ORIGIN.get_or_init(Instant::now).elapsed()
+}
+
+/// 取り込んだ時刻にソースごとの補正をかけたタイムスタンプ
+///
+/// 正の `offset_ms` は「そのソースが遅れて届く」ことを表し、その分だけ前の時刻として扱う
+pub fn stamp(offset_ms: i32) -> Duration {
+    offset(now(), offset_ms)
+}
+
+/// `time` を `offset_ms` だけ前（負の場合は後）にずらす
+pub fn offset(time: Duration, offset_ms: i32) -> Duration {
+    let offset = Duration::from_millis(offset_ms.unsigned_abs() as u64);
+    if offset_ms >= 0 {
+        time.saturating_sub(offset)
+    } else {
+        time + offset
+    }
+}
+
+/// インターリーブする前のストリーム
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MediaStream {
+    /// ミキサーのマスター出力
+    Audio,
+    /// `CameraSettings::device_id`
+    Camera(String),
+    Screen,
+}
+
+impl MediaStream {
+    pub fn label(&self) -> String {
+        match self {
+            MediaStream::Audio => "音声".to_string(),
+            MediaStream::Camera(device_id) => format!("カメラ {}", device_id),
+            MediaStream::Screen => "画面".to_string(),
+        }
+    }
+}
+
+/// タイムスタンプ付きのパケット
+#[derive(Debug, Clone)]
+pub struct MediaPacket<T> {
+    pub stream: MediaStream,
+    /// 共有の時計での表示時刻
+    pub pts: Duration,
+    pub payload: T,
+}
+
+/// 時刻順に並べたパケットの渡し先（マルチプレクサーなど）
+pub trait PacketSink<T>: Send {
+    fn write_packet(&mut self, packet: MediaPacket<T>);
+}
+
+/// ストリームごとに届いたパケットを、時刻順に並べて `PacketSink` へ渡す
+///
+/// 登録したすべてのストリームがその時刻まで届くのを待ってから、最も古いものを出す。
+/// 止まったソースで全体が詰まらないよう、`max_wait` より古いパケットは待たずに出す
+pub struct Interleaver<T> {
+    queues: HashMap<MediaStream, VecDeque<MediaPacket<T>>>,
+    /// 待ち合わせるストリームと、最後に受け取った時刻（まだ届いていなければ `None`）
+    last_pts: HashMap<MediaStream, Option<Duration>>,
+    max_wait: Duration,
+    sink: Box<dyn PacketSink<T>>,
+}
+
+impl<T> Interleaver<T> {
+    pub fn new(sink: Box<dyn PacketSink<T>>, max_wait: Duration) -> Self {
+        Self {
+            queues: HashMap::new(),
+            last_pts: HashMap::new(),
+            max_wait,
+            sink,
+        }
+    }
+
+    /// 待ち合わせるストリームを追加する（追加済みなら何もしない）
+    pub fn add_stream(&mut self, stream: MediaStream) {
+        self.last_pts.entry(stream).or_insert(None);
+    }
+
+    /// ストリームの待ち合わせをやめる。受け取り済みのパケットは時刻順に出す
+    pub fn remove_stream(&mut self, stream: &MediaStream) {
+        self.last_pts.remove(stream);
+    }
+
+    /// パケットを追加する。未登録のストリームは自動で追加する
+    ///
+    /// 補正の変更などで時刻が前のパケットより戻った場合は、前のパケットの時刻にそろえる
+    pub fn push(&mut self, stream: MediaStream, pts: Duration, payload: T) {
+        let last = self.last_pts.entry(stream.clone()).or_insert(None);
+        let pts = last.map_or(pts, |last| pts.max(last));
+        *last = Some(pts);
+        self.queues.entry(stream.clone()).or_default().push_back(MediaPacket { stream, pts, payload });
+    }
+
+    /// 順番が確定したパケットをすべて渡す
+    pub fn pump(&mut self) {
+        self.pump_at(now());
+    }
+
+    /// `now` を現在時刻として `pump` する
+    pub fn pump_at(&mut self, now: Duration) {
+        while let Some(packet) = self.pop_ready(now) {
+            self.sink.write_packet(packet);
+        }
+    }
+
+    /// 待ち合わせを打ち切り、残りをすべて時刻順に渡す（配信・録画の終了時）
+    pub fn flush(&mut self) {
+        let mut packets: Vec<MediaPacket<T>> = self.queues.values_mut().flat_map(|queue| queue.drain(..)).collect();
+        packets.sort_by_key(|packet| packet.pts);
+        for packet in packets {
+            self.sink.write_packet(packet);
+        }
+    }
+
+    fn pop_ready(&mut self, now: Duration) -> Option<MediaPacket<T>> {
+        let (stream, pts) = self
+            .queues
+            .iter()
+            .filter_map(|(stream, queue)| queue.front().map(|packet| (stream, packet.pts)))
+            .min_by_key(|(_, pts)| *pts)
+            .map(|(stream, pts)| (stream.clone(), pts))?;
+
+        // どのストリームもこの時刻まで届いていれば、これより前のパケットはもう来ない
+        let passed = self.last_pts.values().all(|last| last.is_some_and(|last| last >= pts));
+        if !passed && now.saturating_sub(pts) < self.max_wait {
+            return None;
+        }
+        self.queues.get_mut(&stream)?.pop_front()
+    }
+}
+
+/// 並べ終えたパケットの時刻をストリームごとに記録する（配信状況タブの同期表示用）
+#[derive(Clone, Default)]
+pub struct SyncMonitor {
+    latest: Arc<Mutex<HashMap<MediaStream, Duration>>>,
+}
+
+impl SyncMonitor {
+    /// 最後に出したパケットの、音声に対するずれ（ms、正は映像が後）
+    pub fn skew_ms(&self) -> Vec<(MediaStream, i64)> {
+        let latest = self.latest.lock().unwrap();
+        let Some(audio) = latest.get(&MediaStream::Audio) else {
+            return Vec::new();
+        };
+        let mut skew: Vec<(MediaStream, i64)> = latest
+            .iter()
+            .filter(|(stream, _)| **stream != MediaStream::Audio)
+            .map(|(stream, pts)| (stream.clone(), pts.as_millis() as i64 - audio.as_millis() as i64))
+            .collect();
+        skew.sort_by_key(|(stream, _)| stream.label());
+        skew
+    }
+
+    pub fn clear(&self) {
+        self.latest.lock().unwrap().clear();
+    }
+}
+
+impl<T> PacketSink<T> for SyncMonitor {
+    fn write_packet(&mut self, packet: MediaPacket<T>) {
+        self.latest.lock().unwrap().insert(packet.stream, packet.pts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 受け取ったパケットを順に記録する
+    #[derive(Clone, Default)]
+    struct RecordingSink(Arc<Mutex<Vec<(MediaStream, u64)>>>);
+
+    impl PacketSink<()> for RecordingSink {
+        fn write_packet(&mut self, packet: MediaPacket<()>) {
+            self.0.lock().unwrap().push((packet.stream, packet.pts.as_millis() as u64));
+        }
+    }
+
+    impl RecordingSink {
+        fn take(&self) -> Vec<(MediaStream, u64)> {
+            std::mem::take(&mut *self.0.lock().unwrap())
+        }
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn camera() -> MediaStream {
+        MediaStream::Camera("0".to_string())
+    }
+
+    fn interleaver(sink: &RecordingSink) -> Interleaver<()> {
+        let mut interleaver = Interleaver::new(Box::new(sink.clone()), ms(1000));
+        interleaver.add_stream(MediaStream::Audio);
+        interleaver.add_stream(camera());
+        interleaver.add_stream(MediaStream::Screen);
+        interleaver
+    }
+
+    #[test]
+    fn orders_packets_across_streams() {
+        let sink = RecordingSink::default();
+        let mut interleaver = interleaver(&sink);
+        // 音声が先に進み、カメラと画面は遅れて届く
+        for pts in [0, 10, 20, 30] {
+            interleaver.push(MediaStream::Audio, ms(pts), ());
+        }
+        interleaver.push(camera(), ms(5), ());
+        interleaver.push(MediaStream::Screen, ms(15), ());
+        interleaver.push(camera(), ms(38), ());
+        interleaver.pump_at(ms(40));
+        assert_eq!(sink.take(), vec![
+            (MediaStream::Audio, 0),
+            (camera(), 5),
+            (MediaStream::Audio, 10),
+            (MediaStream::Screen, 15),
+        ]);
+
+        interleaver.push(MediaStream::Screen, ms(40), ());
+        interleaver.pump_at(ms(45));
+        assert_eq!(sink.take(), vec![(MediaStream::Audio, 20), (MediaStream::Audio, 30)]);
+
+        interleaver.flush();
+        assert_eq!(sink.take(), vec![(camera(), 38), (MediaStream::Screen, 40)]);
+    }
+
+    #[test]
+    fn waits_for_streams_until_max_wait() {
+        let sink = RecordingSink::default();
+        let mut interleaver = interleaver(&sink);
+        interleaver.push(MediaStream::Audio, ms(100), ());
+        interleaver.push(camera(), ms(120), ());
+        interleaver.pump_at(ms(500));
+        assert!(sink.take().is_empty());
+
+        // 画面が止まっていても max_wait を過ぎたら出す
+        interleaver.pump_at(ms(1100));
+        assert_eq!(sink.take(), vec![(MediaStream::Audio, 100)]);
+    }
+
+    #[test]
+    fn removed_stream_no_longer_holds_back() {
+        let sink = RecordingSink::default();
+        let mut interleaver = interleaver(&sink);
+        interleaver.push(MediaStream::Screen, ms(10), ());
+        interleaver.push(MediaStream::Audio, ms(20), ());
+        interleaver.push(camera(), ms(30), ());
+        interleaver.pump_at(ms(30));
+        assert_eq!(sink.take(), vec![(MediaStream::Screen, 10)]);
+
+        interleaver.remove_stream(&MediaStream::Screen);
+        interleaver.pump_at(ms(30));
+        assert_eq!(sink.take(), vec![(MediaStream::Audio, 20)]);
+    }
+
+    #[test]
+    fn keeps_pts_monotonic_within_stream() {
+        let sink = RecordingSink::default();
+        let mut interleaver = Interleaver::new(Box::new(sink.clone()), ms(1000));
+        interleaver.push(camera(), ms(50), ());
+        // 補正を増やして時刻が戻っても、前のパケットより前にはならない
+        interleaver.push(camera(), ms(30), ());
+        interleaver.flush();
+        assert_eq!(sink.take(), vec![(camera(), 50), (camera(), 50)]);
+    }
+
+    #[test]
+    fn sync_monitor_reports_skew_against_audio() {
+        let monitor = SyncMonitor::default();
+        let mut interleaver = Interleaver::new(Box::new(monitor.clone()), ms(1000));
+        interleaver.push(MediaStream::Audio, ms(1000), ());
+        interleaver.push(camera(), ms(960), ());
+        interleaver.push(MediaStream::Screen, ms(1010), ());
+        interleaver.flush();
+        assert_eq!(monitor.skew_ms(), vec![(camera(), -40), (MediaStream::Screen, 10)]);
+    }
+}
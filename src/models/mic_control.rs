@@ -0,0 +1,92 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+/// マイクのミュートの操作方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MicMode {
+    /// ホットキーを押すたびにミュートを切り替える
+    #[default]
+    ToggleMute,
+    /// ホットキーを押している間だけマイクを有効にする
+    PushToTalk,
+}
+
+impl MicMode {
+    pub const ALL: [MicMode; 2] = [MicMode::ToggleMute, MicMode::PushToTalk];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MicMode::ToggleMute => "ミュート切り替え",
+            MicMode::PushToTalk => "プッシュトゥトーク",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MicControlSettings {
+    pub mode: MicMode,
+    /// プッシュトゥトークでキーを離してからミュートするまでの時間（語尾が切れないように）
+    pub release_delay_ms: u32,
+    /// ミュート中は配信画面にミュート表示を出す
+    pub show_overlay: bool,
+    pub overlay_text: String,
+}
+
+impl Default for MicControlSettings {
+    fn default() -> Self {
+        Self {
+            mode: MicMode::default(),
+            release_delay_ms: 200,
+            show_overlay: false,
+            overlay_text: "マイクミュート中".to_string(),
+        }
+    }
+}
+
+/// プッシュトゥトークのキーの状態からミュートするかを決める
+pub struct PushToTalk {
+    held: bool,
+    released_at: Option<Instant>,
+}
+
+impl PushToTalk {
+    pub fn new() -> Self {
+        Self {
+            held: false,
+            released_at: None,
+        }
+    }
+
+    /// 毎フレーム呼び出す。ミュートする場合は `true`
+    pub fn update(&mut self, held: bool, release_delay: Duration) -> bool {
+        if self.held && !held {
+            self.released_at = Some(Instant::now());
+        }
+        self.held = held;
+        if held {
+            self.released_at = None;
+            return false;
+        }
+        match self.released_at {
+            Some(released_at) if released_at.elapsed() < release_delay => false,
+            _ => {
+                self.released_at = None;
+                true
+            }
+        }
+    }
+}
+
+/// 配信状況タブに表示するマイクの状態
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MicIndicator {
+    pub muted: bool,
+    pub mode: MicMode,
+    /// 配信画面に出すミュート表示（表示しない設定の場合は `None`）
+    pub overlay_text: Option<String>,
+}
+
+/// 音声設定タブと配信状況タブで共有するマイクの状態
+pub type MicIndicatorFeed = Arc<Mutex<MicIndicator>>;
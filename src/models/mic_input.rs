@@ -0,0 +1,232 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use log::{info, error};
+use super::audio_decoder::Resampler;
+use super::audio_engine::ENGINE_SAMPLE_RATE;
+use super::mic_processing::{MicProcessingSettings, MicProcessor};
+use super::mixer::InputQueue;
+
+/// マイクの遅延の上限。これを超えてたまった分は捨てる
+const MAX_LATENCY: Duration = Duration::from_millis(200);
+
+pub fn input_device_names() -> Vec<String> {
+    cpal::default_host()
+        .input_devices()
+        .map(|devices| devices.filter_map(|device| device.name().ok()).collect())
+        .unwrap_or_default()
+}
+
+/// 名前で入力デバイスを探す。`None` の場合は既定のデバイス
+fn input_device(name: Option<&str>) -> Result<cpal::Device, String> {
+    let host = cpal::default_host();
+    match name {
+        None => host.default_input_device()
+            .ok_or_else(|| "入力デバイスが見つかりません".to_string()),
+        Some(name) => host.input_devices()
+            .map_err(|e| format!("入力デバイスの列挙に失敗: {}", e))?
+            .find(|device| device.name().map(|n| n == name).unwrap_or(false))
+            .ok_or_else(|| format!("入力デバイスが見つかりません: {}", name)),
+    }
+}
+
+/// 入力の形式を決める。リサンプリングを避けるためエンジンと同じサンプルレートを優先する
+fn negotiate_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConfig, String> {
+    let preferred = cpal::SampleRate(ENGINE_SAMPLE_RATE);
+    let supported = device.supported_input_configs()
+        .map(|configs| configs.collect::<Vec<_>>())
+        .unwrap_or_default();
+    let matching = supported
+        .iter()
+        .filter(|range| range.min_sample_rate() <= preferred && preferred <= range.max_sample_rate())
+        .min_by_key(|range| (range.sample_format() != cpal::SampleFormat::F32, range.channels()));
+    match matching {
+        Some(range) => Ok(range.with_sample_rate(preferred)),
+        None => device.default_input_config().map_err(|e| format!("入力設定の取得に失敗: {}", e)),
+    }
+}
+
+/// マイクの状況（UI 表示用）
+#[derive(Debug, Clone, Default)]
+pub struct MicStatus {
+    pub device: String,
+    /// 実際に開いた形式（例: 44100Hz 2ch）
+    pub format: String,
+    pub error: Option<String>,
+    /// 処理後の直近のピーク
+    pub peak: f32,
+}
+
+/// マイクから取り込んだ音声を処理チェーンに通してミキサーのマイクバスへ送る
+pub struct MicCapture {
+    processor: Arc<Mutex<MicProcessor>>,
+    status: Arc<Mutex<MicStatus>>,
+    running: Arc<AtomicBool>,
+    capture_thread: Option<thread::JoinHandle<()>>,
+}
+
+impl MicCapture {
+    pub fn new(settings: MicProcessingSettings) -> Self {
+        Self {
+            processor: Arc::new(Mutex::new(MicProcessor::new(settings, ENGINE_SAMPLE_RATE))),
+            status: Arc::new(Mutex::new(MicStatus::default())),
+            running: Arc::new(AtomicBool::new(false)),
+            capture_thread: None,
+        }
+    }
+
+    /// `device` が `None` の場合は既定のデバイス
+    pub fn start(&mut self, device: Option<String>, output: InputQueue) {
+        self.stop();
+
+        *self.status.lock().unwrap() = MicStatus::default();
+        let processor = Arc::clone(&self.processor);
+        let status = Arc::clone(&self.status);
+        let running = Arc::clone(&self.running);
+        running.store(true, Ordering::SeqCst);
+
+        let handle = thread::spawn(move || {
+            // cpal のストリームはスレッドをまたげないため、このスレッドで開いて保持する
+            let stream = match open_stream(device.as_deref(), processor, output, Arc::clone(&status)) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    error!("マイクを開けません: {}", e);
+                    status.lock().unwrap().error = Some(e);
+                    running.store(false, Ordering::SeqCst);
+                    return;
+                }
+            };
+            while running.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(50));
+            }
+            drop(stream);
+        });
+
+        self.capture_thread = Some(handle);
+    }
+
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.capture_thread.take() {
+            let _ = handle.join();
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn set_settings(&self, settings: MicProcessingSettings) {
+        self.processor.lock().unwrap().set_settings(settings);
+    }
+
+    pub fn status(&self) -> MicStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// （ゲートが開いているか, ゲインリダクション dB）
+    pub fn dynamics(&self) -> (bool, f32) {
+        let processor = self.processor.lock().unwrap();
+        (processor.gate_open(), processor.gain_reduction_db())
+    }
+}
+
+impl Drop for MicCapture {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn open_stream(
+    device_name: Option<&str>,
+    processor: Arc<Mutex<MicProcessor>>,
+    output: InputQueue,
+    status: Arc<Mutex<MicStatus>>,
+) -> Result<cpal::Stream, String> {
+    let device = input_device(device_name)?;
+    let name = device.name().unwrap_or_else(|_| "不明なデバイス".to_string());
+    let config = negotiate_config(&device)?;
+    let rate = config.sample_rate().0;
+    let channels = config.channels() as usize;
+    {
+        let mut status = status.lock().unwrap();
+        status.device = name.clone();
+        status.format = format!("{}Hz {}ch", rate, channels);
+    }
+
+    let chain = CaptureChain {
+        channels,
+        resampler: Resampler::new(rate, 1, ENGINE_SAMPLE_RATE, 1),
+        mono: Vec::new(),
+        resampled: Vec::new(),
+        stereo: Vec::new(),
+        processor,
+        output,
+        status,
+    };
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config.into(), chain),
+        cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config.into(), chain),
+        cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config.into(), chain),
+        format => return Err(format!("未対応のサンプル形式です: {:?}", format)),
+    }?;
+    stream.play().map_err(|e| format!("録音の開始に失敗: {}", e))?;
+    info!("マイク入力デバイス: {} ({}Hz, {}ch)", name, rate, channels);
+    Ok(stream)
+}
+
+/// 入力コールバックで行う変換（モノラル化 → 48kHz へ変換 → 処理チェーン → ステレオ化）
+struct CaptureChain {
+    channels: usize,
+    resampler: Resampler,
+    mono: Vec<f32>,
+    resampled: Vec<f32>,
+    stereo: Vec<f32>,
+    processor: Arc<Mutex<MicProcessor>>,
+    output: InputQueue,
+    status: Arc<Mutex<MicStatus>>,
+}
+
+impl CaptureChain {
+    fn push(&mut self, frames: impl Iterator<Item = f32>) {
+        self.mono.clear();
+        let mut frames = frames.peekable();
+        while frames.peek().is_some() {
+            let sum: f32 = frames.by_ref().take(self.channels).sum();
+            self.mono.push(sum / self.channels as f32);
+        }
+
+        self.resampled.clear();
+        self.resampler.process(&self.mono, &mut self.resampled);
+        self.processor.lock().unwrap().process(&mut self.resampled);
+
+        self.stereo.clear();
+        self.stereo.extend(self.resampled.iter().flat_map(|&sample| [sample, sample]));
+        self.output.push_converted(&self.stereo);
+        self.output.trim(MAX_LATENCY);
+
+        let peak = self.resampled.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        self.status.lock().unwrap().peak = peak;
+    }
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut chain: CaptureChain,
+) -> Result<cpal::Stream, String>
+where
+    T: cpal::SizedSample,
+    f32: cpal::FromSample<T>,
+{
+    device.build_input_stream(
+        config,
+        move |data: &[T], _| {
+            chain.push(data.iter().map(|&sample| cpal::Sample::to_sample::<f32>(sample)));
+        },
+        |e| error!("音声入力エラー: {}", e),
+        None,
+    ).map_err(|e| format!("入力ストリームの作成に失敗: {}", e))
+}
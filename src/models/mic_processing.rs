@@ -0,0 +1,338 @@
+use serde::{Deserialize, Serialize};
+use super::dsp::{db_to_gain, gain_to_db, smoothing_coefficient, Biquad};
+
+/// ゲートが閉じているときの減衰量
+const GATE_FLOOR_DB: f32 = -80.0;
+/// レベル検出の平滑化時間
+const DETECTOR_MS: f32 = 10.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoiseGateSettings {
+    pub enabled: bool,
+    pub threshold_db: f32,
+    pub attack_ms: f32,
+    /// レベルが下がってから閉じ始めるまでの時間
+    pub hold_ms: f32,
+    pub release_ms: f32,
+}
+
+impl Default for NoiseGateSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_db: -50.0,
+            attack_ms: 2.0,
+            hold_ms: 150.0,
+            release_ms: 100.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HighPassSettings {
+    pub enabled: bool,
+    pub cutoff_hz: f32,
+}
+
+impl Default for HighPassSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cutoff_hz: 80.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressorSettings {
+    pub enabled: bool,
+    pub threshold_db: f32,
+    pub ratio: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+    pub makeup_db: f32,
+}
+
+impl Default for CompressorSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_db: -20.0,
+            ratio: 3.0,
+            attack_ms: 5.0,
+            release_ms: 120.0,
+            makeup_db: 3.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimiterSettings {
+    pub enabled: bool,
+    pub ceiling_db: f32,
+    pub release_ms: f32,
+}
+
+impl Default for LimiterSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ceiling_db: -1.0,
+            release_ms: 50.0,
+        }
+    }
+}
+
+/// マイクの処理チェーンの設定。ゲート → ハイパス → コンプレッサー → ゲイン → リミッターの順に処理する
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MicProcessingSettings {
+    pub gate: NoiseGateSettings,
+    pub high_pass: HighPassSettings,
+    pub compressor: CompressorSettings,
+    pub limiter: LimiterSettings,
+    pub gain_db: f32,
+}
+
+/// マイク入力（モノラル）の処理チェーン。デバイスに依存しないので合成した PCM でも動作を確かめられる
+pub struct MicProcessor {
+    settings: MicProcessingSettings,
+    sample_rate: u32,
+    detector: f32,
+    gate_gain: f32,
+    hold_remaining: u32,
+    high_pass: Biquad,
+    /// コンプレッサーのゲインリダクション（dB、正の値）
+    reduction_db: f32,
+    limiter_gain: f32,
+}
+
+impl MicProcessor {
+    pub fn new(settings: MicProcessingSettings, sample_rate: u32) -> Self {
+        Self {
+            high_pass: Biquad::high_pass(settings.high_pass.cutoff_hz, std::f32::consts::FRAC_1_SQRT_2, sample_rate),
+            settings,
+            sample_rate,
+            detector: 0.0,
+            gate_gain: 0.0,
+            hold_remaining: 0,
+            reduction_db: 0.0,
+            limiter_gain: 1.0,
+        }
+    }
+
+    /// 設定を変更する。フィルターの状態は引き継ぐ
+    pub fn set_settings(&mut self, settings: MicProcessingSettings) {
+        if settings.high_pass.cutoff_hz != self.settings.high_pass.cutoff_hz {
+            let coefficients = Biquad::high_pass(settings.high_pass.cutoff_hz, std::f32::consts::FRAC_1_SQRT_2, self.sample_rate);
+            self.high_pass.set_coefficients(&coefficients);
+        }
+        self.settings = settings;
+    }
+
+    /// ゲートが開いているか（UI 表示用）
+    pub fn gate_open(&self) -> bool {
+        !self.settings.gate.enabled || self.gate_gain > 0.5
+    }
+
+    /// コンプレッサーとリミッターによる現在の減衰量（dB）
+    pub fn gain_reduction_db(&self) -> f32 {
+        self.reduction_db - gain_to_db(self.limiter_gain)
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        let rate = self.sample_rate;
+        let detector_coef = smoothing_coefficient(DETECTOR_MS, rate);
+        let gate = &self.settings.gate;
+        let gate_threshold = db_to_gain(gate.threshold_db);
+        let gate_floor = db_to_gain(GATE_FLOOR_DB);
+        let gate_attack = smoothing_coefficient(gate.attack_ms, rate);
+        let gate_release = smoothing_coefficient(gate.release_ms, rate);
+        let hold_samples = (gate.hold_ms * 0.001 * rate as f32) as u32;
+        let compressor = &self.settings.compressor;
+        let comp_attack = smoothing_coefficient(compressor.attack_ms, rate);
+        let comp_release = smoothing_coefficient(compressor.release_ms, rate);
+        let makeup = db_to_gain(compressor.makeup_db);
+        let gain = db_to_gain(self.settings.gain_db);
+        let ceiling = db_to_gain(self.settings.limiter.ceiling_db);
+        let limiter_release = smoothing_coefficient(self.settings.limiter.release_ms, rate);
+
+        for sample in samples.iter_mut() {
+            let mut x = *sample;
+
+            // レベル検出（立ち上がりは即時、減衰は平滑化）
+            let level = x.abs();
+            self.detector = if level > self.detector { level } else { level + detector_coef * (self.detector - level) };
+
+            if gate.enabled {
+                let target = if self.detector >= gate_threshold {
+                    self.hold_remaining = hold_samples;
+                    1.0
+                } else if self.hold_remaining > 0 {
+                    self.hold_remaining -= 1;
+                    1.0
+                } else {
+                    gate_floor
+                };
+                let coef = if target > self.gate_gain { gate_attack } else { gate_release };
+                self.gate_gain = target + coef * (self.gate_gain - target);
+                x *= self.gate_gain;
+            }
+
+            if self.settings.high_pass.enabled {
+                x = self.high_pass.process(x);
+            }
+
+            if compressor.enabled {
+                let over = gain_to_db(self.detector) - compressor.threshold_db;
+                let target = if over > 0.0 { over * (1.0 - 1.0 / compressor.ratio.max(1.0)) } else { 0.0 };
+                let coef = if target > self.reduction_db { comp_attack } else { comp_release };
+                self.reduction_db = target + coef * (self.reduction_db - target);
+                x *= db_to_gain(-self.reduction_db) * makeup;
+            } else {
+                self.reduction_db = 0.0;
+            }
+
+            x *= gain;
+
+            if self.settings.limiter.enabled {
+                // 先読みなしのピークリミッター。超えた分は即座に下げ、ゆっくり戻す
+                let peak = x.abs();
+                let required = if peak > ceiling { ceiling / peak } else { 1.0 };
+                self.limiter_gain = if required < self.limiter_gain {
+                    required
+                } else {
+                    1.0 + limiter_release * (self.limiter_gain - 1.0)
+                };
+                x = (x * self.limiter_gain).clamp(-ceiling, ceiling);
+            } else {
+                self.limiter_gain = 1.0;
+            }
+
+            *sample = x;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48_000;
+
+    /// すべての段を無効にした設定（確かめる段だけ有効にする）
+    fn bypass() -> MicProcessingSettings {
+        MicProcessingSettings {
+            gate: NoiseGateSettings { enabled: false, ..Default::default() },
+            high_pass: HighPassSettings { enabled: false, ..Default::default() },
+            compressor: CompressorSettings { enabled: false, ..Default::default() },
+            limiter: LimiterSettings { enabled: false, ..Default::default() },
+            gain_db: 0.0,
+        }
+    }
+
+    fn sine(frequency: f32, amplitude: f32, seconds: f32) -> Vec<f32> {
+        let len = (seconds * RATE as f32) as usize;
+        (0..len)
+            .map(|i| amplitude * (std::f32::consts::TAU * frequency * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()))
+    }
+
+    /// 後半（応答が落ち着いた区間）のピーク
+    fn settled_peak(samples: &[f32]) -> f32 {
+        peak(&samples[samples.len() / 2..])
+    }
+
+    #[test]
+    fn gate_closes_below_threshold() {
+        let mut settings = bypass();
+        settings.gate = NoiseGateSettings { enabled: true, threshold_db: -40.0, ..Default::default() };
+        let mut processor = MicProcessor::new(settings, RATE);
+
+        // しきい値より大きい音では開いている
+        let mut loud = sine(440.0, db_to_gain(-20.0), 0.5);
+        processor.process(&mut loud);
+        assert!(processor.gate_open());
+        assert!((settled_peak(&loud) - db_to_gain(-20.0)).abs() < 0.01);
+
+        // しきい値より小さい音が続くとホールド・リリースのあとに閉じる
+        let mut quiet = sine(440.0, db_to_gain(-60.0), 2.0);
+        processor.process(&mut quiet);
+        assert!(!processor.gate_open());
+        let tail = &quiet[quiet.len() - RATE as usize / 10..];
+        assert!(peak(tail) < db_to_gain(-60.0) * db_to_gain(-60.0));
+    }
+
+    #[test]
+    fn high_pass_removes_dc_and_low_frequencies() {
+        let mut settings = bypass();
+        settings.high_pass = HighPassSettings { enabled: true, cutoff_hz: 80.0 };
+
+        let mut dc = vec![0.5; RATE as usize];
+        MicProcessor::new(settings.clone(), RATE).process(&mut dc);
+        assert!(settled_peak(&dc) < 1e-3);
+
+        let mut rumble = sine(10.0, 0.5, 1.0);
+        MicProcessor::new(settings.clone(), RATE).process(&mut rumble);
+        assert!(settled_peak(&rumble) < 0.5 * db_to_gain(-30.0));
+
+        // 声の帯域はほぼそのまま通す
+        let mut voice = sine(1000.0, 0.5, 1.0);
+        MicProcessor::new(settings, RATE).process(&mut voice);
+        assert!((settled_peak(&voice) - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn compressor_reduces_by_ratio_above_threshold() {
+        let mut settings = bypass();
+        settings.compressor = CompressorSettings {
+            enabled: true,
+            threshold_db: -20.0,
+            ratio: 4.0,
+            attack_ms: 1.0,
+            release_ms: 100.0,
+            makeup_db: 0.0,
+        };
+
+        // 一定レベルの入力（検出器が揺れないよう直流で確かめる）
+        let mut over = vec![db_to_gain(-8.0); RATE as usize];
+        let mut processor = MicProcessor::new(settings.clone(), RATE);
+        processor.process(&mut over);
+        // しきい値を 12dB 超えた入力は 12 / 4 = 3dB 超えまで下がる
+        let output_db = gain_to_db(*over.last().unwrap());
+        assert!((output_db - -17.0).abs() < 0.1, "{}", output_db);
+        assert!((processor.gain_reduction_db() - 9.0).abs() < 0.1);
+
+        // しきい値より下では変えない
+        let mut under = vec![db_to_gain(-30.0); RATE as usize];
+        let mut processor = MicProcessor::new(settings, RATE);
+        processor.process(&mut under);
+        assert!((gain_to_db(*under.last().unwrap()) - -30.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn limiter_never_exceeds_ceiling() {
+        let mut settings = bypass();
+        settings.limiter = LimiterSettings { enabled: true, ceiling_db: -1.0, release_ms: 50.0 };
+        settings.gain_db = 12.0;
+        let ceiling = db_to_gain(-1.0);
+
+        let mut processor = MicProcessor::new(settings, RATE);
+        // 急な立ち上がりを含む大きな音と小さな音の繰り返し
+        for amplitude in [0.9, 0.05, 1.0, 0.2, 0.8] {
+            let mut samples = sine(220.0, amplitude, 0.2);
+            samples[0] = amplitude;
+            processor.process(&mut samples);
+            assert!(peak(&samples) <= ceiling + 1e-6, "{} > {}", peak(&samples), ceiling);
+        }
+        assert!(processor.gain_reduction_db() > 0.0);
+    }
+}
@@ -0,0 +1,400 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use super::audio_decoder::Resampler;
+use super::audio_engine::{ENGINE_CHANNELS, ENGINE_SAMPLE_RATE};
+use super::dsp::{db_to_gain, smoothing_coefficient};
+use super::effects_chain::{EffectsChain, EffectsChainSettings};
+use super::level_meter::{LevelMeter, LevelMeters};
+use super::loudness::{MasterLimiterSettings, TruePeakLimiter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BusKind {
+    Bgm,
+    Effects,
+    Mic,
+    Tts,
+}
+
+impl BusKind {
+    pub const ALL: [BusKind; 4] = [BusKind::Bgm, BusKind::Effects, BusKind::Mic, BusKind::Tts];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BusKind::Bgm => "BGM",
+            BusKind::Effects => "効果音",
+            BusKind::Mic => "マイク",
+            BusKind::Tts => "読み上げ",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BusSettings {
+    pub gain: f32,
+    pub mute: bool,
+    /// いずれかのバスがソロの場合、ソロでないバスは聞こえなくなる
+    pub solo: bool,
+    /// -1.0（左）〜 1.0（右）
+    pub pan: f32,
+}
+
+impl Default for BusSettings {
+    fn default() -> Self {
+        Self {
+            gain: 1.0,
+            mute: false,
+            solo: false,
+            pan: 0.0,
+        }
+    }
+}
+
+impl BusSettings {
+    /// パン（バランス方式）を含めた左右のゲイン
+    fn channel_gains(&self) -> [f32; 2] {
+        let pan = self.pan.clamp(-1.0, 1.0);
+        [self.gain * (1.0 - pan).min(1.0), self.gain * (1.0 + pan).min(1.0)]
+    }
+}
+
+/// マイク・読み上げの音声があるとき BGM を下げる（サイドチェイン・ダッキング）
+#[derive(Debug, Clone)]
+pub struct DuckingSettings {
+    pub enabled: bool,
+    /// マイクのレベルがこれを超えたら下げる
+    pub threshold_db: f32,
+    /// BGM を下げる量
+    pub amount_db: f32,
+    pub attack_ms: f32,
+    /// 声が途切れてから戻し始めるまでの時間
+    pub hold_ms: f32,
+    pub release_ms: f32,
+    pub on_mic: bool,
+    pub on_tts: bool,
+}
+
+impl Default for DuckingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_db: -40.0,
+            amount_db: 12.0,
+            attack_ms: 50.0,
+            hold_ms: 300.0,
+            release_ms: 600.0,
+            on_mic: true,
+            on_tts: true,
+        }
+    }
+}
+
+/// ダッキングの現在の状態（UI 表示用）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DuckingStatus {
+    /// 下げるきっかけになっているバス
+    pub trigger: Option<BusKind>,
+    /// 現在の減衰量（dB）
+    pub reduction_db: f32,
+}
+
+/// バスごとの送り先（配信のミックスとモニター）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoutingSettings {
+    /// `BusKind::ALL` の順。マスター（配信）に送るバス
+    pub stream: [bool; 4],
+    /// `BusKind::ALL` の順。モニターに送るバス（マイクを送ると自分の声が返ってくる）
+    pub monitor: [bool; 4],
+    pub monitor_gain: f32,
+}
+
+impl Default for RoutingSettings {
+    fn default() -> Self {
+        Self {
+            stream: [true; 4],
+            monitor: [true, true, false, true],
+            monitor_gain: 1.0,
+        }
+    }
+}
+
+impl RoutingSettings {
+    pub fn stream_mut(&mut self, kind: BusKind) -> &mut bool {
+        &mut self.stream[kind.index()]
+    }
+
+    pub fn monitor_mut(&mut self, kind: BusKind) -> &mut bool {
+        &mut self.monitor[kind.index()]
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MixerSettings {
+    pub buses: [BusSettings; 4],
+    /// マスターはゲイン・ミュート・パンのみ使用する
+    pub master: BusSettings,
+    pub ducking: DuckingSettings,
+    pub master_limiter: MasterLimiterSettings,
+    /// バスごとのエフェクトチェーン（フェーダー・ダッキングの前にかける）
+    pub effects: [EffectsChainSettings; 4],
+    pub routing: RoutingSettings,
+}
+
+impl MixerSettings {
+    pub fn bus(&self, kind: BusKind) -> &BusSettings {
+        &self.buses[kind.index()]
+    }
+
+    pub fn bus_mut(&mut self, kind: BusKind) -> &mut BusSettings {
+        &mut self.buses[kind.index()]
+    }
+
+    pub fn effects_mut(&mut self, kind: BusKind) -> &mut EffectsChainSettings {
+        &mut self.effects[kind.index()]
+    }
+
+    fn is_audible(&self, kind: BusKind) -> bool {
+        let any_solo = self.buses.iter().any(|bus| bus.solo);
+        let bus = self.bus(kind);
+        !bus.mute && (!any_solo || bus.solo)
+    }
+}
+
+/// 音源をバスごとに集め、ゲイン・パンをかけてマスターへまとめる
+#[derive(Default)]
+pub struct Mixer {
+    pub settings: MixerSettings,
+    bus_buffers: [Vec<f32>; 4],
+    /// モニターに送る効果音（効果音ごとに送り先が違うため、バスとは別に集める）
+    monitor_effects: Vec<f32>,
+    /// モニターの出力
+    monitor: Vec<f32>,
+    effects: [EffectsChain; 4],
+    ducking: DuckingStatus,
+    /// ダッキングを続ける残りフレーム数
+    duck_hold: usize,
+    limiter: TruePeakLimiter,
+    /// 各バスとマスター（最後）のメーター
+    meters: [LevelMeter; 5],
+    level_meters: LevelMeters,
+}
+
+impl Mixer {
+    /// ブロックの処理を始める。各バスのバッファを無音で初期化する
+    pub fn begin(&mut self, len: usize) {
+        for buffer in self.bus_buffers.iter_mut().chain([&mut self.monitor_effects, &mut self.monitor]) {
+            buffer.clear();
+            buffer.resize(len, 0.0);
+        }
+    }
+
+    /// 音源を加算するバスのバッファ
+    pub fn bus_buffer(&mut self, kind: BusKind) -> &mut [f32] {
+        &mut self.bus_buffers[kind.index()]
+    }
+
+    /// モニターに送る効果音を加算するバッファ（効果音バスのフェーダー・パンをかけてからモニターへ送る）
+    pub fn monitor_effects_buffer(&mut self) -> &mut [f32] {
+        &mut self.monitor_effects
+    }
+
+    /// `mix_down` の後のモニターの音
+    pub fn monitor_output(&self) -> &[f32] {
+        &self.monitor
+    }
+
+    /// `mix_down` の後のバスの音（エフェクト・フェーダー・パンの後）
+    pub fn bus_output(&self, kind: BusKind) -> &[f32] {
+        &self.bus_buffers[kind.index()]
+    }
+
+    pub fn ducking(&self) -> DuckingStatus {
+        self.ducking
+    }
+
+    /// UI から読み取るメーターの値
+    pub fn level_meters(&self) -> LevelMeters {
+        self.level_meters.clone()
+    }
+
+    /// マスターのリミッターによる現在の減衰量（dB）
+    pub fn limiter_reduction_db(&self) -> f32 {
+        if self.settings.master_limiter.enabled { self.limiter.reduction_db() } else { 0.0 }
+    }
+
+    /// サイドチェインのバスに声があるかを調べ、BGM バスを下げる
+    fn apply_ducking(&mut self) {
+        let settings = &self.settings.ducking;
+        let threshold = db_to_gain(settings.threshold_db);
+        let frames = self.bus_buffers[BusKind::Bgm.index()].len() / ENGINE_CHANNELS;
+
+        // 読み上げは音が出ていれば話している、マイクはしきい値で判定する
+        let active = |kind: BusKind, threshold: f32| {
+            self.settings.is_audible(kind) && self.bus_buffers[kind.index()].iter().any(|sample| sample.abs() > threshold)
+        };
+        let trigger = if !settings.enabled {
+            None
+        } else if settings.on_tts && active(BusKind::Tts, db_to_gain(-60.0)) {
+            Some(BusKind::Tts)
+        } else if settings.on_mic && active(BusKind::Mic, threshold) {
+            Some(BusKind::Mic)
+        } else {
+            None
+        };
+
+        let target = if trigger.is_some() {
+            self.duck_hold = (settings.hold_ms * 0.001 * ENGINE_SAMPLE_RATE as f32) as usize;
+            self.ducking.trigger = trigger;
+            settings.amount_db
+        } else if self.duck_hold > 0 {
+            self.duck_hold = self.duck_hold.saturating_sub(frames);
+            settings.amount_db
+        } else {
+            self.ducking.trigger = None;
+            0.0
+        };
+        if !settings.enabled {
+            self.duck_hold = 0;
+        }
+
+        let attack = smoothing_coefficient(settings.attack_ms, ENGINE_SAMPLE_RATE);
+        let release = smoothing_coefficient(settings.release_ms, ENGINE_SAMPLE_RATE);
+        let coef = if target > self.ducking.reduction_db { attack } else { release };
+        let mut reduction = self.ducking.reduction_db;
+        if reduction.abs() < 0.01 && target == 0.0 {
+            self.ducking.reduction_db = 0.0;
+            return;
+        }
+        for frame in self.bus_buffers[BusKind::Bgm.index()].chunks_mut(ENGINE_CHANNELS) {
+            reduction = target + coef * (reduction - target);
+            let gain = db_to_gain(-reduction);
+            for sample in frame {
+                *sample *= gain;
+            }
+        }
+        self.ducking.reduction_db = reduction;
+    }
+
+    /// バスをまとめてマスターの出力を `output` に書き込む
+    pub fn mix_down(&mut self, output: &mut [f32]) {
+        for kind in BusKind::ALL {
+            self.effects[kind.index()].process(&mut self.bus_buffers[kind.index()], &self.settings.effects[kind.index()]);
+        }
+        self.apply_ducking();
+        output.fill(0.0);
+        for kind in BusKind::ALL {
+            // メーターはフェーダー後の値を表示する（聞こえないバスは無音）
+            let gains = if self.settings.is_audible(kind) { self.settings.bus(kind).channel_gains() } else { [0.0; 2] };
+            let buffer = &mut self.bus_buffers[kind.index()];
+            for frame in buffer.chunks_mut(ENGINE_CHANNELS) {
+                for (channel, sample) in frame.iter_mut().enumerate() {
+                    *sample *= gains[channel];
+                }
+            }
+            self.meters[kind.index()].measure(buffer);
+            self.level_meters.update(Some(kind), &mut self.meters[kind.index()]);
+            if self.settings.routing.stream[kind.index()] {
+                add_into(output, buffer);
+            }
+            if self.settings.routing.monitor[kind.index()] && kind != BusKind::Effects {
+                add_into(&mut self.monitor, buffer);
+            }
+        }
+        self.mix_monitor();
+
+        let master = &self.settings.master;
+        let gains = if master.mute { [0.0; 2] } else { master.channel_gains() };
+        for frame in output.chunks_mut(ENGINE_CHANNELS) {
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                *sample *= gains[channel];
+            }
+        }
+        // リミッターの後は 0dBFS を超えないため、クリップはリミッター前で判定する
+        if output.iter().any(|sample| sample.abs() >= 1.0) {
+            self.meters[4].mark_clipped();
+        }
+
+        if self.settings.master_limiter.enabled {
+            self.limiter.process(output, &self.settings.master_limiter);
+        } else {
+            for sample in output.iter_mut() {
+                *sample = sample.clamp(-1.0, 1.0);
+            }
+        }
+        self.meters[4].measure(output);
+        self.level_meters.update(None, &mut self.meters[4]);
+    }
+
+    /// モニター用の効果音を加え、モニターの音量をかける
+    fn mix_monitor(&mut self) {
+        let routing = &self.settings.routing;
+        if routing.monitor[BusKind::Effects.index()] && self.settings.is_audible(BusKind::Effects) {
+            let gains = self.settings.bus(BusKind::Effects).channel_gains();
+            for (frame, effects) in self.monitor.chunks_mut(ENGINE_CHANNELS).zip(self.monitor_effects.chunks(ENGINE_CHANNELS)) {
+                for (channel, (out, sample)) in frame.iter_mut().zip(effects).enumerate() {
+                    *out += sample * gains[channel];
+                }
+            }
+        }
+        for sample in &mut self.monitor {
+            *sample = (*sample * routing.monitor_gain).clamp(-1.0, 1.0);
+        }
+    }
+}
+
+fn add_into(output: &mut [f32], samples: &[f32]) {
+    for (out, sample) in output.iter_mut().zip(samples) {
+        *out += sample;
+    }
+}
+
+/// 別スレッド（マイク・読み上げ）からエンジンへ PCM を渡すキュー
+#[derive(Clone, Default)]
+pub struct InputQueue {
+    buffer: Arc<Mutex<VecDeque<f32>>>,
+}
+
+impl InputQueue {
+    /// 任意の形式の PCM をエンジンの形式に変換して追加する
+    pub fn push(&self, samples: &[f32], sample_rate: u32, channels: usize) {
+        let mut converted = Vec::new();
+        Resampler::new(sample_rate, channels, ENGINE_SAMPLE_RATE, ENGINE_CHANNELS).process(samples, &mut converted);
+        self.push_converted(&converted);
+    }
+
+    /// エンジンの形式（48kHz ステレオ）の PCM を追加する
+    pub fn push_converted(&self, samples: &[f32]) {
+        self.buffer.lock().unwrap().extend(samples.iter().copied());
+    }
+
+    /// たまっている分が `max` を超えたら古い方から捨てる（入力デバイスとエンジンの時計のずれで遅延が積み上がるのを防ぐ）
+    pub fn trim(&self, max: Duration) {
+        let max_samples = (max.as_secs_f64() * ENGINE_SAMPLE_RATE as f64) as usize * ENGINE_CHANNELS;
+        let mut buffer = self.buffer.lock().unwrap();
+        if buffer.len() > max_samples {
+            let excess = buffer.len() - max_samples;
+            buffer.drain(..excess);
+        }
+    }
+
+    /// まだ再生されていない長さ
+    pub fn queued(&self) -> Duration {
+        let frames = self.buffer.lock().unwrap().len() / ENGINE_CHANNELS;
+        Duration::from_secs_f64(frames as f64 / ENGINE_SAMPLE_RATE as f64)
+    }
+
+    /// たまっている分を `output` に加算する
+    pub fn mix_into(&self, output: &mut [f32]) {
+        let mut buffer = self.buffer.lock().unwrap();
+        let available = buffer.len().min(output.len());
+        for (out, sample) in output.iter_mut().zip(buffer.drain(..available)) {
+            *out += sample;
+        }
+    }
+}
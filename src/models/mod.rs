@@ -1,9 +1,44 @@
 pub mod stream;
 pub mod audio;
+pub mod audio_output;
+pub mod audio_decoder;
+pub mod audio_engine;
+pub mod audio_recorder;
+pub mod flac_encoder;
+pub mod audio_config;
+pub mod bgm_player;
+pub mod mixer;
+pub mod loudness;
+pub mod level_meter;
+pub mod dsp;
+pub mod effects_chain;
+pub mod mic_control;
+pub mod mic_input;
+pub mod mic_processing;
+pub mod now_playing;
+pub mod playlist;
+pub mod soundboard;
+pub mod track_license;
 pub mod banner;
 pub mod comment;
+pub mod chat_replay;
+pub mod chat_triggers;
+pub mod chat_source;
+pub mod youtube_chat;
+pub mod twitch_chat;
+pub mod viewer_stats;
+pub mod spam_filter;
+pub mod tts;
+pub mod tts_queue;
+pub mod pronunciation;
+pub mod voice_assignment;
+pub mod settings_store;
+pub mod hotkey;
+#[cfg(target_os = "linux")]
+mod hotkey_x11;
 
 pub mod camera;
 pub mod screen_capture;
 pub mod video_frame;
 pub mod video_config;
+pub mod media_clock;
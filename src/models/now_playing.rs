@@ -0,0 +1,145 @@
+use std::fs::{self, File};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, MetadataRevision, StandardTagKey};
+use symphonia::core::probe::Hint;
+use super::audio::BGMTrack;
+
+/// 音声ファイルのタグ（ID3・Vorbis コメント・FLAC）。ない項目は空文字列
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrackMetadata {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    /// ライセンスの URL（LICENSE・WCOP タグ）
+    pub license_url: String,
+}
+
+impl TrackMetadata {
+    /// ファイルのタグを読む。ファイルの先頭のタグ（ID3v2 など）とコンテナ内のタグの両方を見る
+    pub fn read(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("ファイルを開けません ({}): {}", path, e))?;
+        let stream = MediaSourceStream::new(Box::new(file), Default::default());
+        let mut hint = Hint::new();
+        if let Some(extension) = Path::new(path).extension().and_then(|e| e.to_str()) {
+            hint.with_extension(extension);
+        }
+        let mut probed = symphonia::default::get_probe()
+            .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+            .map_err(|e| format!("対応していない形式です ({}): {}", path, e))?;
+
+        let mut metadata = TrackMetadata::default();
+        if let Some(revision) = probed.format.metadata().current() {
+            metadata.fill_from(revision);
+        }
+        if let Some(revision) = probed.metadata.get().as_ref().and_then(|log| log.current()) {
+            metadata.fill_from(revision);
+        }
+        Ok(metadata)
+    }
+
+    /// まだ空の項目をタグで埋める
+    fn fill_from(&mut self, revision: &MetadataRevision) {
+        for tag in revision.tags() {
+            let field = match tag.std_key {
+                Some(StandardTagKey::TrackTitle) => &mut self.title,
+                Some(StandardTagKey::Artist) => &mut self.artist,
+                Some(StandardTagKey::Album) => &mut self.album,
+                Some(StandardTagKey::License | StandardTagKey::UrlCopyright) => &mut self.license_url,
+                _ if tag.key.eq_ignore_ascii_case("LICENSE") => &mut self.license_url,
+                _ => continue,
+            };
+            let value = tag.value.to_string();
+            if field.is_empty() && !value.trim().is_empty() {
+                *field = value.trim().to_string();
+            }
+        }
+    }
+}
+
+/// 再生中の曲
+#[derive(Debug, Clone, PartialEq)]
+pub struct NowPlaying {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub license_url: String,
+}
+
+impl NowPlaying {
+    /// タグにタイトルがない場合はファイル名をタイトルにする
+    pub fn from_track(track: &BGMTrack) -> Self {
+        let metadata = &track.metadata;
+        Self {
+            title: if metadata.title.is_empty() { track.name.clone() } else { metadata.title.clone() },
+            artist: metadata.artist.clone(),
+            album: metadata.album.clone(),
+            license_url: metadata.license_url.clone(),
+        }
+    }
+
+    /// 「タイトル - アーティスト」
+    pub fn label(&self) -> String {
+        if self.artist.is_empty() {
+            self.title.clone()
+        } else {
+            format!("{} - {}", self.title, self.artist)
+        }
+    }
+}
+
+/// 配信状況タブ・バナーと共有する再生中の曲（停止中は `None`）
+pub type NowPlayingFeed = Arc<Mutex<Option<NowPlaying>>>;
+
+/// 画面上のテキスト・バナーで使える変数
+pub const VARIABLES: [(&str, &str); 5] = [
+    ("{now_playing}", "タイトル - アーティスト"),
+    ("{title}", "タイトル"),
+    ("{artist}", "アーティスト"),
+    ("{album}", "アルバム"),
+    ("{license_url}", "ライセンスの URL"),
+];
+
+/// テキストの変数を再生中の曲で置き換える。停止中は空文字列にする
+pub fn expand_variables(text: &str, now_playing: Option<&NowPlaying>) -> String {
+    let value = |f: fn(&NowPlaying) -> String| now_playing.map(f).unwrap_or_default();
+    text.replace("{now_playing}", &value(NowPlaying::label))
+        .replace("{title}", &value(|n| n.title.clone()))
+        .replace("{artist}", &value(|n| n.artist.clone()))
+        .replace("{album}", &value(|n| n.album.clone()))
+        .replace("{license_url}", &value(|n| n.license_url.clone()))
+}
+
+/// 再生中の曲をテキストファイルに書き出す設定（配信ソフトのテキストソースから読む）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NowPlayingSettings {
+    pub write_file: bool,
+    pub file_path: String,
+    /// `VARIABLES` を使える
+    pub template: String,
+}
+
+impl Default for NowPlayingSettings {
+    fn default() -> Self {
+        Self {
+            write_file: false,
+            file_path: "now_playing.txt".to_string(),
+            template: "♪ {title} / {artist}".to_string(),
+        }
+    }
+}
+
+impl NowPlayingSettings {
+    /// 曲が変わったときに呼び出す。停止中は空のファイルにする
+    pub fn write(&self, now_playing: Option<&NowPlaying>) -> Result<(), String> {
+        let text = if now_playing.is_some() { expand_variables(&self.template, now_playing) } else { String::new() };
+        if let Some(parent) = Path::new(&self.file_path).parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| format!("ディレクトリの作成に失敗: {}", e))?;
+        }
+        fs::write(&self.file_path, text).map_err(|e| format!("{} への書き込みに失敗: {}", self.file_path, e))
+    }
+}
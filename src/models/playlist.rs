@@ -0,0 +1,119 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// BGM として読み込める拡張子
+const AUDIO_EXTENSIONS: [&str; 5] = ["wav", "mp3", "ogg", "flac", "oga"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RepeatMode {
+    Off,
+    One,
+    All,
+}
+
+impl RepeatMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RepeatMode::Off => "なし",
+            RepeatMode::One => "1曲リピート",
+            RepeatMode::All => "全曲リピート",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransitionMode {
+    /// 曲間に無音を入れずに次の曲へ進む
+    Gapless,
+    Crossfade,
+}
+
+impl TransitionMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TransitionMode::Gapless => "ギャップレス",
+            TransitionMode::Crossfade => "クロスフェード",
+        }
+    }
+}
+
+/// 再生エンジンに渡す曲送りの設定
+#[derive(Debug, Clone)]
+pub struct PlaylistOptions {
+    pub shuffle: bool,
+    pub repeat: RepeatMode,
+    pub transition: TransitionMode,
+    pub crossfade: Duration,
+}
+
+impl Default for PlaylistOptions {
+    fn default() -> Self {
+        Self {
+            shuffle: false,
+            repeat: RepeatMode::All,
+            transition: TransitionMode::Gapless,
+            crossfade: Duration::from_secs(3),
+        }
+    }
+}
+
+/// BGM のプレイリスト。曲は `BGMTrack::id` で参照する
+#[derive(Debug, Clone)]
+pub struct Playlist {
+    pub name: String,
+    pub track_ids: Vec<u64>,
+    pub options: PlaylistOptions,
+}
+
+impl Playlist {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            track_ids: Vec::new(),
+            options: PlaylistOptions::default(),
+        }
+    }
+
+    /// `from` の曲を `to` の位置へ移動する
+    pub fn move_track(&mut self, from: usize, to: usize) {
+        if from < self.track_ids.len() && to < self.track_ids.len() && from != to {
+            let id = self.track_ids.remove(from);
+            self.track_ids.insert(to, id);
+        }
+    }
+}
+
+fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| AUDIO_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// フォルダー直下の音声ファイルをファイル名順に列挙する
+pub fn scan_folder(folder: &str) -> Result<Vec<String>, String> {
+    let entries = fs::read_dir(folder).map_err(|e| format!("フォルダーを開けません ({}): {}", folder, e))?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_audio_file(path))
+        .collect();
+    files.sort();
+    Ok(files.into_iter().map(|path| path.to_string_lossy().into_owned()).collect())
+}
+
+/// M3U / M3U8 プレイリストを読み込む。相対パスはプレイリストの場所を基準に解決する
+pub fn parse_m3u(path: &str) -> Result<Vec<String>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("プレイリストを開けません ({}): {}", path, e))?;
+    let base = Path::new(path).parent().unwrap_or(Path::new(""));
+    Ok(content
+        .lines()
+        .map(|line| line.trim().trim_start_matches('\u{feff}'))
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let entry = Path::new(line);
+            if entry.is_absolute() { entry.to_path_buf() } else { base.join(entry) }
+        })
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect())
+}
@@ -0,0 +1,298 @@
+use std::fs;
+use serde::{Deserialize, Serialize};
+use super::tts::VoicevoxClient;
+
+pub const PRONUNCIATION_PATH: &str = "config/pronunciation.json";
+
+/// VOICEVOX の単語の優先度の既定値（0〜10）
+pub const DEFAULT_PRIORITY: u32 = 5;
+
+/// 読み上げ辞書の 1 語
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DictionaryEntry {
+    /// 表記（チャンネル名・ゲーム用語など）
+    pub surface: String,
+    /// 読み（カタカナ）
+    pub pronunciation: String,
+    /// アクセント核の位置（0 は平板型）
+    pub accent_type: u32,
+    #[serde(default = "default_priority")]
+    pub priority: u32,
+}
+
+fn default_priority() -> u32 {
+    DEFAULT_PRIORITY
+}
+
+impl DictionaryEntry {
+    pub fn new(surface: &str, pronunciation: &str, accent_type: u32) -> Self {
+        Self {
+            surface: surface.to_string(),
+            pronunciation: pronunciation.to_string(),
+            accent_type,
+            priority: DEFAULT_PRIORITY,
+        }
+    }
+
+    /// 読みがカタカナ（長音符を含む）だけで書かれ、アクセント核が読みのモーラ数以内か
+    pub fn is_valid(&self) -> bool {
+        !self.surface.trim().is_empty()
+            && !self.pronunciation.is_empty()
+            && self.pronunciation.chars().all(|c| ('ァ'..='ヴ').contains(&c) || c == 'ー')
+            && self.accent_type as usize <= mora_count(&self.pronunciation)
+    }
+}
+
+/// カタカナの読みのモーラ数（拗音などの小書き文字は前の文字と合わせて 1 モーラ）
+pub fn mora_count(pronunciation: &str) -> usize {
+    pronunciation.chars().filter(|c| !"ァィゥェォャュョヮ".contains(*c)).count()
+}
+
+/// ローカルに保存する読み上げ辞書
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PronunciationDictionary {
+    pub entries: Vec<DictionaryEntry>,
+}
+
+/// VOICEVOX との同期結果
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    /// 登録できなかった語ごとのエラー（`表記: 理由`）
+    pub errors: Vec<String>,
+}
+
+impl SyncReport {
+    pub fn summary(&self) -> String {
+        let mut summary = format!("追加 {} 件 / 更新 {} 件 / スキップ {} 件", self.added, self.updated, self.skipped);
+        if !self.errors.is_empty() {
+            summary.push_str(&format!(" / 失敗 {} 件", self.errors.len()));
+        }
+        summary
+    }
+}
+
+impl PronunciationDictionary {
+    /// 表記を読みに置き換える。重なる場合は長い表記を優先する
+    pub fn apply(&self, text: &str) -> String {
+        let mut entries: Vec<(Vec<char>, &str)> = self.entries.iter()
+            .filter(|entry| entry.is_valid())
+            .map(|entry| (entry.surface.chars().collect(), entry.pronunciation.as_str()))
+            .collect();
+        if entries.is_empty() {
+            return text.to_string();
+        }
+        entries.sort_by_key(|(surface, _)| std::cmp::Reverse(surface.len()));
+
+        let chars: Vec<char> = text.chars().collect();
+        let mut result = String::with_capacity(text.len());
+        let mut index = 0;
+        while index < chars.len() {
+            let matched = entries.iter().find(|(surface, _)| {
+                chars.len() - index >= surface.len()
+                    && chars[index..index + surface.len()].iter().zip(surface)
+                        .all(|(a, b)| fold_width(*a) == fold_width(*b))
+            });
+            match matched {
+                Some((surface, pronunciation)) => {
+                    result.push_str(pronunciation);
+                    index += surface.len();
+                }
+                None => {
+                    result.push(chars[index]);
+                    index += 1;
+                }
+            }
+        }
+        result
+    }
+
+    /// 同じ表記の語があれば置き換え、なければ追加する。置き換えた場合は true
+    pub fn upsert(&mut self, entry: DictionaryEntry) -> bool {
+        match self.entries.iter_mut().find(|e| same_surface(&e.surface, &entry.surface)) {
+            Some(existing) => {
+                *existing = entry;
+                true
+            }
+            None => {
+                self.entries.push(entry);
+                false
+            }
+        }
+    }
+
+    /// ローカルの辞書を VOICEVOX のユーザー辞書に登録する。登録できなかった語は `SyncReport::errors` に入れて続ける
+    pub fn push_to_voicevox(&self, client: &VoicevoxClient) -> Result<SyncReport, String> {
+        let remote = client.user_dict()?;
+        let mut report = SyncReport::default();
+        for entry in &self.entries {
+            if !entry.is_valid() {
+                report.skipped += 1;
+                continue;
+            }
+            match remote.iter().find(|(_, word)| same_surface(&word.surface, &entry.surface)) {
+                Some((_, word)) if same_word(word, entry) => report.skipped += 1,
+                Some((uuid, _)) => match client.update_user_dict_word(uuid, entry) {
+                    Ok(()) => report.updated += 1,
+                    Err(e) => report.errors.push(format!("{}: {}", entry.surface, e)),
+                },
+                None => match client.add_user_dict_word(entry) {
+                    Ok(()) => report.added += 1,
+                    Err(e) => report.errors.push(format!("{}: {}", entry.surface, e)),
+                },
+            }
+        }
+        Ok(report)
+    }
+
+    /// VOICEVOX のユーザー辞書を取り込む（同じ表記の語は上書き）
+    pub fn pull_from_voicevox(&mut self, client: &VoicevoxClient) -> Result<SyncReport, String> {
+        let mut report = SyncReport::default();
+        for (_, word) in client.user_dict()? {
+            match self.entries.iter().find(|e| same_surface(&e.surface, &word.surface)) {
+                Some(existing) if same_word(existing, &word) => report.skipped += 1,
+                _ => {
+                    if self.upsert(word) {
+                        report.updated += 1;
+                    } else {
+                        report.added += 1;
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// `表記,読み,アクセント型[,優先度]` 形式の CSV を取り込む
+    pub fn import_csv(&mut self, path: &str) -> Result<SyncReport, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("CSV を読み込めません ({}): {}", path, e))?;
+        let mut report = SyncReport::default();
+        for (line_number, record) in parse_csv(content.trim_start_matches('\u{feff}')).into_iter().enumerate() {
+            // 見出し行
+            if line_number == 0 && record.first().map(String::as_str) == Some("surface") {
+                continue;
+            }
+            let entry = match record.as_slice() {
+                [surface, pronunciation, accent_type, rest @ ..] => accent_type.trim().parse().ok().map(|accent_type| DictionaryEntry {
+                    surface: surface.trim().to_string(),
+                    pronunciation: pronunciation.trim().to_string(),
+                    accent_type,
+                    priority: rest.first()
+                        .and_then(|p| p.trim().parse().ok())
+                        .unwrap_or(DEFAULT_PRIORITY),
+                }),
+                _ => None,
+            };
+            match entry.filter(DictionaryEntry::is_valid) {
+                Some(entry) => {
+                    if self.upsert(entry) {
+                        report.updated += 1;
+                    } else {
+                        report.added += 1;
+                    }
+                }
+                None => report.skipped += 1,
+            }
+        }
+        Ok(report)
+    }
+
+    pub fn export_csv(&self, path: &str) -> Result<(), String> {
+        let mut content = String::from("surface,pronunciation,accent_type,priority\n");
+        for entry in &self.entries {
+            content.push_str(&format!(
+                "{},{},{},{}\n",
+                escape_csv(&entry.surface),
+                escape_csv(&entry.pronunciation),
+                entry.accent_type,
+                entry.priority,
+            ));
+        }
+        fs::write(path, content).map_err(|e| format!("CSV を書き込めません ({}): {}", path, e))
+    }
+}
+
+/// 英数字・記号を半角に揃える（VOICEVOX は表記を全角で保存するため）
+fn fold_width(c: char) -> char {
+    match c {
+        '！'..='～' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        '　' => ' ',
+        _ => c,
+    }
+    .to_ascii_lowercase()
+}
+
+fn same_surface(a: &str, b: &str) -> bool {
+    a.chars().map(fold_width).eq(b.chars().map(fold_width))
+}
+
+fn same_word(a: &DictionaryEntry, b: &DictionaryEntry) -> bool {
+    same_surface(&a.surface, &b.surface)
+        && a.pronunciation == b.pronunciation
+        && a.accent_type == b.accent_type
+        && a.priority == b.priority
+}
+
+/// RFC 4180 形式の CSV を解析する（ダブルクォート内の区切り・改行に対応）
+fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', _) => in_quotes = !in_quotes,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (c, _) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records.retain(|record| record.iter().any(|field| !field.trim().is_empty()));
+    records
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_kana_do_not_count_as_morae() {
+        assert_eq!(mora_count("キャ"), 1);
+        assert_eq!(mora_count("ショウヘイ"), 4);
+        assert_eq!(mora_count("ニッポン"), 4);
+        assert_eq!(mora_count("ファーム"), 3);
+    }
+
+    #[test]
+    fn accent_type_is_limited_by_mora_count() {
+        assert!(DictionaryEntry::new("きゃ", "キャ", 1).is_valid());
+        assert!(!DictionaryEntry::new("きゃ", "キャ", 2).is_valid());
+        assert!(DictionaryEntry::new("配信", "ハイシン", 4).is_valid());
+        assert!(!DictionaryEntry::new("配信", "はいしん", 0).is_valid());
+    }
+}
@@ -1,7 +1,7 @@
 use std::time::{Duration, Instant};
 use std::sync::Arc;
 use std::thread;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
 use log::{info, error};
 use windows::Win32::Graphics::Direct3D11::*;
 use windows::Win32::Graphics::Direct3D::*;
@@ -9,6 +9,7 @@ use windows::Win32::Graphics::Dxgi::*;
 use windows::core::ComInterface;
 use parking_lot::RwLock;
 use windows::Win32::Graphics::Dxgi::Common::DXGI_SAMPLE_DESC;
+use super::media_clock;
 
 #[derive(Default)]
 pub struct ScreenCaptureSettings {
@@ -16,6 +17,8 @@ pub struct ScreenCaptureSettings {
     pub area_type: CaptureAreaType,
     pub position: (f32, f32),
     pub size: (f32, f32),
+    /// 映像の遅延の補正（ms）
+    pub sync_offset_ms: i32,
 }
 
 #[derive(Default, PartialEq)]
@@ -26,8 +29,12 @@ pub enum CaptureAreaType {
     Custom,
 }
 
+/// RGBA のピクセル・幅・高さと、共有の時計でのタイムスタンプ
+pub type StampedFrame = ((Vec<u8>, u32, u32), Duration);
+
 pub struct ScreenCapture {
-    frame: Arc<RwLock<Option<(Vec<u8>, u32, u32)>>>,  // サイズ情報も保持
+    frame: Arc<RwLock<Option<StampedFrame>>>,  // サイズ情報と共有の時計でのタイムスタンプも保持
+    sync_offset_ms: Arc<AtomicI32>,
     running: Arc<AtomicBool>,
     capture_thread: Option<thread::JoinHandle<()>>,
 }
@@ -36,6 +43,7 @@ impl ScreenCapture {
     pub fn new() -> Self {
         Self {
             frame: Arc::new(RwLock::new(None)),
+            sync_offset_ms: Arc::new(AtomicI32::new(0)),
             running: Arc::new(AtomicBool::new(false)),
             capture_thread: None,
         }
@@ -44,6 +52,7 @@ impl ScreenCapture {
     pub fn start(&mut self) {
         if self.capture_thread.is_none() {
             let frame = Arc::clone(&self.frame);
+            let sync_offset_ms = Arc::clone(&self.sync_offset_ms);
             let running = Arc::clone(&self.running);
 
             running.store(true, Ordering::SeqCst);
@@ -132,6 +141,8 @@ impl ScreenCapture {
                             Ok(()) => {
                                 info!("フレーム取得: OK");
                                 if let Some(resource) = frame_resource {
+                                    // GPU からの読み出し前の取得した時点で時刻を付ける
+                                    let timestamp = media_clock::stamp(sync_offset_ms.load(Ordering::Relaxed));
                                     let texture: ID3D11Texture2D = resource.cast().unwrap();
                                     
                                     // staging_textureを静的に保持して再利用
@@ -152,7 +163,7 @@ impl ScreenCapture {
                                     if let Some((frame_image, width, height)) = result {
                                         info!("フレーム変換成功: {}x{}", width, height);
                                         let mut frame_lock = frame.write();
-                                        *frame_lock = Some((frame_image, width, height));
+                                        *frame_lock = Some(((frame_image, width, height), timestamp));
                                     } else {
                                         info!("フレーム変換失敗");
                                     }
@@ -197,9 +208,23 @@ impl ScreenCapture {
     }
 
     pub fn get_frame(&self) -> Option<(Vec<u8>, u32, u32)> {
+        self.get_stamped_frame().map(|(frame, _)| frame)
+    }
+
+    /// 最新フレームと、共有の時計でのタイムスタンプ
+    pub fn get_stamped_frame(&self) -> Option<StampedFrame> {
         self.frame.read().clone()
     }
 
+    /// 最後に取り込んだフレームのタイムスタンプ（フレームは複製しない）
+    pub fn frame_timestamp(&self) -> Option<Duration> {
+        self.frame.read().as_ref().map(|(_, timestamp)| *timestamp)
+    }
+
+    pub fn set_sync_offset_ms(&self, offset_ms: i32) {
+        self.sync_offset_ms.store(offset_ms, Ordering::Relaxed);
+    }
+
     // process_frame_gpuをスタティック関数に変更
     unsafe fn process_frame_gpu(
         texture: &ID3D11Texture2D,
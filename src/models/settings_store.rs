@@ -0,0 +1,29 @@
+use std::fs;
+use std::path::Path;
+use log::{info, error};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// JSON 設定ファイルを読み込む。存在しない・壊れている場合は既定値を返す
+pub fn load_json<T: DeserializeOwned + Default>(path: &str) -> T {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            error!("設定ファイルの解析に失敗しました ({}): {}", path, e);
+            T::default()
+        }),
+        Err(_) => {
+            info!("設定ファイルがないため既定値を使用します: {}", path);
+            T::default()
+        }
+    }
+}
+
+/// JSON 設定ファイルを書き込む
+pub fn save_json<T: Serialize>(path: &str, value: &T) -> Result<(), String> {
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("ディレクトリの作成に失敗: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("設定のシリアライズに失敗: {}", e))?;
+    fs::write(path, content).map_err(|e| format!("設定ファイルの書き込みに失敗 ({}): {}", path, e))
+}
@@ -0,0 +1,235 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use super::audio_engine::{ENGINE_CHANNELS, ENGINE_SAMPLE_RATE};
+use super::bgm_player::TrackPlayer;
+
+pub const SOUNDBOARD_PATH: &str = "config/soundboard.json";
+
+/// ボタンを押したときの動作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PlayMode {
+    /// 再生中なら最初から再生し直す
+    #[default]
+    Restart,
+    /// 再生中の音に重ねて再生する
+    Overlap,
+    /// ループ再生の開始・停止を切り替える
+    ToggleLoop,
+    /// 他の効果音を止めてから再生する
+    StopOthers,
+}
+
+impl PlayMode {
+    pub const ALL: [PlayMode; 4] = [PlayMode::Restart, PlayMode::Overlap, PlayMode::ToggleLoop, PlayMode::StopOthers];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlayMode::Restart => "最初から",
+            PlayMode::Overlap => "重ねる",
+            PlayMode::ToggleLoop => "ループ切り替え",
+            PlayMode::StopOthers => "他を止める",
+        }
+    }
+}
+
+/// 効果音の出力先
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EffectOutput {
+    /// 配信とモニターの両方
+    #[default]
+    Both,
+    StreamOnly,
+    MonitorOnly,
+}
+
+impl EffectOutput {
+    pub const ALL: [EffectOutput; 3] = [EffectOutput::Both, EffectOutput::StreamOnly, EffectOutput::MonitorOnly];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EffectOutput::Both => "配信とモニター",
+            EffectOutput::StreamOnly => "配信のみ",
+            EffectOutput::MonitorOnly => "モニターのみ",
+        }
+    }
+
+    pub fn to_stream(self) -> bool {
+        self != EffectOutput::MonitorOnly
+    }
+
+    pub fn to_monitor(self) -> bool {
+        self != EffectOutput::StreamOnly
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundEffect {
+    pub name: String,
+    pub file_path: String,
+    pub volume: f32,
+    /// `Ctrl+Shift+F1` 形式
+    pub hotkey: String,
+    /// ボタンの色（sRGB）
+    pub color: [u8; 3],
+    /// `Soundboard::pages` の番号
+    pub page: usize,
+    pub play_mode: PlayMode,
+    pub output: EffectOutput,
+    /// ファイルの先頭から飛ばす長さ
+    pub trim_start_ms: u32,
+    /// ファイルの終端から削る長さ
+    pub trim_end_ms: u32,
+    pub fade_in_ms: u32,
+    pub fade_out_ms: u32,
+}
+
+impl Default for SoundEffect {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            file_path: String::new(),
+            volume: 0.5,
+            hotkey: String::new(),
+            color: [70, 90, 140],
+            page: 0,
+            play_mode: PlayMode::default(),
+            output: EffectOutput::default(),
+            trim_start_ms: 0,
+            trim_end_ms: 0,
+            fade_in_ms: 0,
+            fade_out_ms: 0,
+        }
+    }
+}
+
+/// ページに分けて並べる効果音のボタン
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Soundboard {
+    pub pages: Vec<String>,
+    /// 1 行に並べるボタンの数
+    pub columns: usize,
+    pub effects: Vec<SoundEffect>,
+}
+
+impl Default for Soundboard {
+    fn default() -> Self {
+        Self {
+            pages: vec!["ページ1".to_string()],
+            columns: 4,
+            effects: Vec::new(),
+        }
+    }
+}
+
+impl Soundboard {
+    /// ページを削除する。そのページの効果音は前のページへ移す
+    pub fn remove_page(&mut self, page: usize) {
+        if self.pages.len() <= 1 || page >= self.pages.len() {
+            return;
+        }
+        self.pages.remove(page);
+        for effect in &mut self.effects {
+            if effect.page >= page {
+                effect.page = effect.page.saturating_sub(1);
+            }
+        }
+    }
+}
+
+/// 再生中の効果音 1 つ分（トリム・フェード・ループを扱う）
+pub struct EffectVoice {
+    /// `Soundboard::effects` の番号
+    pub effect: usize,
+    pub output: EffectOutput,
+    player: TrackPlayer,
+    start: Duration,
+    /// 再生を終える位置（ファイルの長さが分からない場合は `None` で終端まで）
+    end: Option<Duration>,
+    fade_in: Duration,
+    fade_out: Duration,
+    looping: bool,
+    /// フェードアウトして止めている途中
+    stopping: bool,
+}
+
+impl EffectVoice {
+    /// 設定どおりにトリム・フェードをかけて再生を始める
+    pub fn start(effect: usize, settings: &SoundEffect, player: TrackPlayer, looping: bool) -> Self {
+        let start = Duration::from_millis(settings.trim_start_ms as u64);
+        let end = player
+            .status()
+            .duration
+            .map(|duration| duration.saturating_sub(Duration::from_millis(settings.trim_end_ms as u64)))
+            .filter(|&end| end > start);
+        let mut voice = Self {
+            effect,
+            output: settings.output,
+            player,
+            start,
+            end,
+            fade_in: Duration::from_millis(settings.fade_in_ms as u64),
+            fade_out: Duration::from_millis(settings.fade_out_ms as u64),
+            looping,
+            stopping: false,
+        };
+        voice.player.play_from(voice.start, voice.fade_in);
+        voice
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.player.is_playing()
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping && !self.stopping
+    }
+
+    /// フェードアウトの設定があればフェードアウトしてから止める
+    pub fn stop(&mut self) {
+        if self.fade_out.is_zero() {
+            self.player.stop();
+        } else if !self.stopping {
+            self.player.fade_out(self.fade_out);
+        }
+        self.stopping = true;
+    }
+
+    /// `output` に加算する。ループする場合は終了位置から開始位置へ続けて書き込む
+    pub fn mix_into(&mut self, output: &mut [f32]) {
+        let mut offset = 0;
+        // 極端に短い区間のループで止まらなくならないよう、1 ブロックで折り返す回数を抑える
+        for _ in 0..8 {
+            if offset >= output.len() || !self.player.is_playing() {
+                return;
+            }
+            let mut length = output.len() - offset;
+            if let Some(end) = self.end {
+                let remaining = end.saturating_sub(self.player.position());
+                if !self.looping && !self.stopping && !self.fade_out.is_zero() && remaining <= self.fade_out {
+                    self.player.fade_out(remaining);
+                    self.stopping = true;
+                }
+                let frames = (remaining.as_secs_f64() * ENGINE_SAMPLE_RATE as f64).round() as usize;
+                length = length.min(frames * ENGINE_CHANNELS);
+            }
+
+            let written = if length > 0 { self.player.mix_into(&mut output[offset..offset + length]) } else { 0 };
+            offset += written;
+            if length == 0 || self.player.is_finished() {
+                self.reached_end();
+            } else if written < length {
+                return;
+            }
+        }
+    }
+
+    fn reached_end(&mut self) {
+        if self.looping && !self.stopping {
+            self.player.play_from(self.start, Duration::ZERO);
+        } else {
+            self.player.stop();
+        }
+    }
+}
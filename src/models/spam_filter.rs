@@ -0,0 +1,395 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use super::comment::ChatMessage;
+
+/// 保持するユーザーごとの直近メッセージ数
+const HISTORY_SIZE: usize = 5;
+
+/// 大文字率を判定する最小英字数
+const MIN_LETTERS_FOR_CAPS: usize = 8;
+
+/// 履歴を整理するユーザー数の目安
+const PRUNE_THRESHOLD: usize = 1000;
+
+pub struct SpamSettings {
+    pub enabled: bool,
+    /// 連投とみなす件数（`rate_limit_window_secs` 秒あたり）
+    pub rate_limit_messages: u32,
+    pub rate_limit_window_secs: u32,
+    /// 重複を判定する期間
+    pub duplicate_window_secs: u32,
+    /// 類似度（0〜1）がこの値以上なら重複とみなす
+    pub similarity_threshold: f32,
+    pub max_caps_ratio: f32,
+    pub max_emoji_ratio: f32,
+    pub max_repeated_chars: u32,
+    pub block_links: bool,
+    /// スコアがこの値以上ならオーバーレイに表示しない
+    pub hide_threshold: f32,
+    /// スコアがこの値以上なら読み上げない
+    pub skip_tts_threshold: f32,
+    /// スコアがこの値以上ならタイムアウトを提案する
+    pub timeout_threshold: f32,
+}
+
+impl Default for SpamSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rate_limit_messages: 5,
+            rate_limit_window_secs: 10,
+            duplicate_window_secs: 60,
+            similarity_threshold: 0.85,
+            max_caps_ratio: 0.7,
+            max_emoji_ratio: 0.5,
+            max_repeated_chars: 10,
+            block_links: true,
+            hide_threshold: 1.0,
+            skip_tts_threshold: 0.5,
+            timeout_threshold: 2.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpamReason {
+    Flood,
+    Duplicate,
+    NearDuplicate,
+    ExcessiveCaps,
+    ExcessiveEmoji,
+    RepeatedCharacters,
+    Link,
+    BlockedWord(String),
+}
+
+impl SpamReason {
+    pub fn label(&self) -> String {
+        match self {
+            SpamReason::Flood => "連投".to_string(),
+            SpamReason::Duplicate => "重複".to_string(),
+            SpamReason::NearDuplicate => "類似投稿".to_string(),
+            SpamReason::ExcessiveCaps => "大文字過多".to_string(),
+            SpamReason::ExcessiveEmoji => "絵文字過多".to_string(),
+            SpamReason::RepeatedCharacters => "同一文字の連続".to_string(),
+            SpamReason::Link => "リンク".to_string(),
+            SpamReason::BlockedWord(word) => format!("ブロックワード「{}」", word),
+        }
+    }
+
+    fn weight(&self) -> f32 {
+        match self {
+            SpamReason::Flood | SpamReason::Duplicate | SpamReason::Link => 1.0,
+            SpamReason::NearDuplicate => 0.8,
+            SpamReason::ExcessiveCaps
+            | SpamReason::ExcessiveEmoji
+            | SpamReason::RepeatedCharacters => 0.5,
+            SpamReason::BlockedWord(_) => 2.0,
+        }
+    }
+}
+
+/// スパム判定の結果と、それに基づく自動アクション
+#[derive(Debug, Clone, Default)]
+pub struct SpamVerdict {
+    pub score: f32,
+    pub reasons: Vec<SpamReason>,
+    pub hide_from_overlay: bool,
+    pub skip_tts: bool,
+    pub suggest_timeout: bool,
+}
+
+impl SpamVerdict {
+    pub fn summary(&self) -> String {
+        self.reasons.iter().map(SpamReason::label).collect::<Vec<_>>().join(" / ")
+    }
+}
+
+#[derive(Default)]
+struct UserHistory {
+    timestamps: VecDeque<u64>,
+    messages: VecDeque<(u64, String)>,
+}
+
+/// ユーザーごとの投稿履歴をもとに連投・重複・荒らしを判定する
+#[derive(Default)]
+pub struct SpamDetector {
+    users: HashMap<String, UserHistory>,
+}
+
+impl SpamDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn check(&mut self, message: &ChatMessage, settings: &SpamSettings, block_words: &[String]) -> SpamVerdict {
+        let mut reasons = Vec::new();
+
+        // ブロックワードは設定に関わらず常に判定する
+        let lowered = message.text.to_lowercase();
+        if let Some(word) = block_words.iter()
+            .find(|w| !w.trim().is_empty() && lowered.contains(&w.trim().to_lowercase()))
+        {
+            reasons.push(SpamReason::BlockedWord(word.trim().to_string()));
+        }
+
+        if settings.enabled && !message.is_moderator {
+            self.check_history(message, settings, &mut reasons);
+            check_content(&message.text, settings, &mut reasons);
+        }
+
+        let score = reasons.iter().map(SpamReason::weight).sum::<f32>();
+        let triggered = |threshold: f32| !reasons.is_empty() && score >= threshold;
+        SpamVerdict {
+            hide_from_overlay: triggered(settings.hide_threshold),
+            skip_tts: triggered(settings.skip_tts_threshold),
+            suggest_timeout: triggered(settings.timeout_threshold),
+            score,
+            reasons,
+        }
+    }
+
+    fn check_history(&mut self, message: &ChatMessage, settings: &SpamSettings, reasons: &mut Vec<SpamReason>) {
+        if self.users.len() > PRUNE_THRESHOLD {
+            self.prune(message.timestamp_ms, settings);
+        }
+
+        let now = message.timestamp_ms;
+        let rate_window = settings.rate_limit_window_secs as u64 * 1000;
+        let duplicate_window = settings.duplicate_window_secs as u64 * 1000;
+        let history = self.users.entry(message.author_id.clone()).or_default();
+
+        history.timestamps.retain(|&t| now.saturating_sub(t) <= rate_window);
+        history.timestamps.push_back(now);
+        if history.timestamps.len() as u32 > settings.rate_limit_messages {
+            reasons.push(SpamReason::Flood);
+        }
+
+        let normalized = normalize(&message.text);
+        history.messages.retain(|(t, _)| now.saturating_sub(*t) <= duplicate_window);
+        if history.messages.iter().any(|(_, previous)| *previous == normalized) {
+            reasons.push(SpamReason::Duplicate);
+        } else if history.messages.iter()
+            .any(|(_, previous)| similarity(previous, &normalized) >= settings.similarity_threshold)
+        {
+            reasons.push(SpamReason::NearDuplicate);
+        }
+        history.messages.push_back((now, normalized));
+        if history.messages.len() > HISTORY_SIZE {
+            history.messages.pop_front();
+        }
+    }
+
+    /// 期間外の履歴しか持たないユーザーを破棄する
+    fn prune(&mut self, now: u64, settings: &SpamSettings) {
+        let window = settings.rate_limit_window_secs.max(settings.duplicate_window_secs) as u64 * 1000;
+        self.users.retain(|_, history| {
+            history.timestamps.back()
+                .map(|&t| now.saturating_sub(t) <= window)
+                .unwrap_or(false)
+        });
+    }
+}
+
+fn check_content(text: &str, settings: &SpamSettings, reasons: &mut Vec<SpamReason>) {
+    let letters: Vec<char> = text.chars().filter(|c| c.is_ascii_alphabetic()).collect();
+    if letters.len() >= MIN_LETTERS_FOR_CAPS {
+        let caps = letters.iter().filter(|c| c.is_ascii_uppercase()).count();
+        if caps as f32 / letters.len() as f32 > settings.max_caps_ratio {
+            reasons.push(SpamReason::ExcessiveCaps);
+        }
+    }
+
+    let visible: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if visible.len() >= 4 {
+        let emoji = visible.iter().filter(|&&c| is_emoji(c)).count();
+        if emoji as f32 / visible.len() as f32 > settings.max_emoji_ratio {
+            reasons.push(SpamReason::ExcessiveEmoji);
+        }
+    }
+
+    if longest_run(&visible) > settings.max_repeated_chars as usize {
+        reasons.push(SpamReason::RepeatedCharacters);
+    }
+
+    if settings.block_links && contains_link(text) {
+        reasons.push(SpamReason::Link);
+    }
+}
+
+/// 比較用に空白を除き小文字化する
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// 文字バイグラムの Dice 係数による類似度
+fn similarity(a: &str, b: &str) -> f32 {
+    let bigrams = |s: &str| -> HashSet<(char, char)> {
+        let chars: Vec<char> = s.chars().collect();
+        chars.windows(2).map(|w| (w[0], w[1])).collect()
+    };
+    let (a, b) = (bigrams(a), bigrams(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let shared = a.intersection(&b).count();
+    2.0 * shared as f32 / (a.len() + b.len()) as f32
+}
+
+fn longest_run(chars: &[char]) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    let mut previous = None;
+    for &c in chars {
+        if Some(c) == previous {
+            current += 1;
+        } else {
+            current = 1;
+            previous = Some(c);
+        }
+        longest = longest.max(current);
+    }
+    longest
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF   // 絵文字・記号・ピクトグラム
+        | 0x2600..=0x27BF   // その他の記号・装飾記号
+        | 0xFE0F            // 異体字セレクタ
+        | 0x200D)           // ゼロ幅接合子
+}
+
+/// スキームなしのドメイン表記をリンクとみなすトップレベルドメイン（`good.job` のような普通の文を拾わないよう絞る）
+const LINK_TLDS: &[&str] = &[
+    "com", "net", "org", "info", "biz", "io", "co", "jp", "me", "tv", "gg", "ly", "be", "gl",
+    "xyz", "app", "dev", "site", "online", "link", "live", "shop", "top", "club", "us", "uk", "ru", "cn",
+];
+
+fn contains_link(text: &str) -> bool {
+    let lowered = text.to_lowercase();
+    if lowered.contains("http://") || lowered.contains("https://") || lowered.contains("www.") {
+        return true;
+    }
+    // スキームなしのドメイン表記（例: example.com・bit.ly/xxx）。日本語に続けて書かれても拾えるよう URL に使う文字で区切る
+    lowered
+        .split(|c: char| !(c.is_ascii_alphanumeric() || "-./_?=&%#~+".contains(c)))
+        .any(|word| {
+            let word = word.trim_matches(|c: char| !c.is_ascii_alphanumeric() && c != '/');
+            let (host, path) = match word.split_once('/') {
+                Some((host, path)) => (host, Some(path)),
+                None => (word, None),
+            };
+            let labels: Vec<&str> = host.split('.').collect();
+            if labels.len() < 2 || !labels.iter().all(|l| !l.is_empty() && l.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')) {
+                return false;
+            }
+            let tld = labels[labels.len() - 1];
+            if !(tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic())) {
+                return false;
+            }
+            // 既知のトップレベルドメインか、パスが続いている場合だけリンクとみなす
+            LINK_TLDS.contains(&tld) || path.is_some_and(|path| !path.is_empty())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::comment::Platform;
+
+    fn message(author_id: &str, text: &str, timestamp_ms: u64) -> ChatMessage {
+        ChatMessage {
+            platform: Platform::YouTube,
+            id: format!("{}-{}", author_id, timestamp_ms),
+            author_id: author_id.to_string(),
+            author_name: author_id.to_string(),
+            text: text.to_string(),
+            timestamp_ms,
+            is_member: false,
+            is_moderator: false,
+            super_chat: None,
+            membership: None,
+            simulated: false,
+        }
+    }
+
+    fn reasons(detector: &mut SpamDetector, message: &ChatMessage) -> Vec<SpamReason> {
+        detector.check(message, &SpamSettings::default(), &[]).reasons
+    }
+
+    #[test]
+    fn ordinary_words_with_dots_are_not_links() {
+        for text in ["good.job", "Mr.Smith", "lol.ok", "w.ww", "ver.2 きた", "1.5倍速で見てる", "おつ。またね"] {
+            assert!(!contains_link(text), "{}", text);
+        }
+    }
+
+    #[test]
+    fn detects_links() {
+        for text in [
+            "https://example.net/abc",
+            "www.example",
+            "example.com",
+            "見てexample.comです",
+            "bit.ly/abc",
+            "youtu.be/xyz",
+            "spam.example/path",
+            "check example.com.",
+        ] {
+            assert!(contains_link(text), "{}", text);
+        }
+    }
+
+    #[test]
+    fn flags_flood_within_rate_window() {
+        let mut detector = SpamDetector::new();
+        let texts = ["おはよう", "今日は雨", "配信たのしみ", "BGMいいね", "ゲーム何やる？"];
+        for (i, text) in texts.iter().enumerate() {
+            let reasons = reasons(&mut detector, &message("alice", text, i as u64 * 1000));
+            assert!(reasons.is_empty(), "{}: {:?}", text, reasons);
+        }
+        let flood = reasons(&mut detector, &message("alice", "草", 5000));
+        assert_eq!(flood, vec![SpamReason::Flood]);
+
+        // 期間を過ぎた投稿は数えない
+        let later = reasons(&mut detector, &message("alice", "こんばんは", 16_500));
+        assert!(later.is_empty(), "{:?}", later);
+    }
+
+    #[test]
+    fn flags_duplicates_ignoring_case_and_spaces() {
+        let mut detector = SpamDetector::new();
+        assert!(reasons(&mut detector, &message("alice", "Hello World", 0)).is_empty());
+        let verdict = detector.check(&message("alice", "hello  world", 20_000), &SpamSettings::default(), &[]);
+        assert_eq!(verdict.reasons, vec![SpamReason::Duplicate]);
+        assert!(verdict.hide_from_overlay && verdict.skip_tts && !verdict.suggest_timeout);
+
+        // 別の視聴者や、重複の判定期間を過ぎた投稿は重複にしない
+        assert!(reasons(&mut detector, &message("bob", "Hello World", 20_000)).is_empty());
+        assert!(reasons(&mut detector, &message("alice", "Hello World", 90_000)).is_empty());
+    }
+
+    #[test]
+    fn flags_near_duplicates() {
+        let mut detector = SpamDetector::new();
+        assert!(reasons(&mut detector, &message("alice", "今日の配信おもしろかった", 0)).is_empty());
+        assert_eq!(
+            reasons(&mut detector, &message("alice", "今日の配信おもしろかったね", 1000)),
+            vec![SpamReason::NearDuplicate],
+        );
+        assert!(reasons(&mut detector, &message("alice", "次はいつ配信しますか", 2000)).is_empty());
+    }
+
+    #[test]
+    fn moderators_skip_history_checks() {
+        let mut detector = SpamDetector::new();
+        let mut moderator = message("mod", "Hello World", 0);
+        moderator.is_moderator = true;
+        detector.check(&moderator, &SpamSettings::default(), &[]);
+        moderator.timestamp_ms = 1000;
+        assert!(reasons(&mut detector, &moderator).is_empty());
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+pub const TRACK_LICENSES_PATH: &str = "config/track_licenses.json";
+
+/// 配信で流してよいかどうか
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LicenseStatus {
+    /// 利用規約を確認済みで配信に使える
+    Safe,
+    /// 未確認
+    #[default]
+    Unknown,
+    /// 配信に使えない（権利の申し立てを受けたなど）
+    Unsafe,
+}
+
+impl LicenseStatus {
+    pub const ALL: [LicenseStatus; 3] = [LicenseStatus::Safe, LicenseStatus::Unknown, LicenseStatus::Unsafe];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LicenseStatus::Safe => "安全",
+            LicenseStatus::Unknown => "未確認",
+            LicenseStatus::Unsafe => "使用不可",
+        }
+    }
+}
+
+/// 曲ごとの権利情報
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrackLicense {
+    pub status: LicenseStatus,
+    /// 入手元（サイト名・URL など）
+    pub source: String,
+    pub notes: String,
+}
+
+/// BGM ライブラリの権利情報。曲はファイルパスで識別する
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LicenseLibrary {
+    pub tracks: BTreeMap<String, TrackLicense>,
+    /// 配信開始時に未確認・使用不可の曲がプレイリストにあれば確認する
+    pub warn_on_stream_start: bool,
+    /// 配信中は未確認・使用不可の曲をプレイリストで飛ばす
+    pub skip_when_live: bool,
+}
+
+impl Default for LicenseLibrary {
+    fn default() -> Self {
+        Self {
+            tracks: BTreeMap::new(),
+            warn_on_stream_start: true,
+            skip_when_live: false,
+        }
+    }
+}
+
+impl LicenseLibrary {
+    pub fn status(&self, path: &str) -> LicenseStatus {
+        self.tracks.get(path).map_or(LicenseStatus::Unknown, |license| license.status)
+    }
+
+    pub fn license_mut(&mut self, path: &str) -> &mut TrackLicense {
+        self.tracks.entry(path.to_string()).or_default()
+    }
+
+    pub fn is_safe(&self, path: &str) -> bool {
+        self.status(path) == LicenseStatus::Safe
+    }
+}
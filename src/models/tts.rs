@@ -0,0 +1,499 @@
+use std::fs;
+use std::io::Read;
+use std::process::Command;
+use std::sync::{mpsc, Arc, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+use log::{info, warn, error};
+use super::audio_output;
+use super::comment::now_millis;
+use super::mixer::InputQueue;
+use super::pronunciation::{DictionaryEntry, PronunciationDictionary, DEFAULT_PRIORITY};
+
+pub const DEFAULT_VOICEVOX_ENDPOINT: &str = "http://127.0.0.1:50021";
+
+/// エンジンの死活監視の間隔
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// ずんだもん（ノーマル）
+pub const DEFAULT_SPEAKER_ID: u32 = 3;
+
+/// 読み上げ 1 件分の要求
+#[derive(Debug, Clone)]
+pub struct SpeechRequest {
+    pub text: String,
+    pub speaker_id: u32,
+    pub speed: f32,
+    /// 1.0 を基準とした倍率（0.5〜2.0）
+    pub pitch: f32,
+    pub volume: f32,
+}
+
+/// 音声合成エンジンの共通インターフェース。合成結果は WAV データで返す
+pub trait SpeechSynthesizer {
+    fn name(&self) -> String;
+
+    fn synthesize(&self, request: &SpeechRequest) -> Result<Vec<u8>, String>;
+
+    /// エンジンが利用可能かどうかを確認する
+    fn health_check(&self) -> Result<(), String>;
+}
+
+/// VOICEVOX の話者スタイル（キャラクター名とスタイル名の組）
+#[derive(Debug, Clone)]
+pub struct SpeakerStyle {
+    pub id: u32,
+    pub label: String,
+}
+
+/// VOICEVOX エンジンの HTTP API クライアント
+pub struct VoicevoxClient {
+    endpoint: String,
+    agent: ureq::Agent,
+}
+
+impl VoicevoxClient {
+    pub fn new(endpoint: &str) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(30))
+                .build(),
+        }
+    }
+
+    /// 利用可能な話者スタイルの一覧を取得する
+    pub fn speakers(&self) -> Result<Vec<SpeakerStyle>, String> {
+        let speakers: serde_json::Value = self.agent
+            .get(&format!("{}/speakers", self.endpoint))
+            .call()
+            .map_err(|e| format!("話者一覧の取得に失敗: {}", e))?
+            .into_json()
+            .map_err(|e| format!("話者一覧を解析できません: {}", e))?;
+
+        let mut styles = Vec::new();
+        for speaker in speakers.as_array().into_iter().flatten() {
+            let name = speaker["name"].as_str().unwrap_or("");
+            for style in speaker["styles"].as_array().into_iter().flatten() {
+                if let Some(id) = style["id"].as_u64() {
+                    styles.push(SpeakerStyle {
+                        id: id as u32,
+                        label: format!("{}（{}）", name, style["name"].as_str().unwrap_or("")),
+                    });
+                }
+            }
+        }
+        Ok(styles)
+    }
+
+    /// ユーザー辞書の単語一覧（UUID と単語の組）
+    pub fn user_dict(&self) -> Result<Vec<(String, DictionaryEntry)>, String> {
+        let words: serde_json::Value = self.agent
+            .get(&format!("{}/user_dict", self.endpoint))
+            .call()
+            .map_err(|e| format!("ユーザー辞書の取得に失敗: {}", e))?
+            .into_json()
+            .map_err(|e| format!("ユーザー辞書を解析できません: {}", e))?;
+
+        Ok(words.as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(uuid, word)| {
+                let entry = DictionaryEntry {
+                    surface: word["surface"].as_str()?.to_string(),
+                    pronunciation: word["pronunciation"].as_str()?.to_string(),
+                    accent_type: word["accent_type"].as_u64()? as u32,
+                    priority: word["priority"].as_u64().map(|p| p as u32).unwrap_or(DEFAULT_PRIORITY),
+                };
+                Some((uuid.clone(), entry))
+            })
+            .collect())
+    }
+
+    pub fn add_user_dict_word(&self, entry: &DictionaryEntry) -> Result<(), String> {
+        self.dict_word_request(self.agent.post(&format!("{}/user_dict_word", self.endpoint)), entry)
+    }
+
+    pub fn update_user_dict_word(&self, uuid: &str, entry: &DictionaryEntry) -> Result<(), String> {
+        self.dict_word_request(self.agent.put(&format!("{}/user_dict_word/{}", self.endpoint, uuid)), entry)
+    }
+
+    fn dict_word_request(&self, request: ureq::Request, entry: &DictionaryEntry) -> Result<(), String> {
+        request
+            .query("surface", &entry.surface)
+            .query("pronunciation", &entry.pronunciation)
+            .query("accent_type", &entry.accent_type.to_string())
+            .query("priority", &entry.priority.to_string())
+            .call()
+            .map(|_| ())
+            .map_err(|e| format!("「{}」の登録に失敗: {}", entry.surface, e))
+    }
+}
+
+impl SpeechSynthesizer for VoicevoxClient {
+    fn name(&self) -> String {
+        "VOICEVOX".to_string()
+    }
+
+    /// テキストを合成し WAV データを返す
+    fn synthesize(&self, request: &SpeechRequest) -> Result<Vec<u8>, String> {
+        let speaker = request.speaker_id.to_string();
+
+        let mut query: serde_json::Value = self.agent
+            .post(&format!("{}/audio_query", self.endpoint))
+            .query("text", &request.text)
+            .query("speaker", &speaker)
+            .call()
+            .map_err(|e| format!("audio_query に失敗: {}", e))?
+            .into_json()
+            .map_err(|e| format!("audio_query の応答を解析できません: {}", e))?;
+
+        query["speedScale"] = request.speed.into();
+        // VOICEVOX の pitchScale は 0 基準で ±0.15 程度が実用範囲
+        query["pitchScale"] = (request.pitch.max(0.01).log2() * 0.15).into();
+        query["volumeScale"] = request.volume.into();
+
+        let response = self.agent
+            .post(&format!("{}/synthesis", self.endpoint))
+            .query("speaker", &speaker)
+            .send_json(query)
+            .map_err(|e| format!("synthesis に失敗: {}", e))?;
+
+        let mut wav = Vec::new();
+        response.into_reader()
+            .read_to_end(&mut wav)
+            .map_err(|e| format!("合成音声の受信に失敗: {}", e))?;
+        Ok(wav)
+    }
+
+    fn health_check(&self) -> Result<(), String> {
+        self.agent
+            .get(&format!("{}/version", self.endpoint))
+            .timeout(HEALTH_CHECK_TIMEOUT)
+            .call()
+            .map(|_| ())
+            .map_err(|e| format!("VOICEVOX に接続できません: {}", e))
+    }
+}
+/// コマンドラインの音声合成ソフト（Open JTalk・espeak-ng など）を呼び出すエンジン
+///
+/// テンプレート中の `{text}`・`{input}`（テキストファイルのパス）・`{output}`（WAV の出力先）・
+/// `{speed}` を置き換えて実行する。`{output}` がない場合は標準出力を WAV として扱う。
+/// シェルは経由しないが、`-` で始まるコメントはオプションとして読まれうるため、
+/// `{text}` で始まる引数は手前に `--` がある場合だけ許可する（本文は `{input}` で渡すのが安全）。
+pub struct CommandSynthesizer {
+    template: String,
+}
+
+impl CommandSynthesizer {
+    pub fn new(template: &str) -> Self {
+        Self { template: template.trim().to_string() }
+    }
+
+    fn program(&self) -> String {
+        split_command(&self.template).into_iter().next().unwrap_or_default()
+    }
+}
+
+impl SpeechSynthesizer for CommandSynthesizer {
+    fn name(&self) -> String {
+        format!("コマンド ({})", self.program())
+    }
+
+    fn synthesize(&self, request: &SpeechRequest) -> Result<Vec<u8>, String> {
+        let args = split_command(&self.template);
+        if args.is_empty() {
+            return Err("コマンドが設定されていません".to_string());
+        }
+        check_text_position(&args)?;
+
+        let stem = std::env::temp_dir().join(format!("youtube-live-tool-tts-{}-{}", std::process::id(), now_millis()));
+        let input_path = stem.with_extension("txt");
+        let output_path = stem.with_extension("wav");
+        let uses_output = self.template.contains("{output}");
+        if self.template.contains("{input}") {
+            fs::write(&input_path, &request.text).map_err(|e| format!("入力ファイルの作成に失敗: {}", e))?;
+        }
+
+        let args: Vec<String> = args.iter()
+            .map(|arg| arg
+                .replace("{text}", &request.text)
+                .replace("{input}", &input_path.to_string_lossy())
+                .replace("{output}", &output_path.to_string_lossy())
+                .replace("{speed}", &format!("{:.2}", request.speed)))
+            .collect();
+
+        let result = Command::new(&args[0]).args(&args[1..]).output();
+        let _ = fs::remove_file(&input_path);
+        let output = result.map_err(|e| format!("{} を実行できません: {}", args[0], e))?;
+        if !output.status.success() {
+            let _ = fs::remove_file(&output_path);
+            return Err(format!("{} が失敗しました: {}", args[0], String::from_utf8_lossy(&output.stderr).trim()));
+        }
+
+        if uses_output {
+            let wav = fs::read(&output_path).map_err(|e| format!("合成結果を読み込めません: {}", e));
+            let _ = fs::remove_file(&output_path);
+            wav
+        } else {
+            Ok(output.stdout)
+        }
+    }
+
+    fn health_check(&self) -> Result<(), String> {
+        let request = SpeechRequest {
+            text: "テスト".to_string(),
+            speaker_id: 0,
+            speed: 1.0,
+            pitch: 1.0,
+            volume: 1.0,
+        };
+        let wav = self.synthesize(&request)?;
+        hound::WavReader::new(std::io::Cursor::new(wav))
+            .map(|_| ())
+            .map_err(|e| format!("WAV が出力されませんでした: {}", e))
+    }
+}
+
+/// 本文がオプションとして解釈されないか確かめる。`{text}` で始まる引数は `--` より後にだけ置ける
+fn check_text_position(args: &[String]) -> Result<(), String> {
+    let end_of_options = args.iter().position(|arg| arg == "--");
+    for (i, arg) in args.iter().enumerate().skip(1) {
+        if arg.starts_with("{text}") && !matches!(end_of_options, Some(end) if end < i) {
+            return Err("{text} がオプションとして読まれる位置にあります。{input} を使うか、手前に -- を置いてください".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// 空白区切りでコマンドを分割する（ダブルクォートで囲んだ部分は 1 つの引数）
+fn split_command(template: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_arg = false;
+    for c in template.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_arg = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_arg = true;
+            }
+        }
+    }
+    if has_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// 読み上げエンジンの構成
+#[derive(Clone)]
+pub struct TtsEngineSettings {
+    pub voicevox_endpoint: String,
+    /// VOICEVOX が使えない場合にコマンドラインの合成ソフトへ切り替える
+    pub fallback_enabled: bool,
+    pub fallback_command: String,
+}
+
+impl Default for TtsEngineSettings {
+    fn default() -> Self {
+        Self {
+            voicevox_endpoint: DEFAULT_VOICEVOX_ENDPOINT.to_string(),
+            fallback_enabled: false,
+            fallback_command: "espeak-ng -v ja -w {output} -f {input}".to_string(),
+        }
+    }
+}
+
+impl TtsEngineSettings {
+    /// 優先順に並べたエンジン
+    fn synthesizers(&self) -> Vec<Box<dyn SpeechSynthesizer>> {
+        let mut synthesizers: Vec<Box<dyn SpeechSynthesizer>> = vec![
+            Box::new(VoicevoxClient::new(&self.voicevox_endpoint)),
+        ];
+        if self.fallback_enabled && !self.fallback_command.trim().is_empty() {
+            synthesizers.push(Box::new(CommandSynthesizer::new(&self.fallback_command)));
+        }
+        synthesizers
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HealthStatus {
+    Healthy,
+    Unhealthy(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct EngineHealth {
+    pub name: String,
+    pub status: HealthStatus,
+}
+
+type HealthTable = Arc<Mutex<Vec<EngineHealth>>>;
+
+fn set_health(health: &Mutex<Vec<EngineHealth>>, name: String, status: HealthStatus) {
+    let mut health = health.lock().unwrap();
+    match health.iter_mut().find(|h| h.name == name) {
+        Some(entry) => entry.status = status,
+        None => health.push(EngineHealth { name, status }),
+    }
+}
+
+fn check_all(settings: &Mutex<TtsEngineSettings>, health: &Mutex<Vec<EngineHealth>>) {
+    let synthesizers = settings.lock().unwrap().synthesizers();
+    let names: Vec<String> = synthesizers.iter().map(|s| s.name()).collect();
+    // 構成から外れたエンジンの表示を消す
+    health.lock().unwrap().retain(|h| names.contains(&h.name));
+
+    for synthesizer in synthesizers {
+        let status = match synthesizer.health_check() {
+            Ok(()) => HealthStatus::Healthy,
+            Err(e) => HealthStatus::Unhealthy(e),
+        };
+        set_health(health, synthesizer.name(), status);
+    }
+}
+
+/// 利用可能なエンジンを優先順に試し、失敗したら次のエンジンへ切り替える
+fn synthesize_with_failover(
+    synthesizers: &[Box<dyn SpeechSynthesizer>],
+    health: &Mutex<Vec<EngineHealth>>,
+    request: &SpeechRequest,
+) -> Result<Vec<u8>, String> {
+    // 直近で失敗したエンジンは後回しにしてタイムアウト待ちを避ける
+    let is_unhealthy = |name: &str| health.lock().unwrap().iter()
+        .any(|h| h.name == name && matches!(h.status, HealthStatus::Unhealthy(_)));
+    let mut ordered: Vec<&Box<dyn SpeechSynthesizer>> = synthesizers.iter().collect();
+    ordered.sort_by_key(|s| is_unhealthy(&s.name()));
+
+    let mut errors = Vec::new();
+    for synthesizer in ordered {
+        match synthesizer.synthesize(request) {
+            Ok(wav) => {
+                set_health(health, synthesizer.name(), HealthStatus::Healthy);
+                return Ok(wav);
+            }
+            Err(e) => {
+                warn!("{} での合成に失敗: {}", synthesizer.name(), e);
+                set_health(health, synthesizer.name(), HealthStatus::Unhealthy(e.clone()));
+                errors.push(e);
+            }
+        }
+    }
+    Err(errors.join(" / "))
+}
+
+/// 合成前の正規化。読み上げ辞書で表記を読みに置き換え、連続する空白をまとめる
+pub fn normalize_text(text: &str, dictionary: &PronunciationDictionary) -> String {
+    dictionary.apply(text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 読み上げ要求を順番に合成・再生するワーカー
+pub struct TtsPlayer {
+    sender: mpsc::Sender<SpeechRequest>,
+    settings: Arc<Mutex<TtsEngineSettings>>,
+    dictionary: Arc<Mutex<PronunciationDictionary>>,
+    health: HealthTable,
+}
+
+impl TtsPlayer {
+    /// 合成した音声はミキサーの読み上げバス（`output`）へ流す
+    pub fn new(settings: TtsEngineSettings, dictionary: PronunciationDictionary, output: InputQueue) -> Self {
+        let (sender, receiver) = mpsc::channel::<SpeechRequest>();
+        let settings = Arc::new(Mutex::new(settings));
+        let dictionary = Arc::new(Mutex::new(dictionary));
+        let health: HealthTable = Arc::new(Mutex::new(Vec::new()));
+
+        let worker_settings = Arc::clone(&settings);
+        let worker_dictionary = Arc::clone(&dictionary);
+        let worker_health = Arc::clone(&health);
+        thread::spawn(move || {
+            for mut request in receiver {
+                info!("読み上げ: {}", request.text);
+                request.text = normalize_text(&request.text, &worker_dictionary.lock().unwrap());
+                let synthesizers = worker_settings.lock().unwrap().synthesizers();
+                let result = synthesize_with_failover(&synthesizers, &worker_health, &request)
+                    .and_then(|wav| audio_output::decode_wav(&wav));
+                match result {
+                    Ok((samples, sample_rate, channels)) => {
+                        output.push(&samples, sample_rate, channels as usize);
+                        // 読み終わるまで次のコメントを合成しない
+                        while output.queued() > Duration::ZERO {
+                            thread::sleep(Duration::from_millis(20));
+                        }
+                    }
+                    Err(e) => error!("読み上げに失敗: {}", e),
+                }
+            }
+        });
+
+        // TtsPlayer が破棄されたら監視も終了する
+        let monitor_settings: Weak<Mutex<TtsEngineSettings>> = Arc::downgrade(&settings);
+        let monitor_health = Arc::downgrade(&health);
+        thread::spawn(move || {
+            while let (Some(settings), Some(health)) = (monitor_settings.upgrade(), monitor_health.upgrade()) {
+                check_all(&settings, &health);
+                drop((settings, health));
+                thread::sleep(HEALTH_CHECK_INTERVAL);
+            }
+        });
+
+        Self { sender, settings, dictionary, health }
+    }
+
+    pub fn speak(&self, request: SpeechRequest) {
+        if self.sender.send(request).is_err() {
+            error!("読み上げワーカーが停止しています");
+        }
+    }
+
+    /// 配信には流さず、モニター用の出力デバイスで試聴する
+    pub fn preview(&self, mut request: SpeechRequest, monitor_device: Option<String>) {
+        request.text = normalize_text(&request.text, &self.dictionary.lock().unwrap());
+        let settings = Arc::clone(&self.settings);
+        let health = Arc::clone(&self.health);
+        thread::spawn(move || {
+            let synthesizers = settings.lock().unwrap().synthesizers();
+            let result = synthesize_with_failover(&synthesizers, &health, &request)
+                .and_then(|wav| audio_output::play_wav_on(monitor_device.as_deref(), &wav, 1.0));
+            if let Err(e) = result {
+                error!("試聴に失敗: {}", e);
+            }
+        });
+    }
+
+    pub fn set_settings(&self, settings: TtsEngineSettings) {
+        *self.settings.lock().unwrap() = settings;
+    }
+
+    pub fn set_dictionary(&self, dictionary: PronunciationDictionary) {
+        *self.dictionary.lock().unwrap() = dictionary;
+    }
+
+    pub fn health(&self) -> Vec<EngineHealth> {
+        self.health.lock().unwrap().clone()
+    }
+
+    /// 死活監視をすぐに実行する
+    pub fn check_health(&self) {
+        let settings = Arc::clone(&self.settings);
+        let health = Arc::clone(&self.health);
+        thread::spawn(move || check_all(&settings, &health));
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use super::tts::SpeechRequest;
+
+/// 読み上げ前にモデレーターの承認を求める設定
+#[derive(Default)]
+pub struct ApprovalSettings {
+    pub enabled: bool,
+    /// 一定時間操作がなければ自動で承認する（0 で無効）
+    pub auto_approve_secs: u32,
+    /// 試聴に使う出力デバイス（`None` は既定のデバイス）
+    pub monitor_device: Option<String>,
+}
+
+/// 承認待ちの読み上げ
+pub struct PendingSpeech {
+    pub id: u64,
+    pub author_name: String,
+    /// 承認前に本文を編集できる
+    pub request: SpeechRequest,
+    queued_at: Instant,
+}
+
+impl PendingSpeech {
+    pub fn elapsed(&self) -> Duration {
+        self.queued_at.elapsed()
+    }
+}
+
+/// 承認待ちの読み上げキュー（古い順）
+#[derive(Default)]
+pub struct ApprovalQueue {
+    items: VecDeque<PendingSpeech>,
+    next_id: u64,
+}
+
+impl ApprovalQueue {
+    pub fn push(&mut self, author_name: String, request: SpeechRequest) {
+        self.next_id += 1;
+        self.items.push_back(PendingSpeech {
+            id: self.next_id,
+            author_name,
+            request,
+            queued_at: Instant::now(),
+        });
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = &PendingSpeech> {
+        self.items.iter()
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut PendingSpeech> {
+        self.items.iter_mut().find(|item| item.id == id)
+    }
+
+    /// キューから取り除いて返す（承認・却下のどちらにも使う）
+    pub fn remove(&mut self, id: u64) -> Option<PendingSpeech> {
+        let index = self.items.iter().position(|item| item.id == id)?;
+        self.items.remove(index)
+    }
+
+    /// 自動承認の待ち時間を過ぎたものを取り出す
+    pub fn take_expired(&mut self, delay: Duration) -> Vec<PendingSpeech> {
+        let mut expired = Vec::new();
+        while self.items.front().is_some_and(|item| item.elapsed() >= delay) {
+            expired.extend(self.items.pop_front());
+        }
+        expired
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
@@ -0,0 +1,498 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use log::{info, warn, error};
+use super::chat_source::{wait_while_running, ChatSource, SourceState};
+use super::comment::{ChatMessage, ChatSender, Membership, Platform, SuperChat, now_millis};
+
+pub const DEFAULT_TWITCH_HOST: &str = "irc.chat.twitch.tv";
+pub const DEFAULT_TWITCH_TLS_PORT: u16 = 6697;
+
+/// 読み込みのタイムアウト（停止要求の確認間隔を兼ねる）
+const READ_TIMEOUT: Duration = Duration::from_millis(500);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 再接続の待機時間の上限
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+pub struct TwitchChatSettings {
+    pub channel: String,
+    /// 空の場合は匿名（読み取り専用）で接続する
+    pub username: String,
+    /// `oauth:` 付きのトークン
+    pub oauth_token: String,
+    /// 接続先。テスト時はローカルの IRC スタブサーバーを指定できる
+    pub host: String,
+    pub port: u16,
+    pub use_tls: bool,
+}
+
+impl Default for TwitchChatSettings {
+    fn default() -> Self {
+        Self {
+            channel: String::new(),
+            username: String::new(),
+            oauth_token: String::new(),
+            host: DEFAULT_TWITCH_HOST.to_string(),
+            port: DEFAULT_TWITCH_TLS_PORT,
+            use_tls: true,
+        }
+    }
+}
+
+impl TwitchChatSettings {
+    fn channel_name(&self) -> String {
+        self.channel.trim().trim_start_matches('#').to_lowercase()
+    }
+}
+
+/// Twitch IRC（IRCv3 タグ付き）からコメントを受信する取得元
+pub struct TwitchChatSource {
+    settings: TwitchChatSettings,
+    running: Arc<AtomicBool>,
+    state: Arc<Mutex<SourceState>>,
+    irc_thread: Option<thread::JoinHandle<()>>,
+}
+
+impl TwitchChatSource {
+    pub fn new(settings: TwitchChatSettings) -> Self {
+        Self {
+            settings,
+            running: Arc::new(AtomicBool::new(false)),
+            state: Arc::new(Mutex::new(SourceState::Stopped)),
+            irc_thread: None,
+        }
+    }
+}
+
+impl ChatSource for TwitchChatSource {
+    fn platform(&self) -> Platform {
+        Platform::Twitch
+    }
+
+    fn name(&self) -> String {
+        format!("#{}", self.settings.channel_name())
+    }
+
+    fn start(&mut self, sender: ChatSender) -> Result<(), String> {
+        self.stop();
+        if self.settings.channel_name().is_empty() {
+            return Err("チャンネル名を入力してください".to_string());
+        }
+
+        let settings = self.settings.clone();
+        let running = Arc::clone(&self.running);
+        let state = Arc::clone(&self.state);
+        running.store(true, Ordering::SeqCst);
+
+        let handle = thread::spawn(move || {
+            let mut delay = Duration::from_secs(1);
+            while running.load(Ordering::SeqCst) {
+                *state.lock().unwrap() = SourceState::Connecting;
+                match run_session(&settings, &sender, &running, &state) {
+                    Ok(SessionEnd::Stopped) => break,
+                    Ok(SessionEnd::Fatal(e)) => {
+                        // 認証エラーなど再接続しても解決しないもの
+                        error!("Twitch IRC: {}", e);
+                        *state.lock().unwrap() = SourceState::Error(e);
+                        running.store(false, Ordering::SeqCst);
+                        return;
+                    }
+                    Err(e) => {
+                        warn!("Twitch IRC から切断されました: {}", e);
+                        *state.lock().unwrap() = SourceState::Reconnecting(e);
+                    }
+                }
+                if !wait_while_running(delay, &running) {
+                    break;
+                }
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+            }
+            *state.lock().unwrap() = SourceState::Stopped;
+        });
+
+        self.irc_thread = Some(handle);
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.irc_thread.take() {
+            let _ = handle.join();
+        }
+    }
+
+    fn state(&self) -> SourceState {
+        self.state.lock().unwrap().clone()
+    }
+}
+
+impl Drop for TwitchChatSource {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+trait IrcStream: Read + Write + Send {}
+impl<T: Read + Write + Send> IrcStream for T {}
+
+fn connect(settings: &TwitchChatSettings) -> Result<Box<dyn IrcStream>, String> {
+    let address = (settings.host.as_str(), settings.port)
+        .to_socket_addrs()
+        .map_err(|e| format!("{} の名前解決に失敗: {}", settings.host, e))?
+        .next()
+        .ok_or_else(|| format!("{} のアドレスが見つかりません", settings.host))?;
+
+    let tcp = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)
+        .map_err(|e| format!("接続に失敗: {}", e))?;
+    tcp.set_read_timeout(Some(READ_TIMEOUT)).map_err(|e| e.to_string())?;
+
+    if settings.use_tls {
+        let connector = native_tls::TlsConnector::new().map_err(|e| e.to_string())?;
+        let tls = connector.connect(&settings.host, tcp)
+            .map_err(|e| format!("TLS ハンドシェイクに失敗: {}", e))?;
+        Ok(Box::new(tls))
+    } else {
+        Ok(Box::new(tcp))
+    }
+}
+
+enum SessionEnd {
+    /// 停止要求による終了
+    Stopped,
+    /// 再接続しない終了
+    Fatal(String),
+}
+
+/// 1 回分の接続を処理する。再接続すべき切断は Err を返す
+fn run_session(
+    settings: &TwitchChatSettings,
+    sender: &ChatSender,
+    running: &AtomicBool,
+    state: &Mutex<SourceState>,
+) -> Result<SessionEnd, String> {
+    let channel = settings.channel_name();
+    let mut reader = BufReader::new(connect(settings)?);
+
+    let (nick, pass) = if settings.username.trim().is_empty() {
+        // 匿名ログイン（読み取り専用）
+        ("justinfan12345".to_string(), None)
+    } else {
+        (settings.username.trim().to_lowercase(), Some(settings.oauth_token.trim().to_string()))
+    };
+
+    let mut login = String::from("CAP REQ :twitch.tv/tags twitch.tv/commands\r\n");
+    if let Some(pass) = pass {
+        let pass = if pass.starts_with("oauth:") { pass } else { format!("oauth:{}", pass) };
+        login.push_str(&format!("PASS {}\r\n", pass));
+    }
+    login.push_str(&format!("NICK {}\r\nJOIN #{}\r\n", nick, channel));
+    send_raw(reader.get_mut(), &login)?;
+
+    // 日本語が途中で途切れても壊れないよう、行が揃うまでバイト列で保持する
+    let mut line = Vec::new();
+    while running.load(Ordering::SeqCst) {
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => return Err("サーバーが接続を閉じました".to_string()),
+            Ok(_) => {}
+            // タイムアウト時は読みかけの行を保持したまま停止要求を確認する
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => continue,
+            Err(e) => return Err(e.to_string()),
+        }
+
+        let raw = String::from_utf8_lossy(&line).trim_end_matches(['\r', '\n']).to_string();
+        line.clear();
+        let Some(message) = IrcMessage::parse(&raw) else {
+            continue;
+        };
+
+        match message.command.as_str() {
+            "PING" => {
+                let token = message.params.first().map(String::as_str).unwrap_or("tmi.twitch.tv");
+                send_raw(reader.get_mut(), &format!("PONG :{}\r\n", token))?;
+            }
+            "JOIN" => {
+                info!("Twitch チャンネルに参加: #{}", channel);
+                *state.lock().unwrap() = SourceState::Connected;
+            }
+            "RECONNECT" => return Err("サーバーから再接続を要求されました".to_string()),
+            "NOTICE" if message.params.last().is_some_and(|p| p.contains("authentication failed")) => {
+                return Ok(SessionEnd::Fatal("認証に失敗しました".to_string()));
+            }
+            _ => {
+                if let Some(chat) = message.to_chat_message() {
+                    if sender.send(chat).is_err() {
+                        return Ok(SessionEnd::Fatal("チャットの受信側が閉じられています".to_string()));
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = send_raw(reader.get_mut(), "QUIT\r\n");
+    Ok(SessionEnd::Stopped)
+}
+
+fn send_raw(stream: &mut Box<dyn IrcStream>, data: &str) -> Result<(), String> {
+    stream.write_all(data.as_bytes())
+        .and_then(|_| stream.flush())
+        .map_err(|e| format!("送信に失敗: {}", e))
+}
+
+/// IRCv3 タグ付きの IRC メッセージ
+#[derive(Debug, Default)]
+pub struct IrcMessage {
+    pub tags: HashMap<String, String>,
+    pub prefix: Option<String>,
+    pub command: String,
+    pub params: Vec<String>,
+}
+
+impl IrcMessage {
+    /// `@tags :prefix COMMAND params :trailing` 形式の 1 行を解析する
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut rest = line;
+        let mut message = IrcMessage::default();
+
+        if let Some(stripped) = rest.strip_prefix('@') {
+            let (tags, remaining) = stripped.split_once(' ')?;
+            for tag in tags.split(';') {
+                let (key, value) = tag.split_once('=').unwrap_or((tag, ""));
+                message.tags.insert(key.to_string(), unescape_tag(value));
+            }
+            rest = remaining.trim_start();
+        }
+
+        if let Some(stripped) = rest.strip_prefix(':') {
+            let (prefix, remaining) = stripped.split_once(' ')?;
+            message.prefix = Some(prefix.to_string());
+            rest = remaining.trim_start();
+        }
+
+        let (command, mut params) = rest.split_once(' ').unwrap_or((rest, ""));
+        if command.is_empty() {
+            return None;
+        }
+        message.command = command.to_string();
+
+        while !params.is_empty() {
+            if let Some(trailing) = params.strip_prefix(':') {
+                message.params.push(trailing.to_string());
+                break;
+            }
+            let (param, remaining) = params.split_once(' ').unwrap_or((params, ""));
+            message.params.push(param.to_string());
+            params = remaining.trim_start();
+        }
+
+        Some(message)
+    }
+
+    fn tag(&self, key: &str) -> Option<&str> {
+        self.tags.get(key).map(String::as_str).filter(|v| !v.is_empty())
+    }
+
+    fn has_badge(&self, badge: &str) -> bool {
+        self.tag("badges")
+            .map(|badges| badges.split(',').any(|b| b.split('/').next() == Some(badge)))
+            .unwrap_or(false)
+    }
+
+    /// PRIVMSG（Bits を含む）と USERNOTICE（サブスク）を共通のコメントモデルに変換する
+    pub fn to_chat_message(&self) -> Option<ChatMessage> {
+        let login = self.prefix.as_deref()
+            .and_then(|p| p.split('!').next())
+            .or_else(|| self.tag("login"))
+            .unwrap_or("")
+            .to_string();
+
+        let (text, membership) = match self.command.as_str() {
+            "PRIVMSG" => (self.params.get(1)?.clone(), None),
+            "USERNOTICE" => {
+                let months = self.tag("msg-param-cumulative-months")
+                    .or_else(|| self.tag("msg-param-months"))
+                    .and_then(|m| m.parse().ok())
+                    .unwrap_or(1);
+                let membership = match self.tag("msg-id")? {
+                    "sub" | "resub" => Membership { months, is_gift: false },
+                    "subgift" | "anonsubgift" => Membership { months, is_gift: true },
+                    _ => return None,
+                };
+                // 本文がない場合はシステムメッセージを表示する
+                let text = self.params.get(1).cloned()
+                    .or_else(|| self.tag("system-msg").map(str::to_string))
+                    .unwrap_or_default();
+                (text, Some(membership))
+            }
+            _ => return None,
+        };
+
+        let super_chat = self.tag("bits")
+            .and_then(|bits| bits.parse::<f64>().ok())
+            .map(|bits| SuperChat { amount: bits, currency: "BITS".to_string() });
+
+        let user_id = self.tag("user-id").map(str::to_string).unwrap_or_else(|| login.clone());
+        let timestamp_ms = self.tag("tmi-sent-ts")
+            .and_then(|t| t.parse().ok())
+            .unwrap_or_else(now_millis);
+
+        Some(ChatMessage {
+            platform: Platform::Twitch,
+            id: self.tag("id").map(str::to_string).unwrap_or_else(|| format!("twitch-{}", timestamp_ms)),
+            // YouTube のチャンネル ID と衝突しないよう接頭辞を付ける
+            author_id: format!("twitch:{}", user_id),
+            author_name: self.tag("display-name").map(str::to_string).unwrap_or(login),
+            text,
+            timestamp_ms,
+            is_member: self.tag("subscriber") == Some("1") || self.has_badge("subscriber"),
+            is_moderator: self.tag("mod") == Some("1") || self.has_badge("broadcaster"),
+            super_chat,
+            membership,
+            simulated: false,
+        })
+    }
+}
+
+/// IRCv3 のタグ値のエスケープを戻す
+fn unescape_tag(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(':') => result.push(';'),
+            Some('s') => result.push(' '),
+            Some('r') => result.push('\r'),
+            Some('n') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    #[test]
+    fn parses_tags_prefix_and_params() {
+        let message = IrcMessage::parse(
+            "@badges=moderator/1;display-name=Viewer;emotes= :viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel :hello  world",
+        )
+        .unwrap();
+        assert_eq!(message.tags["badges"], "moderator/1");
+        assert_eq!(message.tags["display-name"], "Viewer");
+        assert_eq!(message.tags["emotes"], "");
+        assert_eq!(message.prefix.as_deref(), Some("viewer!viewer@viewer.tmi.twitch.tv"));
+        assert_eq!(message.command, "PRIVMSG");
+        assert_eq!(message.params, ["#channel", "hello  world"]);
+
+        let ping = IrcMessage::parse("PING :tmi.twitch.tv").unwrap();
+        assert!(ping.tags.is_empty());
+        assert_eq!(ping.prefix, None);
+        assert_eq!(ping.command, "PING");
+        assert_eq!(ping.params, ["tmi.twitch.tv"]);
+
+        let numeric = IrcMessage::parse(":tmi.twitch.tv 001 justinfan12345 :Welcome, GLHF!").unwrap();
+        assert_eq!(numeric.command, "001");
+        assert_eq!(numeric.params, ["justinfan12345", "Welcome, GLHF!"]);
+
+        assert!(IrcMessage::parse("").is_none());
+        assert!(IrcMessage::parse("@tags-without-command").is_none());
+        assert!(IrcMessage::parse(":prefix-only").is_none());
+    }
+
+    #[test]
+    fn unescapes_tag_values() {
+        assert_eq!(unescape_tag(r"Sub\ssubscribed\sfor\s12\smonths!"), "Sub subscribed for 12 months!");
+        assert_eq!(unescape_tag(r"a\:b\\c"), r"a;b\c");
+        assert_eq!(unescape_tag(r"line\rbreak\n"), "line\rbreak\n");
+        // 未定義のエスケープは文字をそのまま残し、末尾の `\` は捨てる
+        assert_eq!(unescape_tag(r"\x\"), "x");
+        assert_eq!(unescape_tag("plain"), "plain");
+    }
+
+    /// ローカルの IRC スタブサーバーに接続して、PING への応答とコメントの変換を確かめる
+    #[test]
+    fn receives_messages_from_stub_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let settings = TwitchChatSettings {
+            channel: "#TestChannel".to_string(),
+            host: "127.0.0.1".to_string(),
+            port: listener.local_addr().unwrap().port(),
+            use_tls: false,
+            ..Default::default()
+        };
+        let (sender, receiver) = mpsc::channel();
+        let mut source = TwitchChatSource::new(settings);
+        source.start(sender).unwrap();
+
+        let (stream, _) = listener.accept().unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let mut server = stream.try_clone().unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        let mut next_line = move || lines.next().expect("接続が閉じられました").unwrap();
+
+        // ログイン（匿名）
+        assert_eq!(next_line(), "CAP REQ :twitch.tv/tags twitch.tv/commands");
+        assert_eq!(next_line(), "NICK justinfan12345");
+        assert_eq!(next_line(), "JOIN #testchannel");
+
+        let mut send = |line: &str| server.write_all(format!("{}\r\n", line).as_bytes()).unwrap();
+        send(":justinfan12345!justinfan12345@justinfan12345.tmi.twitch.tv JOIN #testchannel");
+        send("PING :tmi.twitch.tv");
+        assert_eq!(next_line(), "PONG :tmi.twitch.tv");
+        assert_eq!(source.state(), SourceState::Connected);
+
+        send(
+            "@badges=moderator/1;bits=100;display-name=Viewer;id=msg-1;mod=1;subscriber=0;tmi-sent-ts=1700000000000;user-id=12345 \
+             :viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #testchannel :Cheer100 nice stream",
+        );
+        send(
+            "@badges=subscriber/12;display-name=Subscriber;id=msg-2;login=subscriber;mod=0;msg-id=resub;\
+             msg-param-cumulative-months=12;subscriber=1;system-msg=Subscriber\\ssubscribed\\sfor\\s12\\smonths!;\
+             tmi-sent-ts=1700000001000;user-id=67890 :tmi.twitch.tv USERNOTICE #testchannel :one year!",
+        );
+
+        let cheer = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(cheer.platform, Platform::Twitch);
+        assert_eq!(cheer.id, "msg-1");
+        assert_eq!(cheer.author_id, "twitch:12345");
+        assert_eq!(cheer.author_name, "Viewer");
+        assert_eq!(cheer.text, "Cheer100 nice stream");
+        assert_eq!(cheer.timestamp_ms, 1_700_000_000_000);
+        assert!(cheer.is_moderator);
+        assert!(!cheer.is_member);
+        let bits = cheer.super_chat.expect("Bits がスーパーチャットとして変換されていません");
+        assert_eq!(bits.amount, 100.0);
+        assert_eq!(bits.currency, "BITS");
+        assert!(cheer.membership.is_none());
+
+        let resub = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(resub.author_id, "twitch:67890");
+        assert_eq!(resub.author_name, "Subscriber");
+        assert_eq!(resub.text, "one year!");
+        assert!(resub.is_member);
+        assert!(!resub.is_moderator);
+        assert!(resub.super_chat.is_none());
+        let membership = resub.membership.expect("サブスクがメンバーシップとして変換されていません");
+        assert_eq!(membership.months, 12);
+        assert!(!membership.is_gift);
+
+        source.stop();
+        assert_eq!(next_line(), "QUIT");
+        assert_eq!(source.state(), SourceState::Stopped);
+    }
+}
@@ -0,0 +1,244 @@
+use std::fs;
+use log::{info, error};
+use rusqlite::{params, Connection, OptionalExtension};
+use super::comment::{ChatMessage, now_millis};
+
+const DATABASE_PATH: &str = "data/viewers.db";
+
+/// 視聴者ごとの累計統計
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct ViewerStats {
+    pub author_id: String,
+    pub author_name: String,
+    pub first_seen_ms: u64,
+    pub last_seen_ms: u64,
+    pub streams_attended: u32,
+    pub message_count: u32,
+    /// スーパーチャット合計（通貨は区別しない）
+    pub total_super_chat: f64,
+}
+
+/// コメント 1 件を記録した結果
+#[derive(Debug, Clone)]
+pub struct ViewerVisit {
+    pub stats: ViewerStats,
+    /// この配信での最初のコメントかどうか
+    pub first_in_stream: bool,
+}
+
+impl ViewerStats {
+    /// 初参加日時（ローカル時刻）を表示用に整形する
+    pub fn first_seen_label(&self) -> String {
+        chrono::DateTime::from_timestamp_millis(self.first_seen_ms as i64)
+            .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default()
+    }
+}
+
+impl ViewerVisit {
+    /// 今回の配信が初参加かどうか
+    pub fn is_first_time(&self) -> bool {
+        self.stats.streams_attended <= 1
+    }
+}
+
+/// 配信をまたいで視聴者統計を保持するローカルデータベース
+pub struct ViewerDatabase {
+    conn: Connection,
+    /// 配信中のみ `Some`。配信していない間のコメントは参加回数に数えない
+    stream_id: Option<String>,
+}
+
+impl ViewerDatabase {
+    pub fn open_default() -> rusqlite::Result<Self> {
+        if let Err(e) = fs::create_dir_all("data") {
+            error!("データディレクトリの作成に失敗: {}", e);
+        }
+        Self::open(DATABASE_PATH)
+    }
+
+    pub fn open(path: &str) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS viewers (
+                author_id TEXT PRIMARY KEY,
+                author_name TEXT NOT NULL,
+                first_seen_ms INTEGER NOT NULL,
+                last_seen_ms INTEGER NOT NULL,
+                streams_attended INTEGER NOT NULL DEFAULT 0,
+                message_count INTEGER NOT NULL DEFAULT 0,
+                total_super_chat REAL NOT NULL DEFAULT 0,
+                last_stream_id TEXT
+            );",
+        )?;
+
+        Ok(Self { conn, stream_id: None })
+    }
+
+    /// 新しい配信の区切りを開始する
+    pub fn begin_stream(&mut self) {
+        let stream_id = now_millis().to_string();
+        info!("視聴者統計の配信IDを更新: {}", stream_id);
+        self.stream_id = Some(stream_id);
+    }
+
+    /// 配信の区切りを終える。次に `begin_stream` を呼ぶまで参加回数は増えない
+    pub fn end_stream(&mut self) {
+        self.stream_id = None;
+    }
+
+    pub fn record_message(&mut self, message: &ChatMessage) -> rusqlite::Result<ViewerVisit> {
+        let super_chat = message.super_chat.as_ref().map(|s| s.amount).unwrap_or(0.0);
+        let tx = self.conn.transaction()?;
+
+        let last_stream_id: Option<Option<String>> = tx
+            .query_row(
+                "SELECT last_stream_id FROM viewers WHERE author_id = ?1",
+                params![message.author_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        let first_in_stream = match &last_stream_id {
+            None => {
+                tx.execute(
+                    "INSERT INTO viewers (author_id, author_name, first_seen_ms, last_seen_ms,
+                        streams_attended, message_count, total_super_chat, last_stream_id)
+                     VALUES (?1, ?2, ?3, ?3, ?4, 1, ?5, ?6)",
+                    params![message.author_id, message.author_name, message.timestamp_ms as i64,
+                        self.stream_id.is_some() as i64, super_chat, self.stream_id],
+                )?;
+                self.stream_id.is_some()
+            }
+            Some(last) => {
+                let first_in_stream = self.stream_id.is_some() && *last != self.stream_id;
+                tx.execute(
+                    "UPDATE viewers SET
+                        author_name = ?2,
+                        last_seen_ms = ?3,
+                        streams_attended = streams_attended + ?4,
+                        message_count = message_count + 1,
+                        total_super_chat = total_super_chat + ?5,
+                        last_stream_id = COALESCE(?6, last_stream_id)
+                     WHERE author_id = ?1",
+                    params![message.author_id, message.author_name, message.timestamp_ms as i64,
+                        first_in_stream as i64, super_chat, self.stream_id],
+                )?;
+                first_in_stream
+            }
+        };
+
+        let stats = Self::query_stats(&tx, &message.author_id)?;
+        tx.commit()?;
+
+        Ok(ViewerVisit { stats, first_in_stream })
+    }
+
+    fn query_stats(conn: &Connection, author_id: &str) -> rusqlite::Result<ViewerStats> {
+        conn.query_row(
+            "SELECT author_id, author_name, first_seen_ms, last_seen_ms, streams_attended,
+                message_count, total_super_chat
+             FROM viewers WHERE author_id = ?1",
+            params![author_id],
+            |row| Ok(ViewerStats {
+                author_id: row.get(0)?,
+                author_name: row.get(1)?,
+                first_seen_ms: row.get::<_, i64>(2)? as u64,
+                last_seen_ms: row.get::<_, i64>(3)? as u64,
+                streams_attended: row.get(4)?,
+                message_count: row.get(5)?,
+                total_super_chat: row.get(6)?,
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::comment::{Platform, SuperChat};
+
+    fn message(author_id: &str, super_chat: Option<f64>) -> ChatMessage {
+        ChatMessage {
+            platform: Platform::YouTube,
+            id: format!("{}-{}", author_id, now_millis()),
+            author_id: author_id.to_string(),
+            author_name: format!("{} さん", author_id),
+            text: "こんにちは".to_string(),
+            timestamp_ms: now_millis(),
+            is_member: false,
+            is_moderator: false,
+            super_chat: super_chat.map(|amount| SuperChat { amount, currency: "JPY".to_string() }),
+            membership: None,
+            simulated: false,
+        }
+    }
+
+    /// 配信 ID はミリ秒から作るため、続けて呼んでも別の ID になるよう少し待つ
+    fn begin_stream(database: &mut ViewerDatabase) {
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        database.begin_stream();
+    }
+
+    #[test]
+    fn first_message_in_stream_counts_attendance() {
+        let mut database = ViewerDatabase::open(":memory:").unwrap();
+        begin_stream(&mut database);
+        let visit = database.record_message(&message("alice", Some(500.0))).unwrap();
+        assert!(visit.first_in_stream);
+        assert!(visit.is_first_time());
+        assert_eq!(visit.stats.streams_attended, 1);
+        assert_eq!(visit.stats.message_count, 1);
+        assert_eq!(visit.stats.total_super_chat, 500.0);
+    }
+
+    #[test]
+    fn repeat_message_in_same_stream_is_not_first() {
+        let mut database = ViewerDatabase::open(":memory:").unwrap();
+        begin_stream(&mut database);
+        database.record_message(&message("alice", None)).unwrap();
+        let visit = database.record_message(&message("alice", Some(200.0))).unwrap();
+        assert!(!visit.first_in_stream);
+        assert_eq!(visit.stats.streams_attended, 1);
+        assert_eq!(visit.stats.message_count, 2);
+        assert_eq!(visit.stats.total_super_chat, 200.0);
+    }
+
+    #[test]
+    fn begin_stream_increments_attendance_once() {
+        let mut database = ViewerDatabase::open(":memory:").unwrap();
+        begin_stream(&mut database);
+        database.record_message(&message("alice", None)).unwrap();
+        database.record_message(&message("alice", None)).unwrap();
+
+        begin_stream(&mut database);
+        let visit = database.record_message(&message("alice", None)).unwrap();
+        assert!(visit.first_in_stream);
+        assert!(!visit.is_first_time());
+        assert_eq!(visit.stats.streams_attended, 2);
+        let visit = database.record_message(&message("alice", None)).unwrap();
+        assert!(!visit.first_in_stream);
+        assert_eq!(visit.stats.streams_attended, 2);
+        assert_eq!(visit.stats.message_count, 4);
+    }
+
+    #[test]
+    fn messages_outside_stream_do_not_count_attendance() {
+        let mut database = ViewerDatabase::open(":memory:").unwrap();
+        let visit = database.record_message(&message("alice", None)).unwrap();
+        assert!(!visit.first_in_stream);
+        assert_eq!(visit.stats.streams_attended, 0);
+
+        begin_stream(&mut database);
+        let visit = database.record_message(&message("alice", None)).unwrap();
+        assert!(visit.first_in_stream);
+        assert_eq!(visit.stats.streams_attended, 1);
+
+        database.end_stream();
+        let visit = database.record_message(&message("alice", None)).unwrap();
+        assert!(!visit.first_in_stream);
+        assert_eq!(visit.stats.streams_attended, 1);
+        assert_eq!(visit.stats.message_count, 3);
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use super::comment::ChatMessage;
+
+pub const VOICE_ASSIGNMENTS_PATH: &str = "config/voice_assignments.json";
+
+/// 視聴者に手動で割り当てた話者
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserVoice {
+    /// 設定画面に表示するための名前（割り当て時点）
+    pub author_name: String,
+    pub speaker_id: u32,
+}
+
+/// 視聴者・ロールごとの読み上げ話者の割り当て
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VoiceAssignments {
+    /// author_id → 話者
+    pub users: BTreeMap<String, UserVoice>,
+    /// 手動割り当てのない視聴者に author_id から決まる話者を使う
+    pub hash_by_author: bool,
+    /// 自動割り当ての候補（空の場合は VOICEVOX から取得した全話者）
+    pub hash_pool: Vec<u32>,
+    /// 自動割り当てで使わない話者（配信者の声など）
+    pub reserved: Vec<u32>,
+    pub member_speaker: Option<u32>,
+    pub moderator_speaker: Option<u32>,
+    pub super_chat_speaker: Option<u32>,
+}
+
+impl VoiceAssignments {
+    /// コメントを読み上げる話者を決める
+    ///
+    /// 優先順位: 手動割り当て → スーパーチャット → モデレーター → メンバー → 自動割り当て → 既定
+    pub fn resolve(&self, message: &ChatMessage, known_speakers: &[u32], default_speaker: u32) -> u32 {
+        if let Some(user) = self.users.get(&message.author_id) {
+            return user.speaker_id;
+        }
+
+        let role_speaker = [
+            (message.super_chat.is_some(), self.super_chat_speaker),
+            (message.is_moderator, self.moderator_speaker),
+            (message.is_member, self.member_speaker),
+        ]
+        .into_iter()
+        .find_map(|(applies, speaker)| if applies { speaker } else { None });
+        if let Some(speaker) = role_speaker {
+            return speaker;
+        }
+
+        if self.hash_by_author {
+            if let Some(speaker) = self.hashed_speaker(&message.author_id, known_speakers) {
+                return speaker;
+            }
+        }
+        default_speaker
+    }
+
+    /// author_id から決定的に話者を選ぶ（予約された話者は除く）
+    pub fn hashed_speaker(&self, author_id: &str, known_speakers: &[u32]) -> Option<u32> {
+        let pool = if self.hash_pool.is_empty() { known_speakers } else { &self.hash_pool };
+        let candidates: Vec<u32> = pool.iter()
+            .copied()
+            .filter(|id| !self.reserved.contains(id))
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+        Some(candidates[(fnv1a(author_id) % candidates.len() as u64) as usize])
+    }
+}
+
+/// 実行ごとに変わらないハッシュ（FNV-1a 64bit）
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
@@ -0,0 +1,254 @@
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use log::{info, error};
+use serde_json::Value;
+use super::chat_source::{wait_while_running, ChatSource, SourceState};
+use super::comment::{ChatMessage, ChatSender, Membership, Platform, SuperChat, now_millis};
+
+const API_BASE: &str = "https://www.googleapis.com/youtube/v3";
+
+/// API が間隔を返さなかった場合のポーリング間隔
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// エラー時の再試行間隔
+const RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Default, Clone)]
+pub struct YouTubeChatSettings {
+    pub api_key: String,
+    /// 配信の動画 ID（URL の v= 以降）
+    pub video_id: String,
+}
+
+/// YouTube Data API v3 の liveChatMessages をポーリングする取得元
+pub struct YouTubeChatSource {
+    settings: YouTubeChatSettings,
+    running: Arc<AtomicBool>,
+    state: Arc<Mutex<SourceState>>,
+    poll_thread: Option<thread::JoinHandle<()>>,
+}
+
+impl YouTubeChatSource {
+    pub fn new(settings: YouTubeChatSettings) -> Self {
+        Self {
+            settings,
+            running: Arc::new(AtomicBool::new(false)),
+            state: Arc::new(Mutex::new(SourceState::Stopped)),
+            poll_thread: None,
+        }
+    }
+}
+
+impl ChatSource for YouTubeChatSource {
+    fn platform(&self) -> Platform {
+        Platform::YouTube
+    }
+
+    fn name(&self) -> String {
+        self.settings.video_id.clone()
+    }
+
+    fn start(&mut self, sender: ChatSender) -> Result<(), String> {
+        self.stop();
+        if self.settings.api_key.trim().is_empty() || self.settings.video_id.trim().is_empty() {
+            return Err("APIキーと動画IDを入力してください".to_string());
+        }
+
+        let settings = self.settings.clone();
+        let running = Arc::clone(&self.running);
+        let state = Arc::clone(&self.state);
+        running.store(true, Ordering::SeqCst);
+        *state.lock().unwrap() = SourceState::Connecting;
+
+        let handle = thread::spawn(move || {
+            let client = YouTubeClient::new(&settings.api_key);
+            poll_loop(&client, &settings.video_id, &sender, &running, &state);
+            *state.lock().unwrap() = SourceState::Stopped;
+        });
+
+        self.poll_thread = Some(handle);
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.poll_thread.take() {
+            let _ = handle.join();
+        }
+    }
+
+    fn state(&self) -> SourceState {
+        self.state.lock().unwrap().clone()
+    }
+}
+
+impl Drop for YouTubeChatSource {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn poll_loop(
+    client: &YouTubeClient,
+    video_id: &str,
+    sender: &ChatSender,
+    running: &AtomicBool,
+    state: &Mutex<SourceState>,
+) {
+    let live_chat_id = loop {
+        match client.live_chat_id(video_id) {
+            Ok(id) => break id,
+            Err(e) => {
+                error!("liveChatId の取得に失敗: {}", e);
+                *state.lock().unwrap() = SourceState::Reconnecting(e);
+                if !wait_while_running(RETRY_INTERVAL, running) {
+                    return;
+                }
+            }
+        }
+    };
+    info!("YouTube チャットに接続: {}", live_chat_id);
+
+    let mut page_token: Option<String> = None;
+    // 接続前の過去ログは流さない
+    let mut skip_backlog = true;
+
+    while running.load(Ordering::SeqCst) {
+        let interval = match client.messages(&live_chat_id, page_token.as_deref()) {
+            Ok(page) => {
+                *state.lock().unwrap() = SourceState::Connected;
+                if !skip_backlog {
+                    for message in page.messages {
+                        if sender.send(message).is_err() {
+                            error!("チャットの受信側が閉じられています");
+                            return;
+                        }
+                    }
+                }
+                skip_backlog = false;
+                page_token = page.next_page_token;
+                page.polling_interval.unwrap_or(DEFAULT_POLL_INTERVAL)
+            }
+            Err(e) => {
+                error!("YouTube チャットの取得に失敗: {}", e);
+                *state.lock().unwrap() = SourceState::Reconnecting(e);
+                RETRY_INTERVAL
+            }
+        };
+
+        if !wait_while_running(interval, running) {
+            return;
+        }
+    }
+}
+
+struct MessagePage {
+    messages: Vec<ChatMessage>,
+    next_page_token: Option<String>,
+    polling_interval: Option<Duration>,
+}
+
+struct YouTubeClient {
+    api_key: String,
+    agent: ureq::Agent,
+}
+
+impl YouTubeClient {
+    fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.trim().to_string(),
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(15))
+                .build(),
+        }
+    }
+
+    fn get(&self, path: &str, query: &[(&str, &str)]) -> Result<Value, String> {
+        let mut request = self.agent
+            .get(&format!("{}/{}", API_BASE, path))
+            .query("key", &self.api_key);
+        for (key, value) in query {
+            request = request.query(key, value);
+        }
+        request.call()
+            .map_err(|e| e.to_string())?
+            .into_json()
+            .map_err(|e| format!("応答を解析できません: {}", e))
+    }
+
+    fn live_chat_id(&self, video_id: &str) -> Result<String, String> {
+        let response = self.get("videos", &[("part", "liveStreamingDetails"), ("id", video_id.trim())])?;
+        response["items"][0]["liveStreamingDetails"]["activeLiveChatId"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "配信中のライブチャットが見つかりません".to_string())
+    }
+
+    fn messages(&self, live_chat_id: &str, page_token: Option<&str>) -> Result<MessagePage, String> {
+        let mut query = vec![("liveChatId", live_chat_id), ("part", "snippet,authorDetails")];
+        if let Some(token) = page_token {
+            query.push(("pageToken", token));
+        }
+        let response = self.get("liveChat/messages", &query)?;
+
+        let messages = response["items"]
+            .as_array()
+            .map(|items| items.iter().filter_map(to_chat_message).collect())
+            .unwrap_or_default();
+
+        Ok(MessagePage {
+            messages,
+            next_page_token: response["nextPageToken"].as_str().map(str::to_string),
+            polling_interval: response["pollingIntervalMillis"].as_u64().map(Duration::from_millis),
+        })
+    }
+}
+
+/// liveChatMessage リソースを共通のコメントモデルに変換する
+fn to_chat_message(item: &Value) -> Option<ChatMessage> {
+    let snippet = &item["snippet"];
+    let author = &item["authorDetails"];
+
+    let super_chat = snippet["superChatDetails"]["amountMicros"]
+        .as_str()
+        .and_then(|micros| micros.parse::<f64>().ok())
+        .map(|micros| SuperChat {
+            amount: micros / 1_000_000.0,
+            currency: snippet["superChatDetails"]["currency"].as_str().unwrap_or("").to_string(),
+        });
+
+    let membership = match snippet["type"].as_str()? {
+        "newSponsorEvent" => Some(Membership { months: 1, is_gift: false }),
+        "memberMilestoneChatEvent" => Some(Membership {
+            months: snippet["memberMilestoneChatDetails"]["memberMonth"].as_u64().unwrap_or(1) as u32,
+            is_gift: false,
+        }),
+        "giftMembershipReceivedEvent" => Some(Membership { months: 1, is_gift: true }),
+        "textMessageEvent" | "superChatEvent" => None,
+        // 削除通知などコメントとして表示しないもの
+        _ => return None,
+    };
+
+    let timestamp_ms = snippet["publishedAt"]
+        .as_str()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.timestamp_millis() as u64)
+        .unwrap_or_else(now_millis);
+
+    Some(ChatMessage {
+        platform: Platform::YouTube,
+        id: item["id"].as_str()?.to_string(),
+        author_id: author["channelId"].as_str()?.to_string(),
+        author_name: author["displayName"].as_str().unwrap_or("").to_string(),
+        text: snippet["displayMessage"].as_str().unwrap_or("").to_string(),
+        timestamp_ms,
+        is_member: author["isChatSponsor"].as_bool().unwrap_or(false),
+        is_moderator: author["isChatModerator"].as_bool().unwrap_or(false)
+            || author["isChatOwner"].as_bool().unwrap_or(false),
+        super_chat,
+        membership,
+        simulated: false,
+    })
+}
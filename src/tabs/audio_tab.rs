@@ -1,67 +1,754 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
 use eframe::egui;
+use super::widgets;
+use crate::models::audio::BGMTrack;
+use crate::models::audio_config::{AudioConfig, AUDIO_CONFIG_PATH};
+use crate::models::audio_engine::{AudioBlock, AudioEngine, EngineInputs, MonitorSettings, OutputTarget, TapPoint};
+use crate::models::audio_recorder::{AudioRecorder, RecorderSettings, RecordingFormat};
+use crate::models::audio_output;
+use crate::models::bgm_player::{PlaybackState, TrackPlayer};
+use crate::models::chat_triggers::{
+    ChatTrigger, ChatTriggerSettings, ChatTriggerState, TriggerKind, TriggerOutcome, CHAT_TRIGGERS_PATH, TRIGGER_LOG_PATH,
+};
+use crate::models::comment::ReceivedComment;
+use crate::models::effects_chain::{EffectsChainSettings, EqBand, EqBandKind, MAX_DELAY_MS, MAX_EQ_BANDS};
+use crate::models::hotkey::{self, Accelerator, HotkeyAction};
+use crate::models::level_meter::LevelMeters;
+use crate::models::loudness::{LoudnessAnalyzer, LoudnessInfo, LoudnessSettings};
+use crate::models::mic_control::{MicControlSettings, MicIndicator, MicIndicatorFeed, MicMode, PushToTalk};
+use crate::models::mic_input::{self, MicCapture};
+use crate::models::mic_processing::MicProcessingSettings;
+use crate::models::mixer::{BusKind, BusSettings, MixerSettings};
+use crate::models::now_playing::{self, NowPlaying, NowPlayingFeed, NowPlayingSettings, TrackMetadata};
+use crate::models::playlist::{self, Playlist, RepeatMode, TransitionMode};
+use crate::models::settings_store;
+use crate::models::soundboard::{EffectOutput, EffectVoice, PlayMode, SoundEffect, Soundboard, SOUNDBOARD_PATH};
+use crate::models::track_license::{LicenseLibrary, LicenseStatus, TRACK_LICENSES_PATH};
+
+/// ミキサーのメーターの大きさ
+const METER_SIZE: egui::Vec2 = egui::vec2(14.0, 48.0);
+/// サウンドボードのボタンの大きさ
+const PAD_SIZE: egui::Vec2 = egui::vec2(110.0, 48.0);
+/// 同期表示用のマスター出力の購読で滞留させるブロック数（1 秒分）
+const SYNC_TAP_CAPACITY: usize = 100;
 
-#[derive(Default)]
 pub struct AudioTab {
     pub bgm_tracks: Vec<BGMTrack>,
-    pub effects: Vec<SoundEffect>,
-    pub master_volume: f32,
-    pub bgm_volume: f32,
-    pub effects_volume: f32,
+    pub soundboard: Soundboard,
+    /// コメントから鳴らす効果音
+    pub chat_triggers: ChatTriggerSettings,
+    /// マスター・各バスの音量など（BGM 音量・効果音音量はバスのゲイン）
+    pub mixer_settings: MixerSettings,
+    pub playlists: Vec<Playlist>,
+    selected_playlist: usize,
+    playlist_import_path: String,
+    playlist_error: Option<String>,
+    /// ドラッグ中の曲の位置
+    dragging_track: Option<usize>,
+    pub mic_settings: MicProcessingSettings,
+    /// マイクのミュートの操作方法と配信画面のミュート表示
+    pub mic_control: MicControlSettings,
+    pub loudness_settings: LoudnessSettings,
+    pub recorder_settings: RecorderSettings,
+    pub now_playing_settings: NowPlayingSettings,
+    /// BGM の権利情報
+    pub licenses: LicenseLibrary,
+    licenses_dirty: bool,
+    /// 権利情報を編集中の曲
+    editing_license: Option<u64>,
+    /// 配信中かどうか（配信中は権利を確認していない曲を飛ばせる）
+    live: bool,
+    /// プレイリストで飛ばしている曲
+    skipped_tracks: HashSet<u64>,
+    /// エフェクトを編集中のバス
+    effects_bus: BusKind,
+    /// 保存していない変更がある（スライダーの操作中は保存しない）
+    config_dirty: bool,
+    soundboard_dirty: bool,
+    chat_triggers_dirty: bool,
+    chat_trigger_state: ChatTriggerState,
+    /// 表示中のサウンドボードのページ
+    soundboard_page: usize,
+    /// 設定を編集中の効果音
+    editing_effect: Option<usize>,
+    engine: AudioEngine,
+    loudness: LoudnessAnalyzer,
+    recorder: AudioRecorder,
+    recorder_error: Option<String>,
+    level_meters: LevelMeters,
+    now_playing: NowPlayingFeed,
+    /// ファイルパスごとの解析結果（解析中は `None`）
+    loudness_results: HashMap<String, Option<Result<LoudnessInfo, String>>>,
+    mic: MicCapture,
+    push_to_talk: PushToTalk,
+    mic_indicator: MicIndicatorFeed,
+    /// `None` は既定のデバイス
+    mic_device: Option<String>,
+    input_devices: Vec<String>,
+    output_target: OutputTarget,
+    monitor_settings: MonitorSettings,
+    output_devices: Vec<String>,
+    new_track_path: String,
+    next_track_id: u64,
+    add_error: Option<String>,
+    effect_error: Option<String>,
+    /// ホットキーを記録中の効果音
+    recording_effect: Option<usize>,
 }
 
-#[derive(Default)]
-pub struct BGMTrack {
-    pub name: String,
-    pub volume: f32,
-    pub is_playing: bool,
-}
+impl AudioTab {
+    pub fn new() -> Self {
+        let mut mixer_settings = MixerSettings::default();
+        mixer_settings.bus_mut(BusKind::Bgm).gain = 0.5;
+        mixer_settings.bus_mut(BusKind::Effects).gain = 0.5;
+        let config: AudioConfig = settings_store::load_json(AUDIO_CONFIG_PATH);
+        mixer_settings.effects = config.bus_effects;
+        mixer_settings.routing = config.routing;
+        mixer_settings.master_limiter = config.master_limiter;
+        let recorder_settings = config.recorder;
+        let monitor_settings = config.monitor;
+        let now_playing_settings = config.now_playing;
+        let mic_control = config.mic_control;
+        let mic_settings = config.mic_processing;
+        let loudness_settings = config.loudness;
 
-#[derive(Default)]
-pub struct SoundEffect {
-    pub name: String,
-    pub volume: f32,
-    pub hotkey: String,
-}
+        let engine = AudioEngine::new();
+        let level_meters = engine.state().mixer.level_meters();
+        let mut audio_tab = Self {
+            bgm_tracks: Vec::new(),
+            soundboard: settings_store::load_json(SOUNDBOARD_PATH),
+            chat_triggers: settings_store::load_json(CHAT_TRIGGERS_PATH),
+            mixer_settings,
+            playlists: vec![Playlist::new("プレイリスト1")],
+            selected_playlist: 0,
+            playlist_import_path: String::new(),
+            playlist_error: None,
+            dragging_track: None,
+            mic_settings: mic_settings.clone(),
+            mic_control,
+            loudness_settings,
+            recorder_settings,
+            now_playing_settings,
+            licenses: settings_store::load_json(TRACK_LICENSES_PATH),
+            licenses_dirty: false,
+            editing_license: None,
+            live: false,
+            skipped_tracks: HashSet::new(),
+            effects_bus: BusKind::Bgm,
+            config_dirty: false,
+            soundboard_dirty: false,
+            chat_triggers_dirty: false,
+            chat_trigger_state: ChatTriggerState::new(),
+            soundboard_page: 0,
+            editing_effect: None,
+            engine,
+            loudness: LoudnessAnalyzer::new(),
+            recorder: AudioRecorder::new(),
+            recorder_error: None,
+            level_meters,
+            now_playing: NowPlayingFeed::default(),
+            loudness_results: HashMap::new(),
+            mic: MicCapture::new(mic_settings),
+            push_to_talk: PushToTalk::new(),
+            mic_indicator: MicIndicatorFeed::default(),
+            mic_device: None,
+            input_devices: mic_input::input_device_names(),
+            output_target: OutputTarget::default(),
+            monitor_settings,
+            output_devices: audio_output::output_device_names(),
+            new_track_path: String::new(),
+            next_track_id: 1,
+            add_error: None,
+            effect_error: None,
+            recording_effect: None,
+        };
+        audio_tab.engine.start(audio_tab.output_target.clone());
+        audio_tab.sync_mixer();
+        audio_tab.apply_monitor();
+        let paths: Vec<String> = audio_tab.soundboard.effects.iter().map(|effect| effect.file_path.clone()).collect();
+        for path in paths {
+            audio_tab.request_loudness(&path);
+        }
+        audio_tab
+    }
+
+    /// 各バスとマスターのレベル（配信状況タブのメーター用）
+    pub fn level_meters(&self) -> LevelMeters {
+        self.level_meters.clone()
+    }
+
+    /// 再生中の曲（配信状況タブ・バナー用）
+    pub fn now_playing(&self) -> NowPlayingFeed {
+        Arc::clone(&self.now_playing)
+    }
+
+    /// マイクのミュート状態（配信状況タブ用）
+    pub fn mic_indicator(&self) -> MicIndicatorFeed {
+        Arc::clone(&self.mic_indicator)
+    }
+
+    /// マスター出力の購読（配信状況タブの同期表示用）
+    pub fn subscribe_master(&self) -> Receiver<AudioBlock> {
+        self.engine.subscribe(TapPoint::Master, SYNC_TAP_CAPACITY)
+    }
+
+    /// マイク・読み上げをミキサーへ流すための入力
+    pub fn engine_inputs(&self) -> EngineInputs {
+        self.engine.inputs()
+    }
+
+    /// ミキサーの設定をエンジンに反映する
+    fn sync_mixer(&self) {
+        self.engine.state().mixer.settings = self.mixer_settings.clone();
+    }
+
+    fn save_config(&mut self) {
+        let config = AudioConfig {
+            bus_effects: self.mixer_settings.effects.clone(),
+            recorder: self.recorder_settings.clone(),
+            routing: self.mixer_settings.routing.clone(),
+            monitor: self.monitor_settings.clone(),
+            now_playing: self.now_playing_settings.clone(),
+            mic_control: self.mic_control.clone(),
+            mic_processing: self.mic_settings.clone(),
+            loudness: self.loudness_settings.clone(),
+            master_limiter: self.mixer_settings.master_limiter.clone(),
+        };
+        if let Err(e) = settings_store::save_json(AUDIO_CONFIG_PATH, &config) {
+            log::error!("音声設定を保存できません: {}", e);
+        }
+        self.config_dirty = false;
+    }
+
+    fn save_soundboard(&mut self) {
+        if let Err(e) = settings_store::save_json(SOUNDBOARD_PATH, &self.soundboard) {
+            log::error!("サウンドボードを保存できません: {}", e);
+        }
+        self.soundboard_dirty = false;
+    }
+
+    fn save_licenses(&mut self) {
+        if let Err(e) = settings_store::save_json(TRACK_LICENSES_PATH, &self.licenses) {
+            log::error!("BGM の権利情報を保存できません: {}", e);
+        }
+        self.licenses_dirty = false;
+    }
+
+    fn save_chat_triggers(&mut self) {
+        if let Err(e) = settings_store::save_json(CHAT_TRIGGERS_PATH, &self.chat_triggers) {
+            log::error!("チャットの効果音の設定を保存できません: {}", e);
+        }
+        self.chat_triggers_dirty = false;
+    }
+
+    /// バックグラウンドの処理結果を取り込む。タブの表示状態に関わらず毎フレーム呼び出す
+    pub fn update(&mut self) {
+        self.update_now_playing();
+        self.update_skipped_tracks();
+
+        let results = self.loudness.poll();
+        if results.is_empty() {
+            return;
+        }
+        for (path, result) in results {
+            if let Err(e) = &result {
+                log::error!("ラウドネスを解析できません ({}): {}", path, e);
+            }
+            self.loudness_results.insert(path, Some(result));
+        }
+        self.apply_normalization();
+    }
+
+    /// 再生中の曲（プレイリストの曲、なければ個別に再生中の曲）が変わったら共有の値とテキストファイルを更新する
+    fn update_now_playing(&mut self) {
+        let current = {
+            let mut state = self.engine.state();
+            state.bgm.playlist_current().or_else(|| {
+                self.bgm_tracks
+                    .iter()
+                    .map(|track| track.id)
+                    .find(|&id| state.bgm.track_mut(id).is_some_and(|player| player.status().state == PlaybackState::Playing))
+            })
+        };
+        let now_playing = current.and_then(|id| self.bgm_tracks.iter().find(|track| track.id == id)).map(NowPlaying::from_track);
+
+        let mut shared = self.now_playing.lock().unwrap();
+        if *shared == now_playing {
+            return;
+        }
+        if let Some(track) = &now_playing {
+            log::info!("再生中の曲: {}", track.label());
+        }
+        *shared = now_playing.clone();
+        drop(shared);
+        if self.now_playing_settings.write_file {
+            if let Err(e) = self.now_playing_settings.write(now_playing.as_ref()) {
+                log::error!("再生中の曲を書き出せません: {}", e);
+            }
+        }
+    }
+
+    pub fn set_live(&mut self, live: bool) {
+        self.live = live;
+    }
+
+    pub fn skip_unsafe_when_live(&self) -> bool {
+        self.licenses.skip_when_live
+    }
+
+    pub fn set_skip_unsafe_when_live(&mut self, skip: bool) {
+        self.licenses.skip_when_live = skip;
+        self.save_licenses();
+    }
+
+    /// 配信開始前に確認する、プレイリストにある権利未確認・使用不可の曲（確認しない設定の場合は空）
+    pub fn license_warnings(&self) -> Vec<String> {
+        if !self.licenses.warn_on_stream_start {
+            return Vec::new();
+        }
+        let mut warnings = Vec::new();
+        for playlist in &self.playlists {
+            for id in &playlist.track_ids {
+                let Some(track) = self.bgm_tracks.iter().find(|track| track.id == *id) else { continue };
+                let status = self.licenses.status(&track.file_path);
+                let warning = format!("{}: {}（{}）", playlist.name, track.name, status.label());
+                if status != LicenseStatus::Safe && !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            }
+        }
+        warnings
+    }
+
+    /// 配信中で「飛ばす」設定の場合、安全と確認していない曲をプレイリストで飛ばす
+    fn update_skipped_tracks(&mut self) {
+        let skipped: HashSet<u64> = if self.live && self.licenses.skip_when_live {
+            self.bgm_tracks.iter().filter(|track| !self.licenses.is_safe(&track.file_path)).map(|track| track.id).collect()
+        } else {
+            HashSet::new()
+        };
+        if skipped != self.skipped_tracks {
+            if !skipped.is_empty() {
+                log::info!("配信中のため権利を確認していない {} 曲を飛ばします", skipped.len());
+            }
+            self.engine.state().bgm.set_skipped(skipped.clone());
+            self.skipped_tracks = skipped;
+        }
+    }
+
+    /// まだ解析していないファイルの解析を始める
+    fn request_loudness(&mut self, path: &str) {
+        let path = path.trim();
+        if !path.is_empty() && !self.loudness_results.contains_key(path) {
+            self.loudness_results.insert(path.to_string(), None);
+            self.loudness.request(path);
+        }
+    }
+
+    fn loudness_info(&self, path: &str) -> Option<&LoudnessInfo> {
+        self.loudness_results.get(path.trim()).and_then(|result| result.as_ref()?.as_ref().ok())
+    }
+
+    fn normalization_gain(&self, path: &str) -> f32 {
+        self.loudness_settings.gain_for(self.loudness_info(path))
+    }
+
+    /// 解析結果と目標ラウドネスから BGM トラックの倍率を設定し直す
+    fn apply_normalization(&self) {
+        let gains: Vec<(u64, f32)> = self.bgm_tracks.iter().map(|track| (track.id, self.normalization_gain(&track.file_path))).collect();
+        let mut state = self.engine.state();
+        for (id, gain) in gains {
+            if let Some(player) = state.bgm.track_mut(id) {
+                player.set_normalization(gain);
+            }
+        }
+    }
+
+    /// 効果音の再生モードに従って再生する
+    pub fn play_effect(&mut self, index: usize) {
+        match self.start_effect(index) {
+            Ok(()) => self.effect_error = None,
+            Err(e) => self.effect_error = Some(e),
+        }
+    }
+
+    fn start_effect(&mut self, index: usize) -> Result<(), String> {
+        let Some(effect) = self.soundboard.effects.get(index).cloned() else {
+            return Err(format!("効果音 {} がありません", index + 1));
+        };
+        let looping = effect.play_mode == PlayMode::ToggleLoop;
+        if looping {
+            // ループ中なら止めるだけ
+            let mut state = self.engine.state();
+            let mut stopped = false;
+            for voice in state.effects.iter_mut().filter(|voice| voice.effect == index && voice.is_looping()) {
+                voice.stop();
+                stopped = true;
+            }
+            if stopped {
+                return Ok(());
+            }
+        }
+
+        let path = effect.file_path.trim().to_string();
+        self.request_loudness(&path);
+        // ファイルを開くのはエンジンのロック外で行う
+        let mut player = TrackPlayer::open(&path, effect.volume).map_err(|e| format!("{}: {}", effect.name, e))?;
+        player.set_normalization(self.normalization_gain(&path));
+        let voice = EffectVoice::start(index, &effect, player, looping);
+
+        let mut state = self.engine.state();
+        match effect.play_mode {
+            PlayMode::Restart => state.effects.retain(|voice| voice.effect != index),
+            PlayMode::StopOthers => state.effects.iter_mut().for_each(EffectVoice::stop),
+            PlayMode::Overlap | PlayMode::ToggleLoop => {}
+        }
+        state.effects.push(voice);
+        Ok(())
+    }
+
+    /// コメントに一致するトリガーがあれば、クールダウンなどを確かめて効果音を鳴らす
+    pub fn handle_comment(&mut self, comment: &ReceivedComment) {
+        if !self.chat_triggers.enabled {
+            return;
+        }
+        let Some(trigger) = self.chat_triggers.find(&comment.message).cloned() else { return };
+        let playing = self.engine.state().effects.iter().filter(|voice| voice.is_playing()).count();
+        let message = &comment.message;
+        let mut outcome = self.chat_trigger_state.check(message, &trigger, &self.chat_triggers, comment.spam.skip_tts, playing);
+        if outcome == TriggerOutcome::Played {
+            if let Err(e) = self.start_effect(trigger.effect) {
+                log::warn!("コメントから効果音を鳴らせません: {}", e);
+                outcome = TriggerOutcome::Failed;
+            }
+        }
+        let name = self.soundboard.effects.get(trigger.effect).map_or("", |effect| effect.name.as_str());
+        self.chat_trigger_state.record(message, name, outcome);
+    }
+
+    pub fn stop_effects(&mut self) {
+        self.engine.state().effects.clear();
+    }
+
+    /// 効果音を削除し、再生中の音の番号を詰める
+    fn remove_effect(&mut self, index: usize) {
+        self.soundboard.effects.remove(index);
+        let mut state = self.engine.state();
+        state.effects.retain(|voice| voice.effect != index);
+        for voice in &mut state.effects {
+            if voice.effect > index {
+                voice.effect -= 1;
+            }
+        }
+        self.chat_triggers.effect_removed(index);
+        self.chat_triggers_dirty = true;
+        self.editing_effect = None;
+        self.recording_effect = None;
+    }
+
+    /// プッシュトゥトークの間はキーの状態でミュートするため何もしない
+    pub fn toggle_mic_mute(&mut self) {
+        if self.mic_control.mode == MicMode::PushToTalk {
+            return;
+        }
+        let mic = self.mixer_settings.bus_mut(BusKind::Mic);
+        mic.mute = !mic.mute;
+        self.sync_mixer();
+    }
+
+    /// プッシュトゥトークのキーの状態をミュートに反映し、配信状況タブの表示を更新する。毎フレーム呼び出す
+    pub fn update_mic_control(&mut self, push_to_talk_held: bool) {
+        if self.mic_control.mode == MicMode::PushToTalk {
+            let delay = Duration::from_millis(self.mic_control.release_delay_ms as u64);
+            let mute = self.push_to_talk.update(push_to_talk_held, delay);
+            let mic = self.mixer_settings.bus_mut(BusKind::Mic);
+            if mic.mute != mute {
+                mic.mute = mute;
+                self.sync_mixer();
+            }
+        }
+
+        let muted = self.mixer_settings.bus(BusKind::Mic).mute;
+        *self.mic_indicator.lock().unwrap() = MicIndicator {
+            muted,
+            mode: self.mic_control.mode,
+            overlay_text: (muted && self.mic_control.show_overlay).then(|| self.mic_control.overlay_text.clone()),
+        };
+    }
+
+    /// 効果音ごとの（操作, 効果音名, ホットキー）
+    pub fn effect_hotkeys(&self) -> Vec<(HotkeyAction, String, String)> {
+        self.soundboard
+            .effects
+            .iter()
+            .enumerate()
+            .map(|(index, effect)| (HotkeyAction::PlayEffect(index), effect.name.clone(), effect.hotkey.clone()))
+            .collect()
+    }
+
+    pub fn is_recording_hotkey(&self) -> bool {
+        self.recording_effect.is_some()
+    }
+
+    fn add_track(&mut self) {
+        let path = self.new_track_path.trim().to_string();
+        match self.load_track(&path) {
+            Ok(_) => {
+                self.new_track_path.clear();
+                self.add_error = None;
+            }
+            Err(e) => self.add_error = Some(e),
+        }
+    }
+
+    /// ファイルを開いて BGM に追加し、割り当てた ID を返す。既に追加済みのファイルはその ID を返す
+    fn load_track(&mut self, path: &str) -> Result<u64, String> {
+        if let Some(track) = self.bgm_tracks.iter().find(|track| track.file_path == path) {
+            return Ok(track.id);
+        }
+        let mut track = BGMTrack::from_path(self.next_track_id, path);
+        // ファイルを開くのはエンジンのロック外で行う
+        let mut player = TrackPlayer::open(path, track.volume)?;
+        track.metadata = TrackMetadata::read(path).unwrap_or_else(|e| {
+            log::warn!("タグを読めません: {}", e);
+            TrackMetadata::default()
+        });
+        player.set_normalization(self.normalization_gain(path));
+        self.engine.state().bgm.insert(track.id, player);
+        self.request_loudness(path);
+        let id = track.id;
+        self.bgm_tracks.push(track);
+        self.next_track_id += 1;
+        Ok(id)
+    }
+
+    /// フォルダーまたは M3U の曲を選択中のプレイリストに追加する
+    fn import_playlist(&mut self) {
+        let path = self.playlist_import_path.trim().to_string();
+        let files = if std::path::Path::new(&path).is_dir() {
+            playlist::scan_folder(&path)
+        } else {
+            playlist::parse_m3u(&path)
+        };
+        let files = match files {
+            Ok(files) => files,
+            Err(e) => {
+                self.playlist_error = Some(e);
+                return;
+            }
+        };
+
+        let mut errors = Vec::new();
+        for file in files {
+            match self.load_track(&file) {
+                Ok(id) => self.playlists[self.selected_playlist].track_ids.push(id),
+                Err(e) => errors.push(e),
+            }
+        }
+        self.playlist_error = (!errors.is_empty()).then(|| errors.join("\n"));
+    }
 
-impl AudioTab {
     pub fn ui(&mut self, ui: &mut egui::Ui) {
         ui.heading("音声設定");
 
         // マスターボリューム
+        let mut mixer_changed = false;
         ui.horizontal(|ui| {
             ui.label("マスター音量:");
-            ui.add(egui::Slider::new(&mut self.master_volume, 0.0..=1.0));
+            mixer_changed |= ui.add(egui::Slider::new(&mut self.mixer_settings.master.gain, 0.0..=1.0)).changed();
+        });
+
+        // ミキサー
+        ui.collapsing("ミキサー", |ui| {
+            egui::Grid::new("mixer_grid").striped(true).show(ui, |ui| {
+                ui.strong("バス");
+                ui.strong("レベル");
+                ui.strong("音量");
+                ui.strong("パン");
+                ui.strong("");
+                ui.end_row();
+
+                for kind in BusKind::ALL {
+                    ui.label(kind.label());
+                    if widgets::level_meter(ui, &self.level_meters.reading(Some(kind)), METER_SIZE) {
+                        self.level_meters.reset_clip(Some(kind));
+                    }
+                    mixer_changed |= bus_controls(ui, self.mixer_settings.bus_mut(kind), true);
+                    ui.end_row();
+                }
+
+                ui.strong("マスター");
+                if widgets::level_meter(ui, &self.level_meters.reading(None), METER_SIZE) {
+                    self.level_meters.reset_clip(None);
+                }
+                mixer_changed |= bus_controls(ui, &mut self.mixer_settings.master, false);
+                ui.end_row();
+            });
+        });
+
+        // エフェクト
+        ui.collapsing("エフェクト", |ui| {
+            if self.effects_ui(ui) {
+                mixer_changed = true;
+                self.config_dirty = true;
+            }
+        });
+
+        // ダッキング
+        ui.collapsing("ダッキング", |ui| {
+            mixer_changed |= self.ducking_ui(ui);
+        });
+
+        // ラウドネス
+        ui.collapsing("ラウドネス正規化", |ui| {
+            mixer_changed |= self.loudness_ui(ui);
+        });
+
+        // 録音
+        ui.collapsing("録音", |ui| {
+            if self.recorder_ui(ui) {
+                self.config_dirty = true;
+            }
+        });
+
+        // 出力先
+        ui.collapsing("出力先", |ui| {
+            self.output_ui(ui);
+        });
+
+        // モニター
+        ui.collapsing("モニター", |ui| {
+            if self.monitor_ui(ui) {
+                mixer_changed = true;
+                self.config_dirty = true;
+            }
+        });
+
+        // マイク
+        ui.collapsing("マイク", |ui| {
+            self.mic_ui(ui);
         });
 
         // BGM設定
         ui.collapsing("BGM設定", |ui| {
             ui.horizontal(|ui| {
                 ui.label("BGM音量:");
-                ui.add(egui::Slider::new(&mut self.bgm_volume, 0.0..=1.0));
+                mixer_changed |= ui.add(egui::Slider::new(&mut self.mixer_settings.bus_mut(BusKind::Bgm).gain, 0.0..=1.0)).changed();
+            });
+            ui.horizontal(|ui| {
+                self.licenses_dirty |= ui.checkbox(&mut self.licenses.warn_on_stream_start, "配信開始時に権利を確認していない曲を警告").changed();
+                self.licenses_dirty |= ui.checkbox(&mut self.licenses.skip_when_live, "配信中は飛ばす").changed();
             });
 
+            let mut removed = None;
             for track in &mut self.bgm_tracks {
+                let mut state = self.engine.state();
+                let Some(player) = state.bgm.track_mut(track.id) else { continue };
+                let status = player.status();
+
                 ui.horizontal(|ui| {
-                    if track.is_playing {
+                    if status.state == PlaybackState::Playing {
                         if ui.button("⏸").clicked() {
-                            track.is_playing = false;
-                        }
-                    } else {
-                        if ui.button("▶").clicked() {
-                            track.is_playing = true;
+                            player.pause();
                         }
+                    } else if ui.button("▶").clicked() {
+                        player.play();
+                    }
+                    if ui.button("⏹").clicked() {
+                        player.stop();
+                    }
+                    ui.label(&track.name).on_hover_text(track_tags(&track.metadata));
+                    ui.label(egui::RichText::new(loudness_label(&self.loudness_results, &track.file_path)).small().weak());
+
+                    let duration = status.duration.unwrap_or(status.position).as_secs_f32();
+                    let mut position = status.position.as_secs_f32();
+                    let seek = ui.add(egui::Slider::new(&mut position, 0.0..=duration.max(0.1)).show_value(false));
+                    if seek.drag_released() || (seek.changed() && !seek.dragged()) {
+                        player.seek(Duration::from_secs_f32(position));
+                    }
+                    ui.label(format!("{} / {}", format_time(status.position), format_time(Duration::from_secs_f32(duration))));
+
+                    if ui.add(egui::Slider::new(&mut track.volume, 0.0..=1.0).text("音量")).changed() {
+                        player.set_volume(track.volume);
+                    }
+                    let mut license_status = self.licenses.status(&track.file_path);
+                    egui::ComboBox::from_id_source(("track_license", track.id))
+                        .selected_text(egui::RichText::new(license_status.label()).color(license_color(license_status)))
+                        .width(70.0)
+                        .show_ui(ui, |ui| {
+                            for option in LicenseStatus::ALL {
+                                ui.selectable_value(&mut license_status, option, option.label());
+                            }
+                        });
+                    if license_status != self.licenses.status(&track.file_path) {
+                        self.licenses.license_mut(&track.file_path).status = license_status;
+                        self.licenses_dirty = true;
+                    }
+                    let editing = self.editing_license == Some(track.id);
+                    if ui.selectable_label(editing, "権利情報").clicked() {
+                        self.editing_license = if editing { None } else { Some(track.id) };
+                    }
+                    if ui.button("削除").clicked() {
+                        removed = Some(track.id);
                     }
-                    ui.label(&track.name);
-                    ui.add(egui::Slider::new(&mut track.volume, 0.0..=1.0).text("音量"));
                 });
+                if self.editing_license == Some(track.id) {
+                    let license = self.licenses.license_mut(&track.file_path);
+                    ui.indent(("track_license_detail", track.id), |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("入手元:");
+                            self.licenses_dirty |= ui.text_edit_singleline(&mut license.source).changed();
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("メモ:");
+                            self.licenses_dirty |= ui.text_edit_multiline(&mut license.notes).changed();
+                        });
+                        if !track.metadata.license_url.is_empty() {
+                            ui.horizontal(|ui| {
+                                ui.label("タグのライセンス:");
+                                ui.hyperlink(&track.metadata.license_url);
+                            });
+                        }
+                    });
+                }
+                if let Some(error) = &status.error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
             }
 
-            if ui.button("BGMを追加").clicked() {
-                self.bgm_tracks.push(BGMTrack {
-                    name: "新しいBGM".to_string(),
-                    volume: 0.5,
-                    is_playing: false,
-                });
+            if let Some(id) = removed {
+                self.engine.state().bgm.remove(id);
+                self.bgm_tracks.retain(|track| track.id != id);
+                for playlist in &mut self.playlists {
+                    playlist.track_ids.retain(|&track| track != id);
+                }
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("ファイル:");
+                ui.text_edit_singleline(&mut self.new_track_path);
+                if ui.add_enabled(!self.new_track_path.trim().is_empty(), egui::Button::new("BGMを追加")).clicked() {
+                    self.add_track();
+                }
+            });
+            ui.label(egui::RichText::new("WAV / MP3 / OGG / FLAC に対応").small().weak());
+            if let Some(error) = &self.add_error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+        });
+
+        // プレイリスト
+        ui.collapsing("プレイリスト", |ui| {
+            self.playlist_ui(ui);
+        });
+
+        // 再生中の曲
+        ui.collapsing("再生中の曲", |ui| {
+            if self.now_playing_ui(ui) {
+                self.config_dirty = true;
             }
         });
 
@@ -69,28 +756,989 @@ impl AudioTab {
         ui.collapsing("効果音設定", |ui| {
             ui.horizontal(|ui| {
                 ui.label("効果音音量:");
-                ui.add(egui::Slider::new(&mut self.effects_volume, 0.0..=1.0));
+                mixer_changed |= ui.add(egui::Slider::new(&mut self.mixer_settings.bus_mut(BusKind::Effects).gain, 0.0..=1.0)).changed();
             });
+            if self.soundboard_ui(ui) {
+                self.soundboard_dirty = true;
+            }
+        });
 
-            for effect in &mut self.effects {
-                ui.horizontal(|ui| {
-                    if ui.button("▶").clicked() {
-                        // 効果音再生処理
-                    }
-                    ui.label(&effect.name);
-                    ui.add(egui::Slider::new(&mut effect.volume, 0.0..=1.0).text("音量"));
-                    ui.label("ホットキー:");
-                    ui.text_edit_singleline(&mut effect.hotkey);
-                });
+        // チャットで効果音
+        ui.collapsing("チャットで効果音", |ui| {
+            if self.chat_triggers_ui(ui) {
+                self.chat_triggers_dirty = true;
+            }
+        });
+
+        if mixer_changed {
+            self.sync_mixer();
+        }
+        let pointer_down = ui.ctx().input(|input| input.pointer.any_down());
+        if self.config_dirty && !pointer_down {
+            self.save_config();
+        }
+        if self.soundboard_dirty && !pointer_down {
+            self.save_soundboard();
+        }
+        if self.chat_triggers_dirty && !pointer_down {
+            self.save_chat_triggers();
+        }
+        if self.licenses_dirty && !pointer_down {
+            self.save_licenses();
+        }
+    }
+
+    /// ページごとの効果音のボタンと、選択した効果音の設定。変更があった場合は true を返す
+    fn soundboard_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        self.soundboard_page = self.soundboard_page.min(self.soundboard.pages.len().saturating_sub(1));
+
+        ui.horizontal(|ui| {
+            for (page, name) in self.soundboard.pages.iter().enumerate() {
+                ui.selectable_value(&mut self.soundboard_page, page, name);
+            }
+            if ui.button("＋").on_hover_text("ページを追加").clicked() {
+                self.soundboard.pages.push(format!("ページ{}", self.soundboard.pages.len() + 1));
+                self.soundboard_page = self.soundboard.pages.len() - 1;
+                changed = true;
             }
+        });
+        ui.horizontal(|ui| {
+            ui.label("ページ名:");
+            if let Some(name) = self.soundboard.pages.get_mut(self.soundboard_page) {
+                changed |= ui.add(egui::TextEdit::singleline(name).desired_width(100.0)).changed();
+            }
+            ui.label("列数:");
+            changed |= ui.add(egui::DragValue::new(&mut self.soundboard.columns).clamp_range(1..=12)).changed();
+            if ui.add_enabled(self.soundboard.pages.len() > 1, egui::Button::new("ページを削除")).clicked() {
+                self.soundboard.remove_page(self.soundboard_page);
+                self.soundboard_page = self.soundboard_page.saturating_sub(1);
+                changed = true;
+            }
+        });
 
+        // 再生中の効果音は枠で示す。右クリックで設定を開く
+        let playing: Vec<usize> = self.engine.state().effects.iter().filter(|voice| voice.is_playing()).map(|voice| voice.effect).collect();
+        let columns = self.soundboard.columns.max(1);
+        let mut play = None;
+        egui::Grid::new(("soundboard_grid", self.soundboard_page)).spacing([4.0, 4.0]).show(ui, |ui| {
+            let effects = self.soundboard.effects.iter().enumerate().filter(|(_, effect)| effect.page == self.soundboard_page);
+            for (count, (index, effect)) in effects.enumerate() {
+                let [r, g, b] = effect.color;
+                let mut button = egui::Button::new(egui::RichText::new(&effect.name).color(egui::Color32::WHITE))
+                    .fill(egui::Color32::from_rgb(r, g, b))
+                    .min_size(PAD_SIZE);
+                if playing.contains(&index) {
+                    button = button.stroke(egui::Stroke::new(2.0, egui::Color32::YELLOW));
+                }
+                let mut hover = effect.play_mode.label().to_string();
+                if !effect.hotkey.trim().is_empty() {
+                    hover = format!("{}（{}）", hover, effect.hotkey);
+                }
+                let response = ui.add(button).on_hover_text(hover);
+                if response.clicked() {
+                    play = Some(index);
+                }
+                if response.secondary_clicked() {
+                    self.editing_effect = Some(index);
+                }
+                if (count + 1) % columns == 0 {
+                    ui.end_row();
+                }
+            }
+        });
+        if let Some(index) = play {
+            self.play_effect(index);
+        }
+        if let Some(error) = &self.effect_error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+
+        ui.horizontal(|ui| {
             if ui.button("効果音を追加").clicked() {
-                self.effects.push(SoundEffect {
+                self.soundboard.effects.push(SoundEffect {
                     name: "新しい効果音".to_string(),
-                    volume: 0.5,
-                    hotkey: String::new(),
+                    page: self.soundboard_page,
+                    ..Default::default()
+                });
+                self.editing_effect = Some(self.soundboard.effects.len() - 1);
+                changed = true;
+            }
+            if ui.button("⏹ すべて停止").clicked() {
+                self.stop_effects();
+            }
+        });
+
+        if let Some(index) = self.editing_effect {
+            ui.separator();
+            changed |= self.effect_editor_ui(ui, index);
+        }
+        changed
+    }
+
+    /// 効果音 1 つ分の設定。変更があった場合は true を返す
+    fn effect_editor_ui(&mut self, ui: &mut egui::Ui, index: usize) -> bool {
+        let mut changed = false;
+        if let Some(recording) = self.recording_effect {
+            ui.label("割り当てるキーを押してください（Esc で取り消し）");
+            if let Some(captured) = hotkey::capture_accelerator(ui.ctx()) {
+                if let (Some(accelerator), Some(effect)) = (captured, self.soundboard.effects.get_mut(recording)) {
+                    effect.hotkey = accelerator.to_string();
+                    changed = true;
+                }
+                self.recording_effect = None;
+            }
+        }
+
+        let pages = &self.soundboard.pages;
+        let Some(effect) = self.soundboard.effects.get_mut(index) else {
+            self.editing_effect = None;
+            return changed;
+        };
+        let mut analyze = None;
+        let mut removed = false;
+        let mut closed = false;
+        egui::Grid::new("effect_editor").num_columns(2).show(ui, |ui| {
+            ui.label("名前:");
+            ui.horizontal(|ui| {
+                changed |= ui.add(egui::TextEdit::singleline(&mut effect.name).desired_width(120.0)).changed();
+                changed |= ui.color_edit_button_srgb(&mut effect.color).changed();
+            });
+            ui.end_row();
+
+            ui.label("ファイル:");
+            ui.horizontal(|ui| {
+                let response = ui.add(egui::TextEdit::singleline(&mut effect.file_path).desired_width(200.0));
+                changed |= response.changed();
+                if response.lost_focus() {
+                    analyze = Some(effect.file_path.clone());
+                }
+                ui.label(egui::RichText::new(loudness_label(&self.loudness_results, &effect.file_path)).small().weak());
+            });
+            ui.end_row();
+
+            ui.label("ページ:");
+            egui::ComboBox::from_id_source("effect_page")
+                .selected_text(pages.get(effect.page).map_or("", String::as_str))
+                .show_ui(ui, |ui| {
+                    for (page, name) in pages.iter().enumerate() {
+                        changed |= ui.selectable_value(&mut effect.page, page, name).changed();
+                    }
+                });
+            ui.end_row();
+
+            ui.label("再生モード:");
+            egui::ComboBox::from_id_source("effect_play_mode")
+                .selected_text(effect.play_mode.label())
+                .show_ui(ui, |ui| {
+                    for mode in PlayMode::ALL {
+                        changed |= ui.selectable_value(&mut effect.play_mode, mode, mode.label()).changed();
+                    }
+                });
+            ui.end_row();
+
+            ui.label("出力先:");
+            egui::ComboBox::from_id_source("effect_output")
+                .selected_text(effect.output.label())
+                .show_ui(ui, |ui| {
+                    for output in EffectOutput::ALL {
+                        changed |= ui.selectable_value(&mut effect.output, output, output.label()).changed();
+                    }
+                });
+            ui.end_row();
+
+            ui.label("トリム:");
+            ui.horizontal(|ui| {
+                ui.label("先頭");
+                changed |= ui.add(egui::DragValue::new(&mut effect.trim_start_ms).speed(10.0).suffix("ms")).changed();
+                ui.label("末尾");
+                changed |= ui.add(egui::DragValue::new(&mut effect.trim_end_ms).speed(10.0).suffix("ms")).changed();
+            });
+            ui.end_row();
+
+            ui.label("フェード:");
+            ui.horizontal(|ui| {
+                ui.label("イン");
+                changed |= ui.add(egui::DragValue::new(&mut effect.fade_in_ms).speed(10.0).suffix("ms").clamp_range(0..=10000)).changed();
+                ui.label("アウト");
+                changed |= ui.add(egui::DragValue::new(&mut effect.fade_out_ms).speed(10.0).suffix("ms").clamp_range(0..=10000)).changed();
+            });
+            ui.end_row();
+
+            ui.label("音量:");
+            changed |= ui.add(egui::Slider::new(&mut effect.volume, 0.0..=1.0)).changed();
+            ui.end_row();
+
+            ui.label("ホットキー:");
+            ui.horizontal(|ui| {
+                changed |= ui.add(egui::TextEdit::singleline(&mut effect.hotkey).desired_width(100.0)).changed();
+                let recording = self.recording_effect == Some(index);
+                if ui.selectable_label(recording, "記録").clicked() {
+                    self.recording_effect = if recording { None } else { Some(index) };
+                }
+            });
+            ui.end_row();
+        });
+        if !effect.hotkey.trim().is_empty() {
+            if let Err(e) = Accelerator::parse(&effect.hotkey) {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+        }
+        ui.horizontal(|ui| {
+            if ui.button("削除").clicked() {
+                removed = true;
+            }
+            if ui.button("閉じる").clicked() {
+                closed = true;
+            }
+        });
+
+        if let Some(path) = analyze {
+            self.request_loudness(&path);
+        }
+        if removed {
+            self.remove_effect(index);
+            changed = true;
+        } else if closed {
+            self.editing_effect = None;
+            self.recording_effect = None;
+        }
+        changed
+    }
+
+    /// コメントのキーワード・コマンド・スーパーチャットと効果音の対応。変更があった場合は true を返す
+    fn chat_triggers_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let settings = &mut self.chat_triggers;
+        let mut changed = ui.checkbox(&mut settings.enabled, "コメントで効果音を鳴らす").changed();
+        ui.horizontal(|ui| {
+            ui.label("全体のクールダウン:");
+            changed |= ui.add(egui::DragValue::new(&mut settings.global_cooldown_secs).suffix("秒").clamp_range(0..=3600)).changed();
+            ui.label("ユーザーごと:");
+            changed |= ui.add(egui::DragValue::new(&mut settings.user_cooldown_secs).suffix("秒").clamp_range(0..=3600)).changed();
+            ui.label("同時再生:");
+            changed |= ui.add(egui::DragValue::new(&mut settings.max_concurrent).clamp_range(1..=16)).changed();
+        });
+        changed |= ui.checkbox(&mut settings.super_chat_bypasses_cooldown, "スーパーチャットはクールダウンを無視する").changed();
+
+        let effects = &self.soundboard.effects;
+        let mut removed = None;
+        egui::Grid::new("chat_trigger_grid").striped(true).show(ui, |ui| {
+            for (index, trigger) in settings.triggers.iter_mut().enumerate() {
+                changed |= ui.checkbox(&mut trigger.enabled, "").changed();
+                egui::ComboBox::from_id_source(("chat_trigger_kind", index))
+                    .selected_text(trigger.kind.label())
+                    .show_ui(ui, |ui| {
+                        for kind in TriggerKind::ALL {
+                            changed |= ui.selectable_value(&mut trigger.kind, kind, kind.label()).changed();
+                        }
+                    });
+                if trigger.kind == TriggerKind::SuperChat {
+                    ui.horizontal(|ui| {
+                        changed |= ui.add(egui::DragValue::new(&mut trigger.min_amount).prefix("≥ ").clamp_range(0.0..=1_000_000.0)).changed();
+                        changed |= ui.add(egui::TextEdit::singleline(&mut trigger.currency).hint_text("通貨").desired_width(50.0)).changed();
+                    });
+                } else {
+                    let hint = if trigger.kind == TriggerKind::Command { "!コマンド" } else { "キーワード" };
+                    changed |= ui.add(egui::TextEdit::singleline(&mut trigger.pattern).hint_text(hint).desired_width(120.0)).changed();
+                }
+                egui::ComboBox::from_id_source(("chat_trigger_effect", index))
+                    .selected_text(effects.get(trigger.effect).map_or("（なし）", |effect| effect.name.as_str()))
+                    .show_ui(ui, |ui| {
+                        for (effect_index, effect) in effects.iter().enumerate() {
+                            changed |= ui.selectable_value(&mut trigger.effect, effect_index, &effect.name).changed();
+                        }
+                    });
+                changed |= ui.checkbox(&mut trigger.members_only, "メンバー限定").changed();
+                if ui.small_button("✕").clicked() {
+                    removed = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(index) = removed {
+            settings.triggers.remove(index);
+            changed = true;
+        }
+        if ui.add_enabled(!effects.is_empty(), egui::Button::new("トリガーを追加")).clicked() {
+            settings.triggers.push(ChatTrigger::default());
+            changed = true;
+        }
+
+        ui.collapsing("記録", |ui| {
+            ui.label(egui::RichText::new(format!("すべての記録は {} に保存されます", TRIGGER_LOG_PATH)).small().weak());
+            egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                for entry in self.chat_trigger_state.recent().iter().rev() {
+                    // RFC 3339 の時刻部分だけ表示する
+                    let time = entry.time.get(11..19).unwrap_or(&entry.time);
+                    let text = format!("{} {} 「{}」 → {} ({})", time, entry.author_name, entry.text, entry.effect, entry.outcome.label());
+                    if entry.outcome == TriggerOutcome::Played {
+                        ui.label(text);
+                    } else {
+                        ui.label(egui::RichText::new(text).weak());
+                    }
+                }
+            });
+        });
+        changed
+    }
+
+    /// 再生中の曲とテキストファイルへの書き出し。変更があった場合は true を返す
+    fn now_playing_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let current = self.now_playing.lock().unwrap().clone();
+        match &current {
+            Some(track) => {
+                ui.label(format!("♪ {}", track.label()));
+                if !track.license_url.is_empty() {
+                    ui.hyperlink(&track.license_url);
+                }
+            }
+            None => {
+                ui.label("停止中");
+            }
+        }
+
+        let settings = &mut self.now_playing_settings;
+        let mut changed = ui.checkbox(&mut settings.write_file, "テキストファイルに書き出す").changed();
+        ui.add_enabled_ui(settings.write_file, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("ファイル:");
+                changed |= ui.text_edit_singleline(&mut settings.file_path).changed();
+            });
+            ui.horizontal(|ui| {
+                ui.label("書式:");
+                changed |= ui.text_edit_singleline(&mut settings.template).changed();
+            });
+            ui.label(egui::RichText::new(format!("出力: {}", now_playing::expand_variables(&settings.template, current.as_ref()))).small().weak());
+        });
+        let variables: Vec<String> = now_playing::VARIABLES.iter().map(|(name, label)| format!("{} {}", name, label)).collect();
+        ui.label(egui::RichText::new(format!("バナー・テキストで使える変数: {}", variables.join("、"))).small().weak());
+
+        // 書式を変えたらすぐに書き直す
+        if changed && settings.write_file {
+            if let Err(e) = settings.write(current.as_ref()) {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+        }
+        changed
+    }
+
+    fn playlist_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let selected = self.playlists.get(self.selected_playlist).map(|p| p.name.clone()).unwrap_or_default();
+            egui::ComboBox::from_id_source("playlist_select")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for (index, playlist) in self.playlists.iter().enumerate() {
+                        ui.selectable_value(&mut self.selected_playlist, index, &playlist.name);
+                    }
+                });
+            if ui.button("新規").clicked() {
+                self.playlists.push(Playlist::new(&format!("プレイリスト{}", self.playlists.len() + 1)));
+                self.selected_playlist = self.playlists.len() - 1;
+            }
+            if ui.add_enabled(self.playlists.len() > 1, egui::Button::new("削除")).clicked() {
+                self.playlists.remove(self.selected_playlist);
+                self.selected_playlist = self.selected_playlist.min(self.playlists.len() - 1);
+            }
+        });
+
+        let current = self.engine.state().bgm.playlist_current();
+        let Some(playlist) = self.playlists.get_mut(self.selected_playlist) else { return };
+        ui.horizontal(|ui| {
+            ui.label("名前:");
+            ui.text_edit_singleline(&mut playlist.name);
+        });
+
+        let options = &mut playlist.options;
+        let mut options_changed = false;
+        ui.horizontal(|ui| {
+            options_changed |= ui.checkbox(&mut options.shuffle, "シャッフル").changed();
+            egui::ComboBox::from_id_source("playlist_repeat")
+                .selected_text(options.repeat.label())
+                .show_ui(ui, |ui| {
+                    for mode in [RepeatMode::Off, RepeatMode::One, RepeatMode::All] {
+                        options_changed |= ui.selectable_value(&mut options.repeat, mode, mode.label()).changed();
+                    }
+                });
+            egui::ComboBox::from_id_source("playlist_transition")
+                .selected_text(options.transition.label())
+                .show_ui(ui, |ui| {
+                    for mode in [TransitionMode::Gapless, TransitionMode::Crossfade] {
+                        options_changed |= ui.selectable_value(&mut options.transition, mode, mode.label()).changed();
+                    }
+                });
+            if options.transition == TransitionMode::Crossfade {
+                let mut seconds = options.crossfade.as_secs_f32();
+                if ui.add(egui::DragValue::new(&mut seconds).speed(0.1).suffix("秒").clamp_range(0.5..=15.0)).changed() {
+                    options.crossfade = Duration::from_secs_f32(seconds);
+                    options_changed = true;
+                }
+            }
+        });
+        if options_changed {
+            self.engine.state().bgm.set_playlist_options(options.clone());
+        }
+
+        // ☰ をドラッグして並べ替える
+        let mut row_rects = Vec::new();
+        let mut removed = None;
+        for (index, id) in playlist.track_ids.iter().enumerate() {
+            let name = self.bgm_tracks.iter().find(|track| track.id == *id).map_or("", |track| track.name.as_str());
+            let row = ui.horizontal(|ui| {
+                let handle = ui.add(egui::Label::new("☰").sense(egui::Sense::drag()));
+                if handle.drag_started() {
+                    self.dragging_track = Some(index);
+                }
+                let label = format!("{}. {}", index + 1, name);
+                if current == Some(*id) {
+                    ui.strong(format!("▶ {}", label));
+                } else {
+                    ui.label(label);
+                }
+                if ui.small_button("✕").clicked() {
+                    removed = Some(index);
+                }
+            });
+            row_rects.push(row.response.rect);
+        }
+
+        if let Some(from) = self.dragging_track {
+            let pointer = ui.ctx().pointer_interact_pos();
+            if ui.ctx().input(|input| input.pointer.any_released()) {
+                let to = pointer.and_then(|pos| row_rects.iter().position(|rect| pos.y <= rect.bottom()))
+                    .unwrap_or(row_rects.len().saturating_sub(1));
+                playlist.move_track(from, to);
+                self.dragging_track = None;
+            } else if let (Some(pos), Some(rect)) = (pointer, row_rects.get(from)) {
+                ui.painter().hline(rect.x_range(), pos.y, egui::Stroke::new(1.0, egui::Color32::LIGHT_BLUE));
+            }
+        }
+        if let Some(index) = removed {
+            playlist.track_ids.remove(index);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.add_enabled(!playlist.track_ids.is_empty(), egui::Button::new("▶ 再生")).clicked() {
+                self.engine.state().bgm.start_playlist(&playlist.track_ids, playlist.options.clone(), 0);
+            }
+            if ui.add_enabled(current.is_some(), egui::Button::new("⏭ 次へ")).clicked() {
+                self.engine.state().bgm.skip();
+            }
+            if ui.add_enabled(current.is_some(), egui::Button::new("⏹ 停止")).clicked() {
+                self.engine.state().bgm.stop_playlist();
+            }
+        });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("フォルダー / M3U:");
+            ui.text_edit_singleline(&mut self.playlist_import_path);
+            if ui.add_enabled(!self.playlist_import_path.trim().is_empty(), egui::Button::new("追加")).clicked() {
+                self.import_playlist();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("BGM から追加:");
+            let playlist = &mut self.playlists[self.selected_playlist];
+            egui::ComboBox::from_id_source("playlist_add_track")
+                .selected_text("曲を選択")
+                .show_ui(ui, |ui| {
+                    for track in &self.bgm_tracks {
+                        if ui.selectable_label(false, &track.name).clicked() {
+                            playlist.track_ids.push(track.id);
+                        }
+                    }
+                });
+        });
+        if let Some(error) = &self.playlist_error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+    }
+
+    /// 選択したバスのエフェクトチェーン。変更があった場合は true を返す
+    fn effects_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("バス:");
+            egui::ComboBox::from_id_source("effects_bus")
+                .selected_text(self.effects_bus.label())
+                .show_ui(ui, |ui| {
+                    for kind in BusKind::ALL {
+                        ui.selectable_value(&mut self.effects_bus, kind, kind.label());
+                    }
+                });
+            let chain = self.mixer_settings.effects_mut(self.effects_bus);
+            changed |= ui.toggle_value(&mut chain.bypass, "バイパス").on_hover_text("エフェクトを外して原音と聴き比べる").changed();
+            if ui.button("リセット").clicked() {
+                *chain = EffectsChainSettings::default();
+                changed = true;
+            }
+        });
+
+        let chain = self.mixer_settings.effects_mut(self.effects_bus);
+        ui.add_enabled_ui(!chain.bypass, |ui| {
+            changed |= ui.checkbox(&mut chain.eq.enabled, "パラメトリック EQ").changed();
+            let mut removed = None;
+            egui::Grid::new("eq_grid").striped(true).show(ui, |ui| {
+                for (index, band) in chain.eq.bands.iter_mut().enumerate() {
+                    changed |= ui.checkbox(&mut band.enabled, format!("{}", index + 1)).changed();
+                    egui::ComboBox::from_id_source(("eq_band_kind", index))
+                        .selected_text(band.kind.label())
+                        .show_ui(ui, |ui| {
+                            for kind in EqBandKind::ALL {
+                                changed |= ui.selectable_value(&mut band.kind, kind, kind.label()).changed();
+                            }
+                        });
+                    // 周波数は値に比例した速さで動かす
+                    let speed = band.frequency_hz * 0.01;
+                    changed |= ui.add(egui::DragValue::new(&mut band.frequency_hz).speed(speed).suffix("Hz").clamp_range(20.0..=20000.0)).changed();
+                    if band.kind.has_gain() {
+                        changed |= db_drag(ui, &mut band.gain_db, "", -18.0..=18.0);
+                    } else {
+                        ui.label("");
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Q");
+                        changed |= ui.add(egui::DragValue::new(&mut band.q).speed(0.01).clamp_range(0.1..=10.0)).changed();
+                    });
+                    if ui.small_button("✕").clicked() {
+                        removed = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(index) = removed {
+                chain.eq.bands.remove(index);
+                changed = true;
+            }
+            if ui.add_enabled(chain.eq.bands.len() < MAX_EQ_BANDS, egui::Button::new("帯域を追加")).clicked() {
+                chain.eq.bands.push(EqBand::new(EqBandKind::Peaking, 1000.0));
+                changed = true;
+            }
+
+            ui.separator();
+            changed |= ui.checkbox(&mut chain.delay.enabled, "ディレイ").changed();
+            ui.horizontal(|ui| {
+                changed |= ms_drag(ui, &mut chain.delay.time_ms, "時間", 1.0..=MAX_DELAY_MS);
+                changed |= ui.add(egui::Slider::new(&mut chain.delay.feedback, 0.0..=0.95).text("フィードバック")).changed();
+                changed |= ui.add(egui::Slider::new(&mut chain.delay.mix, 0.0..=1.0).text("ミックス")).changed();
+            });
+
+            ui.separator();
+            changed |= ui.checkbox(&mut chain.reverb.enabled, "リバーブ").changed();
+            ui.horizontal(|ui| {
+                changed |= ui.add(egui::Slider::new(&mut chain.reverb.room_size, 0.0..=1.0).text("広さ")).changed();
+                changed |= ui.add(egui::Slider::new(&mut chain.reverb.damping, 0.0..=1.0).text("高域の減衰")).changed();
+                changed |= ui.add(egui::Slider::new(&mut chain.reverb.mix, 0.0..=1.0).text("ミックス")).changed();
+            });
+        });
+        changed
+    }
+
+    /// 変更があった場合は true を返す
+    fn ducking_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let status = self.engine.state().mixer.ducking();
+        if status.reduction_db > 0.1 {
+            let trigger = status.trigger.map_or("解除中", |kind| kind.label());
+            ui.colored_label(egui::Color32::YELLOW, format!("BGM を {:.1}dB 下げています（{}）", status.reduction_db, trigger));
+        } else {
+            ui.label("BGM は通常の音量です");
+        }
+
+        let settings = &mut self.mixer_settings.ducking;
+        let mut changed = ui.checkbox(&mut settings.enabled, "マイク・読み上げの間は BGM を下げる").changed();
+        ui.horizontal(|ui| {
+            changed |= ui.checkbox(&mut settings.on_mic, "マイク").changed();
+            changed |= db_drag(ui, &mut settings.threshold_db, "しきい値", -70.0..=0.0);
+            changed |= ui.checkbox(&mut settings.on_tts, "読み上げ").changed();
+        });
+        ui.horizontal(|ui| {
+            changed |= db_drag(ui, &mut settings.amount_db, "下げる量", 0.0..=40.0);
+            changed |= ms_drag(ui, &mut settings.attack_ms, "アタック", 1.0..=1000.0);
+            changed |= ms_drag(ui, &mut settings.hold_ms, "ホールド", 0.0..=3000.0);
+            changed |= ms_drag(ui, &mut settings.release_ms, "リリース", 10.0..=5000.0);
+        });
+        changed
+    }
+
+    /// ミキサーの設定に変更があった場合は true を返す
+    fn loudness_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let settings = &mut self.loudness_settings;
+        let mut normalization_changed = ui.checkbox(&mut settings.enabled, "BGM・効果音の音量をそろえる").changed();
+        ui.horizontal(|ui| {
+            ui.label("目標ラウドネス:");
+            normalization_changed |= ui.add(egui::DragValue::new(&mut settings.target_lufs).speed(0.5).suffix(" LUFS").clamp_range(-36.0..=-8.0)).changed();
+        });
+        if normalization_changed {
+            self.apply_normalization();
+            self.config_dirty = true;
+        }
+
+        ui.separator();
+        let limiter = &mut self.mixer_settings.master_limiter;
+        let mut changed = ui.checkbox(&mut limiter.enabled, "マスターのトゥルーピークリミッター").changed();
+        ui.horizontal(|ui| {
+            ui.label("上限:");
+            changed |= ui.add(egui::DragValue::new(&mut limiter.ceiling_db).speed(0.1).suffix(" dBTP").clamp_range(-6.0..=0.0)).changed();
+            changed |= ms_drag(ui, &mut limiter.release_ms, "リリース", 10.0..=1000.0);
+        });
+        let reduction = self.engine.state().mixer.limiter_reduction_db();
+        ui.label(format!("リダクション: {:.1}dB", reduction));
+        if changed {
+            self.config_dirty = true;
+        }
+        changed
+    }
+
+    /// 録音の設定に変更があった場合は true を返す
+    fn recorder_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.horizontal(|ui| {
+            if self.recorder.is_recording() {
+                if ui.button("⏹ 録音停止").clicked() {
+                    self.recorder.stop();
+                }
+                ui.colored_label(egui::Color32::RED, format!("● 録音中 {}", format_time(self.recorder.elapsed())));
+            } else if ui.button("⏺ 録音開始").clicked() {
+                self.recorder_error = self.recorder.start(&self.engine, &self.recorder_settings).err();
+            }
+        });
+        let status = self.recorder.status();
+        if self.recorder.is_recording() {
+            for file in &status.files {
+                ui.label(egui::RichText::new(file).small().weak());
+            }
+        }
+        if let Some(error) = self.recorder_error.as_ref().or(status.error.as_ref()) {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+
+        let settings = &mut self.recorder_settings;
+        let mut changed = false;
+        ui.add_enabled_ui(!self.recorder.is_recording(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("形式:");
+                for format in [RecordingFormat::Wav, RecordingFormat::Flac] {
+                    changed |= ui.radio_value(&mut settings.format, format, format.label()).changed();
+                }
+                for bits in [16, 24] {
+                    changed |= ui.radio_value(&mut settings.bits_per_sample, bits, format!("{}bit", bits)).changed();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("保存先:");
+                changed |= ui.text_edit_singleline(&mut settings.directory).changed();
+            });
+            ui.horizontal(|ui| {
+                ui.label("ファイル名:");
+                changed |= ui.text_edit_singleline(&mut settings.file_template).changed();
+            });
+            ui.label(egui::RichText::new("{date}・{time}・{source}（master / bgm など）を置き換えます").small().weak());
+            ui.horizontal(|ui| {
+                ui.label("バスごとにも書き出す:");
+                for (kind, enabled) in BusKind::ALL.iter().zip(settings.buses.iter_mut()) {
+                    changed |= ui.checkbox(enabled, kind.label()).changed();
+                }
+            });
+        });
+        changed
+    }
+
+    fn mic_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("入力デバイス:");
+            egui::ComboBox::from_id_source("mic_device")
+                .selected_text(self.mic_device.as_deref().unwrap_or("既定のデバイス"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.mic_device, None, "既定のデバイス");
+                    for name in &self.input_devices {
+                        ui.selectable_value(&mut self.mic_device, Some(name.clone()), name);
+                    }
+                });
+            if ui.button("再取得").clicked() {
+                self.input_devices = mic_input::input_device_names();
+            }
+            if self.mic.is_running() {
+                if ui.button("停止").clicked() {
+                    self.mic.stop();
+                }
+            } else if ui.button("開始").clicked() {
+                self.mic.start(self.mic_device.clone(), self.engine.inputs().mic);
+            }
+        });
+
+        let mut control_changed = false;
+        ui.horizontal(|ui| {
+            ui.label("ミュートの操作:");
+            egui::ComboBox::from_id_source("mic_mode")
+                .selected_text(self.mic_control.mode.label())
+                .show_ui(ui, |ui| {
+                    for mode in MicMode::ALL {
+                        control_changed |= ui.selectable_value(&mut self.mic_control.mode, mode, mode.label()).changed();
+                    }
+                });
+            match self.mic_control.mode {
+                MicMode::ToggleMute => {
+                    let muted = self.mixer_settings.bus(BusKind::Mic).mute;
+                    if ui.selectable_label(muted, if muted { "ミュート中" } else { "ミュート" }).clicked() {
+                        self.toggle_mic_mute();
+                    }
+                }
+                MicMode::PushToTalk => {
+                    ui.label("離してからミュートまで");
+                    control_changed |= ui
+                        .add(egui::DragValue::new(&mut self.mic_control.release_delay_ms).speed(10.0).suffix("ms").clamp_range(0..=2000))
+                        .changed();
+                }
+            }
+            ui.label(egui::RichText::new("キーはホットキー設定タブで割り当てます").small().weak());
+        });
+        ui.horizontal(|ui| {
+            control_changed |= ui.checkbox(&mut self.mic_control.show_overlay, "ミュート中は配信画面に表示").changed();
+            ui.add_enabled_ui(self.mic_control.show_overlay, |ui| {
+                control_changed |= ui.text_edit_singleline(&mut self.mic_control.overlay_text).changed();
+            });
+        });
+        if control_changed {
+            self.config_dirty = true;
+        }
+
+        let status = self.mic.status();
+        if let Some(error) = &status.error {
+            ui.colored_label(egui::Color32::RED, error);
+        } else if self.mic.is_running() {
+            let (gate_open, reduction) = self.mic.dynamics();
+            ui.label(format!("{} ({})", status.device, status.format));
+            ui.horizontal(|ui| {
+                ui.add(egui::ProgressBar::new(status.peak.min(1.0)).desired_width(200.0));
+                ui.label(if gate_open { "ゲート: 開" } else { "ゲート: 閉" });
+                ui.label(format!("リダクション: {:.1}dB", reduction));
+            });
+        }
+
+        let settings = &mut self.mic_settings;
+        let mut changed = false;
+        egui::Grid::new("mic_chain_grid").striped(true).show(ui, |ui| {
+            changed |= ui.checkbox(&mut settings.gate.enabled, "ノイズゲート").changed();
+            ui.horizontal(|ui| {
+                changed |= db_drag(ui, &mut settings.gate.threshold_db, "しきい値", -90.0..=0.0);
+                changed |= ms_drag(ui, &mut settings.gate.attack_ms, "アタック", 0.1..=100.0);
+                changed |= ms_drag(ui, &mut settings.gate.hold_ms, "ホールド", 0.0..=1000.0);
+                changed |= ms_drag(ui, &mut settings.gate.release_ms, "リリース", 1.0..=2000.0);
+            });
+            ui.end_row();
+
+            changed |= ui.checkbox(&mut settings.high_pass.enabled, "ハイパス").changed();
+            ui.horizontal(|ui| {
+                ui.label("カットオフ");
+                changed |= ui.add(egui::DragValue::new(&mut settings.high_pass.cutoff_hz).speed(1.0).suffix("Hz").clamp_range(20.0..=400.0)).changed();
+            });
+            ui.end_row();
+
+            changed |= ui.checkbox(&mut settings.compressor.enabled, "コンプレッサー").changed();
+            ui.horizontal(|ui| {
+                changed |= db_drag(ui, &mut settings.compressor.threshold_db, "しきい値", -60.0..=0.0);
+                ui.label("レシオ");
+                changed |= ui.add(egui::DragValue::new(&mut settings.compressor.ratio).speed(0.1).suffix(":1").clamp_range(1.0..=20.0)).changed();
+                changed |= ms_drag(ui, &mut settings.compressor.attack_ms, "アタック", 0.1..=200.0);
+                changed |= ms_drag(ui, &mut settings.compressor.release_ms, "リリース", 1.0..=2000.0);
+                changed |= db_drag(ui, &mut settings.compressor.makeup_db, "メイクアップ", 0.0..=24.0);
+            });
+            ui.end_row();
+
+            changed |= ui.checkbox(&mut settings.limiter.enabled, "リミッター").changed();
+            ui.horizontal(|ui| {
+                changed |= db_drag(ui, &mut settings.limiter.ceiling_db, "上限", -12.0..=0.0);
+                changed |= ms_drag(ui, &mut settings.limiter.release_ms, "リリース", 1.0..=1000.0);
+            });
+            ui.end_row();
+
+            ui.label("ゲイン");
+            changed |= db_drag(ui, &mut settings.gain_db, "", -24.0..=24.0);
+            ui.end_row();
+        });
+        if changed {
+            self.mic.set_settings(self.mic_settings.clone());
+            self.config_dirty = true;
+        }
+    }
+
+    fn output_ui(&mut self, ui: &mut egui::Ui) {
+        let status = self.engine.status();
+        ui.label(format!("現在の出力: {}", status.output));
+        if let Some(error) = &status.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("出力先:");
+            egui::ComboBox::from_id_source("audio_output_target")
+                .selected_text(target_label(&self.output_target))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.output_target, OutputTarget::Device(None), "既定のデバイス");
+                    for name in &self.output_devices {
+                        ui.selectable_value(&mut self.output_target, OutputTarget::Device(Some(name.clone())), name);
+                    }
+                    ui.selectable_value(&mut self.output_target, OutputTarget::Null, "なし（ヘッドレス）");
+                    if !matches!(self.output_target, OutputTarget::File(_)) {
+                        ui.selectable_value(&mut self.output_target, OutputTarget::File("output.wav".to_string()), "WAV ファイル");
+                    }
                 });
+            if ui.button("再取得").clicked() {
+                self.output_devices = audio_output::output_device_names();
+            }
+        });
+
+        if let OutputTarget::File(path) = &mut self.output_target {
+            ui.horizontal(|ui| {
+                ui.label("ファイル:");
+                ui.text_edit_singleline(path);
+            });
+        }
+
+        if ui.button("適用").clicked() {
+            self.engine.start(self.output_target.clone());
+        }
+    }
+
+    /// モニターの出力先とバスの送り先。変更があった場合は true を返す
+    fn monitor_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        if self.monitor_settings.enabled {
+            let status = self.engine.monitor_status();
+            ui.label(format!("現在のモニター: {}", status.output));
+            if let Some(error) = &status.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+        }
+
+        let monitor = &mut self.monitor_settings;
+        ui.horizontal(|ui| {
+            changed |= ui.checkbox(&mut monitor.enabled, "モニターを使う").changed();
+            egui::ComboBox::from_id_source("audio_monitor_target")
+                .selected_text(target_label(&monitor.target))
+                .show_ui(ui, |ui| {
+                    changed |= ui.selectable_value(&mut monitor.target, OutputTarget::Device(None), "既定のデバイス").changed();
+                    for name in &self.output_devices {
+                        changed |= ui.selectable_value(&mut monitor.target, OutputTarget::Device(Some(name.clone())), name).changed();
+                    }
+                    changed |= ui.selectable_value(&mut monitor.target, OutputTarget::Null, "なし（ヘッドレス）").changed();
+                    if !matches!(monitor.target, OutputTarget::File(_)) {
+                        changed |= ui.selectable_value(&mut monitor.target, OutputTarget::File("monitor.wav".to_string()), "WAV ファイル").changed();
+                    }
+                });
+        });
+        if let OutputTarget::File(path) = &mut monitor.target {
+            ui.horizontal(|ui| {
+                ui.label("ファイル:");
+                changed |= ui.text_edit_singleline(path).changed();
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.label("遅延:");
+            changed |= ui.add(egui::DragValue::new(&mut monitor.latency_ms).suffix("ms").clamp_range(10..=500))
+                .on_hover_text("短いほど遅れは小さくなるが、途切れやすくなる")
+                .changed();
+            ui.label("音量:");
+            changed |= ui.add(egui::Slider::new(&mut self.mixer_settings.routing.monitor_gain, 0.0..=2.0)).changed();
+        });
+        if ui.button("適用").clicked() {
+            self.apply_monitor();
+        }
+
+        ui.label("送り先:");
+        egui::Grid::new("routing_grid").striped(true).show(ui, |ui| {
+            ui.strong("バス");
+            ui.strong("配信");
+            ui.strong("モニター");
+            ui.end_row();
+            let routing = &mut self.mixer_settings.routing;
+            for kind in BusKind::ALL {
+                ui.label(kind.label());
+                changed |= ui.checkbox(routing.stream_mut(kind), "").changed();
+                changed |= ui.checkbox(routing.monitor_mut(kind), "").changed();
+                ui.end_row();
             }
         });
+        ui.label(egui::RichText::new("マイクをモニターに送ると自分の声が遅れて聞こえます").small().weak());
+        changed
+    }
+
+    /// モニターの設定に合わせて出力を開き直す
+    fn apply_monitor(&mut self) {
+        if self.monitor_settings.enabled {
+            self.engine.start_monitor(&self.monitor_settings);
+        } else {
+            self.engine.stop_monitor();
+        }
     }
-} 
\ No newline at end of file
+}
+
+/// バスの音量・パン・ミュート・ソロ。変更があった場合は true を返す
+fn bus_controls(ui: &mut egui::Ui, bus: &mut BusSettings, show_solo: bool) -> bool {
+    let mut changed = ui.add(egui::Slider::new(&mut bus.gain, 0.0..=2.0)).changed();
+    changed |= ui.add(egui::Slider::new(&mut bus.pan, -1.0..=1.0).show_value(false)).changed();
+    ui.horizontal(|ui| {
+        changed |= ui.toggle_value(&mut bus.mute, "M").on_hover_text("ミュート").changed();
+        if show_solo {
+            changed |= ui.toggle_value(&mut bus.solo, "S").on_hover_text("ソロ").changed();
+        }
+    });
+    changed
+}
+
+fn db_drag(ui: &mut egui::Ui, value: &mut f32, label: &str, range: std::ops::RangeInclusive<f32>) -> bool {
+    if !label.is_empty() {
+        ui.label(label);
+    }
+    ui.add(egui::DragValue::new(value).speed(0.5).suffix("dB").clamp_range(range)).changed()
+}
+
+fn ms_drag(ui: &mut egui::Ui, value: &mut f32, label: &str, range: std::ops::RangeInclusive<f32>) -> bool {
+    ui.label(label);
+    ui.add(egui::DragValue::new(value).speed(1.0).suffix("ms").clamp_range(range)).changed()
+}
+
+fn license_color(status: LicenseStatus) -> egui::Color32 {
+    match status {
+        LicenseStatus::Safe => egui::Color32::from_rgb(80, 200, 120),
+        LicenseStatus::Unknown => egui::Color32::from_rgb(230, 190, 60),
+        LicenseStatus::Unsafe => egui::Color32::RED,
+    }
+}
+
+/// BGM の一覧で表示するタグ
+fn track_tags(metadata: &TrackMetadata) -> String {
+    let fields = [("タイトル", &metadata.title), ("アーティスト", &metadata.artist), ("アルバム", &metadata.album), ("ライセンス", &metadata.license_url)];
+    let lines: Vec<String> = fields
+        .iter()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(label, value)| format!("{}: {}", label, value))
+        .collect();
+    if lines.is_empty() { "タグなし".to_string() } else { lines.join("\n") }
+}
+
+fn loudness_label(results: &HashMap<String, Option<Result<LoudnessInfo, String>>>, path: &str) -> String {
+    match results.get(path.trim()) {
+        None => String::new(),
+        Some(None) => "解析中…".to_string(),
+        Some(Some(Ok(info))) => match info.integrated_lufs {
+            Some(lufs) => format!("{:.1} LUFS", lufs),
+            None => "無音".to_string(),
+        },
+        Some(Some(Err(_))) => "解析失敗".to_string(),
+    }
+}
+
+fn target_label(target: &OutputTarget) -> String {
+    match target {
+        OutputTarget::Device(None) => "既定のデバイス".to_string(),
+        OutputTarget::Device(Some(name)) => name.clone(),
+        OutputTarget::Null => "なし（ヘッドレス）".to_string(),
+        OutputTarget::File(_) => "WAV ファイル".to_string(),
+    }
+}
+
+fn format_time(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
@@ -1,9 +1,12 @@
 use eframe::egui;
+use crate::models::now_playing::{self, NowPlayingFeed};
 
 #[derive(Default)]
 pub struct BannerTab {
     pub banners: Vec<Banner>,
     pub default_duration: u32,
+    /// バナーの文字の `{title}` などを置き換える再生中の曲
+    now_playing: NowPlayingFeed,
 }
 
 #[derive(Default)]
@@ -23,6 +26,13 @@ pub enum BannerPosition {
 }
 
 impl BannerTab {
+    pub fn new(now_playing: NowPlayingFeed) -> Self {
+        Self {
+            now_playing,
+            ..Default::default()
+        }
+    }
+
     pub fn ui(&mut self, ui: &mut egui::Ui) {
         ui.heading("バナー設定");
 
@@ -34,12 +44,20 @@ impl BannerTab {
                 .clamp_range(1..=300));
         });
 
+        let variables: Vec<&str> = now_playing::VARIABLES.iter().map(|(name, _)| *name).collect();
+        ui.label(egui::RichText::new(format!("使える変数: {}", variables.join(" "))).small().weak());
+
+        let current = self.now_playing.lock().unwrap().clone();
         for banner in &mut self.banners {
             ui.group(|ui| {
                 ui.horizontal(|ui| {
                     ui.checkbox(&mut banner.enabled, "");
                     ui.text_edit_multiline(&mut banner.text);
                 });
+                if banner.text.contains('{') {
+                    let preview = now_playing::expand_variables(&banner.text, current.as_ref());
+                    ui.label(egui::RichText::new(format!("表示: {}", preview)).small().weak());
+                }
 
                 ui.horizontal(|ui| {
                     ui.label("表示位置:");
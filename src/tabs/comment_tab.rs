@@ -1,13 +1,73 @@
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 use eframe::egui;
+use log::{error, warn};
+use crate::models::audio_output;
+use crate::models::chat_replay::{ChatReplay, ChatReplaySettings, ReplayMode};
+use crate::models::chat_source::{ChatSource, SourceState};
+use crate::models::comment::{
+    ChatMessage, ChatReceiver, ChatSender, CommentFeed, ReceivedComment, ViewerBadge,
+};
+use crate::models::mixer::InputQueue;
+use crate::models::pronunciation::{DictionaryEntry, PronunciationDictionary, SyncReport, PRONUNCIATION_PATH};
+use crate::models::settings_store;
+use crate::models::spam_filter::{SpamDetector, SpamSettings};
+use crate::models::twitch_chat::{TwitchChatSettings, TwitchChatSource};
+use crate::models::youtube_chat::{YouTubeChatSettings, YouTubeChatSource};
+use crate::models::tts::{
+    HealthStatus, SpeakerStyle, SpeechRequest, TtsEngineSettings, TtsPlayer, VoicevoxClient, DEFAULT_SPEAKER_ID,
+};
+use crate::models::tts_queue::{ApprovalQueue, ApprovalSettings};
+use crate::models::viewer_stats::{ViewerDatabase, ViewerVisit};
+use crate::models::voice_assignment::{UserVoice, VoiceAssignments, VOICE_ASSIGNMENTS_PATH};
+
+/// コメント欄に保持する最大件数
+const MAX_COMMENTS: usize = 200;
 
-#[derive(Default)]
 #[allow(dead_code)]
 pub struct CommentTab {
     pub filter_settings: CommentFilter,
     pub voice_settings: VoiceSettings,
     pub display_settings: CommentDisplaySettings,
+    pub viewer_settings: ViewerStatsSettings,
+    pub replay_settings: ChatReplaySettings,
+    pub source_settings: ChatSourceSettings,
+    pub voice_assignments: VoiceAssignments,
+    pub engine_settings: TtsEngineSettings,
+    pub approval_settings: ApprovalSettings,
+    pub pronunciation: PronunciationDictionary,
+    chat_sender: ChatSender,
+    chat_receiver: ChatReceiver,
+    comment_feed: CommentFeed,
+    /// `take_new_comments` でまだ取り出していないコメント
+    new_comments: Vec<ReceivedComment>,
+    chat_replay: ChatReplay,
+    replay_error: Option<String>,
+    chat_sources: Vec<Box<dyn ChatSource>>,
+    source_error: Option<String>,
+    viewer_db: Option<ViewerDatabase>,
+    /// リプレイ・合成チャット用の視聴者統計（メモリ上のみで、data/viewers.db には書き込まない）
+    rehearsal_db: Option<ViewerDatabase>,
+    spam_detector: SpamDetector,
+    tts: TtsPlayer,
+    approval_queue: ApprovalQueue,
+    output_devices: Vec<String>,
+    speaker_styles: Arc<Mutex<Vec<SpeakerStyle>>>,
+    new_assignment_author: String,
+    new_assignment_speaker: u32,
+    new_reserved_speaker: u32,
+    new_dictionary_entry: DictionaryEntry,
+    dictionary_csv_path: String,
+    dictionary_status: Option<Result<String, String>>,
+    dictionary_job: Option<Receiver<DictionaryJobResult>>,
 }
 
+/// バックグラウンドで実行した読み上げ辞書の処理の結果（辞書を変える処理なら処理後の辞書）
+type DictionaryJobResult = (Option<PronunciationDictionary>, Result<String, String>);
+
 #[derive(Default)]
 #[allow(dead_code)]
 pub struct CommentFilter {
@@ -15,18 +75,43 @@ pub struct CommentFilter {
     pub min_account_age_days: u32,
     pub block_non_members: bool,
     pub block_first_time: bool,
+    pub spam: SpamSettings,
 }
 
-#[derive(Default)]
 #[allow(dead_code)]
 pub struct VoiceSettings {
     pub enabled: bool,
-    pub voice_type: String,
+    /// VOICEVOX の話者（スタイル）ID
+    pub speaker_id: u32,
     pub speed: f32,
     pub pitch: f32,
     pub volume: f32,
 }
 
+impl Default for VoiceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            speaker_id: DEFAULT_SPEAKER_ID,
+            speed: 1.0,
+            pitch: 1.0,
+            volume: 1.0,
+        }
+    }
+}
+
+impl VoiceSettings {
+    fn request(&self, text: String, speaker_id: u32) -> SpeechRequest {
+        SpeechRequest {
+            text,
+            speaker_id,
+            speed: self.speed,
+            pitch: self.pitch,
+            volume: self.volume,
+        }
+    }
+}
+
 #[derive(Default)]
 #[allow(dead_code)]
 pub struct CommentDisplaySettings {
@@ -37,11 +122,348 @@ pub struct CommentDisplaySettings {
     pub color_member_names: bool,
 }
 
+#[derive(Default)]
+pub struct ChatSourceSettings {
+    pub youtube_enabled: bool,
+    pub youtube: YouTubeChatSettings,
+    pub twitch_enabled: bool,
+    pub twitch: TwitchChatSettings,
+}
+
+pub struct ViewerStatsSettings {
+    /// 常連とみなす参加配信数
+    pub regular_min_streams: u32,
+    pub greet_regulars: bool,
+    /// `{name}` が視聴者名に置き換わる
+    pub greeting_template: String,
+}
+
+impl Default for ViewerStatsSettings {
+    fn default() -> Self {
+        Self {
+            regular_min_streams: 3,
+            greet_regulars: false,
+            greeting_template: "{name}さん、おかえりなさい！".to_string(),
+        }
+    }
+}
+
+/// 承認待ちの読み上げに対する操作
+enum QueueAction {
+    Preview,
+    Approve,
+    Reject,
+}
+
 impl CommentTab {
-    #[allow(dead_code)]
+    /// `tts_output` はミキサーの読み上げバスへの入力
+    pub fn new(chat_sender: ChatSender, chat_receiver: ChatReceiver, tts_output: InputQueue) -> Self {
+        let viewer_db = match ViewerDatabase::open_default() {
+            Ok(db) => Some(db),
+            Err(e) => {
+                error!("視聴者データベースを開けません: {}", e);
+                None
+            }
+        };
+
+        let pronunciation: PronunciationDictionary = settings_store::load_json(PRONUNCIATION_PATH);
+        let comment_tab = Self {
+            filter_settings: CommentFilter::default(),
+            voice_settings: VoiceSettings::default(),
+            display_settings: CommentDisplaySettings::default(),
+            viewer_settings: ViewerStatsSettings::default(),
+            replay_settings: ChatReplaySettings::default(),
+            source_settings: ChatSourceSettings::default(),
+            voice_assignments: settings_store::load_json(VOICE_ASSIGNMENTS_PATH),
+            engine_settings: TtsEngineSettings::default(),
+            approval_settings: ApprovalSettings::default(),
+            pronunciation: pronunciation.clone(),
+            chat_sender,
+            chat_receiver,
+            comment_feed: Arc::new(Mutex::new(VecDeque::new())),
+            new_comments: Vec::new(),
+            chat_replay: ChatReplay::new(),
+            replay_error: None,
+            chat_sources: Vec::new(),
+            source_error: None,
+            viewer_db,
+            rehearsal_db: None,
+            spam_detector: SpamDetector::new(),
+            tts: TtsPlayer::new(TtsEngineSettings::default(), pronunciation, tts_output),
+            approval_queue: ApprovalQueue::default(),
+            output_devices: audio_output::output_device_names(),
+            speaker_styles: Arc::new(Mutex::new(Vec::new())),
+            new_assignment_author: String::new(),
+            new_assignment_speaker: DEFAULT_SPEAKER_ID,
+            new_reserved_speaker: DEFAULT_SPEAKER_ID,
+            new_dictionary_entry: DictionaryEntry::new("", "", 0),
+            dictionary_csv_path: "config/pronunciation.csv".to_string(),
+            dictionary_status: None,
+            dictionary_job: None,
+        };
+        comment_tab.refresh_speakers();
+        comment_tab
+    }
+
+    /// VOICEVOX から話者一覧をバックグラウンドで取得する
+    fn refresh_speakers(&self) {
+        let speaker_styles = Arc::clone(&self.speaker_styles);
+        let endpoint = self.engine_settings.voicevox_endpoint.clone();
+        thread::spawn(move || {
+            match VoicevoxClient::new(&endpoint).speakers() {
+                Ok(styles) => *speaker_styles.lock().unwrap() = styles,
+                Err(e) => warn!("VOICEVOX の話者一覧を取得できません: {}", e),
+            }
+        });
+    }
+
+    fn save_voice_assignments(&self) {
+        if let Err(e) = settings_store::save_json(VOICE_ASSIGNMENTS_PATH, &self.voice_assignments) {
+            error!("話者の割り当てを保存できません: {}", e);
+        }
+    }
+
+    /// 読み上げ辞書を保存し、読み上げワーカーに反映する
+    fn save_pronunciation(&self) {
+        if let Err(e) = settings_store::save_json(PRONUNCIATION_PATH, &self.pronunciation) {
+            error!("読み上げ辞書を保存できません: {}", e);
+        }
+        self.tts.set_dictionary(self.pronunciation.clone());
+    }
+
+    /// コメント欄の表示側と共有するバッファ
+    pub fn comment_feed(&self) -> CommentFeed {
+        Arc::clone(&self.comment_feed)
+    }
+
+    /// 前回呼び出してから取り込んだコメント（効果音のトリガーなど他のタブへ渡す）
+    pub fn take_new_comments(&mut self) -> Vec<ReceivedComment> {
+        std::mem::take(&mut self.new_comments)
+    }
+
+    /// 配信開始時に呼び出し、視聴者統計の配信の区切りを更新する
+    pub fn begin_stream(&mut self) {
+        if let Some(db) = &mut self.viewer_db {
+            db.begin_stream();
+        }
+    }
+
+    /// 配信終了時に呼び出し、以降のコメントを参加回数に数えないようにする
+    pub fn end_stream(&mut self) {
+        if let Some(db) = &mut self.viewer_db {
+            db.end_stream();
+        }
+    }
+
+    /// リプレイごとに空の視聴者統計を用意する。リプレイ全体を 1 回の配信として数える
+    fn start_rehearsal_db(&mut self) {
+        self.rehearsal_db = match ViewerDatabase::open(":memory:") {
+            Ok(mut db) => {
+                db.begin_stream();
+                Some(db)
+            }
+            Err(e) => {
+                error!("リプレイ用の視聴者データベースを開けません: {}", e);
+                None
+            }
+        };
+    }
+
+    /// 届いたコメントを取り込む。タブの表示状態に関わらず毎フレーム呼び出す
+    pub fn update(&mut self) {
+        while let Ok(message) = self.chat_receiver.try_recv() {
+            self.process_comment(message);
+        }
+
+        let delay = self.approval_settings.auto_approve_secs;
+        if self.approval_settings.enabled && delay > 0 {
+            for item in self.approval_queue.take_expired(Duration::from_secs(delay as u64)) {
+                self.tts.speak(item.request);
+            }
+        }
+    }
+
+    /// 有効なチャット取得元をすべて接続する
+    fn connect_sources(&mut self) {
+        self.disconnect_sources();
+
+        let settings = &self.source_settings;
+        let mut sources: Vec<Box<dyn ChatSource>> = Vec::new();
+        if settings.youtube_enabled {
+            sources.push(Box::new(YouTubeChatSource::new(settings.youtube.clone())));
+        }
+        if settings.twitch_enabled {
+            sources.push(Box::new(TwitchChatSource::new(settings.twitch.clone())));
+        }
+        if sources.is_empty() {
+            self.source_error = Some("取得元が選択されていません".to_string());
+            return;
+        }
+
+        let mut errors = Vec::new();
+        for mut source in sources {
+            match source.start(self.chat_sender.clone()) {
+                Ok(()) => self.chat_sources.push(source),
+                Err(e) => errors.push(format!("{}: {}", source.platform().label(), e)),
+            }
+        }
+        self.source_error = (!errors.is_empty()).then(|| errors.join("\n"));
+    }
+
+    fn disconnect_sources(&mut self) {
+        for source in &mut self.chat_sources {
+            source.stop();
+        }
+        self.chat_sources.clear();
+        self.source_error = None;
+    }
+
+    fn process_comment(&mut self, message: ChatMessage) {
+        let db = if message.simulated { self.rehearsal_db.as_mut() } else { self.viewer_db.as_mut() };
+        let visit = db.and_then(|db| {
+            db.record_message(&message)
+                .map_err(|e| error!("視聴者統計の記録に失敗: {}", e))
+                .ok()
+        });
+        let badge = visit.as_ref().and_then(|visit| self.badge_for(visit));
+
+        let spam = self.spam_detector.check(
+            &message,
+            &self.filter_settings.spam,
+            &self.filter_settings.block_words,
+        );
+        if spam.suggest_timeout {
+            warn!("タイムアウトを推奨: {} ({})", message.author_name, spam.summary());
+        }
+
+        if let Some(visit) = &visit {
+            if self.voice_settings.enabled
+                && !spam.skip_tts
+                && self.viewer_settings.greet_regulars
+                && visit.first_in_stream
+                && badge == Some(ViewerBadge::Regular)
+            {
+                // 挨拶にも視聴者の名前が入るため、コメントと同じく承認モードを通す
+                let greeting = self.viewer_settings.greeting_template
+                    .replace("{name}", &message.author_name);
+                let request = self.voice_settings.request(greeting, self.voice_settings.speaker_id);
+                self.speak_or_queue(message.author_name.clone(), request);
+            }
+        }
+
+        if self.voice_settings.enabled && !spam.skip_tts {
+            let known_speakers: Vec<u32> = self.speaker_styles.lock().unwrap()
+                .iter()
+                .map(|style| style.id)
+                .collect();
+            let speaker_id = self.voice_assignments
+                .resolve(&message, &known_speakers, self.voice_settings.speaker_id);
+            let request = self.voice_settings.request(message.text.clone(), speaker_id);
+            self.speak_or_queue(message.author_name.clone(), request);
+        }
+
+        let comment = ReceivedComment { message, visit, badge, spam };
+        self.new_comments.push(comment.clone());
+        let mut feed = self.comment_feed.lock().unwrap();
+        feed.push_back(comment);
+        if feed.len() > MAX_COMMENTS {
+            feed.pop_front();
+        }
+    }
+
+    /// 承認モードでは承認待ちキューへ、それ以外はすぐに読み上げる
+    fn speak_or_queue(&mut self, author_name: String, request: SpeechRequest) {
+        if self.approval_settings.enabled {
+            self.approval_queue.push(author_name, request);
+        } else {
+            self.tts.speak(request);
+        }
+    }
+
+    fn badge_for(&self, visit: &ViewerVisit) -> Option<ViewerBadge> {
+        if visit.is_first_time() {
+            Some(ViewerBadge::FirstTime)
+        } else if visit.stats.streams_attended >= self.viewer_settings.regular_min_streams {
+            Some(ViewerBadge::Regular)
+        } else {
+            None
+        }
+    }
+
     pub fn ui(&mut self, ui: &mut egui::Ui) {
         ui.heading("コメント設定");
 
+        // チャット取得元
+        ui.collapsing("チャット取得元", |ui| {
+            let connected = !self.chat_sources.is_empty();
+
+            ui.add_enabled_ui(!connected, |ui| {
+                let settings = &mut self.source_settings;
+                ui.checkbox(&mut settings.youtube_enabled, "YouTube");
+                if settings.youtube_enabled {
+                    ui.horizontal(|ui| {
+                        ui.label("APIキー:");
+                        ui.add(egui::TextEdit::singleline(&mut settings.youtube.api_key).password(true));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("動画ID:");
+                        ui.text_edit_singleline(&mut settings.youtube.video_id);
+                    });
+                }
+
+                ui.checkbox(&mut settings.twitch_enabled, "Twitch");
+                if settings.twitch_enabled {
+                    let twitch = &mut settings.twitch;
+                    ui.horizontal(|ui| {
+                        ui.label("チャンネル:");
+                        ui.text_edit_singleline(&mut twitch.channel);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("ユーザー名（空欄で匿名）:");
+                        ui.text_edit_singleline(&mut twitch.username);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("OAuthトークン:");
+                        ui.add(egui::TextEdit::singleline(&mut twitch.oauth_token).password(true));
+                    });
+                    ui.collapsing("接続先（詳細）", |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("ホスト:");
+                            ui.text_edit_singleline(&mut twitch.host);
+                            ui.label("ポート:");
+                            ui.add(egui::DragValue::new(&mut twitch.port).speed(1));
+                        });
+                        ui.checkbox(&mut twitch.use_tls, "TLSを使用");
+                    });
+                }
+            });
+
+            for source in &self.chat_sources {
+                let state = source.state();
+                let color = match state {
+                    SourceState::Connected => egui::Color32::GREEN,
+                    SourceState::Error(_) => egui::Color32::RED,
+                    _ => egui::Color32::GRAY,
+                };
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} {}:", source.platform().label(), source.name()));
+                    ui.colored_label(color, state.label());
+                });
+            }
+
+            if connected {
+                if ui.button("切断").clicked() {
+                    self.disconnect_sources();
+                }
+            } else if ui.button("接続").clicked() {
+                self.connect_sources();
+            }
+
+            if let Some(error) = &self.source_error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+        });
+
         // フィルター設定
         ui.collapsing("フィルター設定", |ui| {
             ui.horizontal(|ui| {
@@ -68,6 +490,61 @@ impl CommentTab {
             if ui.button("ブロックワードを追加").clicked() {
                 self.filter_settings.block_words.push(String::new());
             }
+
+            ui.separator();
+            let spam = &mut self.filter_settings.spam;
+            ui.checkbox(&mut spam.enabled, "スパム・連投対策を有効化");
+            ui.add_enabled_ui(spam.enabled, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("連投制限:");
+                    ui.add(egui::DragValue::new(&mut spam.rate_limit_messages)
+                        .speed(1)
+                        .suffix("件")
+                        .clamp_range(1..=50));
+                    ui.label("/");
+                    ui.add(egui::DragValue::new(&mut spam.rate_limit_window_secs)
+                        .speed(1)
+                        .suffix("秒")
+                        .clamp_range(1..=300));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("重複判定期間:");
+                    ui.add(egui::DragValue::new(&mut spam.duplicate_window_secs)
+                        .speed(1)
+                        .suffix("秒")
+                        .clamp_range(1..=600));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("類似度しきい値:");
+                    ui.add(egui::Slider::new(&mut spam.similarity_threshold, 0.5..=1.0));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("大文字率の上限:");
+                    ui.add(egui::Slider::new(&mut spam.max_caps_ratio, 0.0..=1.0));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("絵文字率の上限:");
+                    ui.add(egui::Slider::new(&mut spam.max_emoji_ratio, 0.0..=1.0));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("同一文字の連続上限:");
+                    ui.add(egui::DragValue::new(&mut spam.max_repeated_chars)
+                        .speed(1)
+                        .suffix("文字")
+                        .clamp_range(2..=100));
+                });
+                ui.checkbox(&mut spam.block_links, "リンクを含むコメントを検出");
+
+                ui.label("自動アクションのスコアしきい値:");
+                ui.horizontal(|ui| {
+                    ui.label("読み上げスキップ:");
+                    ui.add(egui::DragValue::new(&mut spam.skip_tts_threshold).speed(0.1).clamp_range(0.0..=10.0));
+                    ui.label("非表示:");
+                    ui.add(egui::DragValue::new(&mut spam.hide_threshold).speed(0.1).clamp_range(0.0..=10.0));
+                    ui.label("タイムアウト提案:");
+                    ui.add(egui::DragValue::new(&mut spam.timeout_threshold).speed(0.1).clamp_range(0.0..=10.0));
+                });
+            });
         });
 
         // 読み上げ設定
@@ -75,9 +552,13 @@ impl CommentTab {
             ui.checkbox(&mut self.voice_settings.enabled, "コメント読み上げを有効化");
 
             if self.voice_settings.enabled {
+                let styles = self.speaker_styles.lock().unwrap().clone();
                 ui.horizontal(|ui| {
-                    ui.label("声の種類:");
-                    ui.text_edit_singleline(&mut self.voice_settings.voice_type);
+                    ui.label("既定の話者:");
+                    speaker_picker(ui, "default_speaker", &mut self.voice_settings.speaker_id, &styles);
+                    if ui.button("話者一覧を再取得").clicked() {
+                        self.refresh_speakers();
+                    }
                 });
 
                 ui.horizontal(|ui| {
@@ -94,6 +575,24 @@ impl CommentTab {
                     ui.label("音量:");
                     ui.add(egui::Slider::new(&mut self.voice_settings.volume, 0.0..=1.0));
                 });
+
+                ui.collapsing("話者の割り当て", |ui| {
+                    if self.voice_assignment_ui(ui, &styles) {
+                        self.save_voice_assignments();
+                    }
+                });
+
+                ui.collapsing(format!("読み上げ辞書（{} 語）", self.pronunciation.entries.len()), |ui| {
+                    self.pronunciation_ui(ui);
+                });
+
+                ui.collapsing("読み上げエンジン", |ui| {
+                    self.engine_ui(ui);
+                });
+
+                ui.collapsing(format!("承認モード（承認待ち {} 件）", self.approval_queue.len()), |ui| {
+                    self.approval_ui(ui);
+                });
             }
         });
 
@@ -119,5 +618,496 @@ impl CommentTab {
             ui.checkbox(&mut self.display_settings.show_member_icon, "メンバーアイコンを表示");
             ui.checkbox(&mut self.display_settings.color_member_names, "メンバー名を色付け");
         });
+
+        // 視聴者統計
+        ui.collapsing("視聴者統計", |ui| {
+            if self.viewer_db.is_none() {
+                ui.colored_label(egui::Color32::RED, "視聴者データベースを開けませんでした");
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("常連とみなす参加回数:");
+                ui.add(egui::DragValue::new(&mut self.viewer_settings.regular_min_streams)
+                    .speed(1)
+                    .suffix("回")
+                    .clamp_range(2..=100));
+            });
+
+            ui.checkbox(&mut self.viewer_settings.greet_regulars, "常連の来場を読み上げで挨拶（配信ごとに1回）");
+            if self.viewer_settings.greet_regulars {
+                ui.horizontal(|ui| {
+                    ui.label("挨拶文:");
+                    ui.text_edit_singleline(&mut self.viewer_settings.greeting_template);
+                });
+            }
+        });
+
+        // チャットリプレイ（リハーサル用）
+        ui.collapsing("チャットリプレイ", |ui| {
+            let running = self.chat_replay.is_running();
+
+            ui.add_enabled_ui(!running, |ui| {
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.replay_settings.mode, ReplayMode::Recorded, "チャットログ");
+                    ui.radio_value(&mut self.replay_settings.mode, ReplayMode::Synthetic, "合成チャット");
+                });
+
+                match self.replay_settings.mode {
+                    ReplayMode::Recorded => {
+                        ui.horizontal(|ui| {
+                            ui.label("ログファイル (JSONL):");
+                            ui.text_edit_singleline(&mut self.replay_settings.log_path);
+                        });
+                    }
+                    ReplayMode::Synthetic => {
+                        let synthetic = &mut self.replay_settings.synthetic;
+                        ui.horizontal(|ui| {
+                            ui.label("投稿頻度:");
+                            ui.add(egui::DragValue::new(&mut synthetic.messages_per_minute)
+                                .speed(1)
+                                .suffix("件/分")
+                                .clamp_range(1..=600));
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("スーパーチャット率:");
+                            ui.add(egui::Slider::new(&mut synthetic.super_chat_ratio, 0.0..=1.0));
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("メンバー率:");
+                            ui.add(egui::Slider::new(&mut synthetic.member_ratio, 0.0..=1.0));
+                        });
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("再生速度:");
+                    ui.add(egui::Slider::new(&mut self.replay_settings.speed, 0.25..=8.0)
+                        .logarithmic(true)
+                        .suffix("x"));
+                });
+            });
+
+            if running {
+                if ui.button("リプレイ停止").clicked() {
+                    self.chat_replay.stop();
+                }
+            } else if ui.button("リプレイ開始").clicked() {
+                self.replay_error = self.chat_replay
+                    .start(&self.replay_settings, self.chat_sender.clone())
+                    .err();
+                if self.replay_error.is_none() {
+                    self.start_rehearsal_db();
+                }
+            }
+
+            if let Some(error) = &self.replay_error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+        });
+    }
+
+    /// 読み上げ辞書の処理をバックグラウンドで実行する（VOICEVOX が応答しなくても UI を止めない）
+    ///
+    /// `modifies` が true の処理は、成功したら処理後の辞書で置き換える
+    fn spawn_dictionary_job<F>(&mut self, modifies: bool, job: F)
+    where
+        F: FnOnce(&mut PronunciationDictionary, &VoicevoxClient) -> Result<String, String> + Send + 'static,
+    {
+        let mut dictionary = self.pronunciation.clone();
+        let endpoint = self.engine_settings.voicevox_endpoint.clone();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let status = job(&mut dictionary, &VoicevoxClient::new(&endpoint));
+            let dictionary = (modifies && status.is_ok()).then_some(dictionary);
+            let _ = sender.send((dictionary, status));
+        });
+        self.dictionary_job = Some(receiver);
+    }
+
+    /// 終わった読み上げ辞書の処理の結果を反映する
+    fn poll_dictionary_job(&mut self) {
+        let Some(receiver) = &self.dictionary_job else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok((dictionary, status)) => {
+                if let Some(dictionary) = dictionary {
+                    self.pronunciation = dictionary;
+                    self.save_pronunciation();
+                }
+                self.dictionary_status = Some(status);
+                self.dictionary_job = None;
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => self.dictionary_job = None,
+        }
+    }
+
+    /// 読み上げ辞書の編集と VOICEVOX・CSV との同期
+    fn pronunciation_ui(&mut self, ui: &mut egui::Ui) {
+        self.poll_dictionary_job();
+        // 処理中に編集すると、取り込み後の辞書で上書きされてしまうため
+        if self.dictionary_job.is_some() {
+            ui.set_enabled(false);
+        }
+        let mut changed = false;
+        let mut remove = None;
+        egui::Grid::new("pronunciation_grid").striped(true).show(ui, |ui| {
+            ui.strong("表記");
+            ui.strong("読み（カタカナ）");
+            ui.strong("アクセント");
+            ui.end_row();
+
+            for (index, entry) in self.pronunciation.entries.iter_mut().enumerate() {
+                changed |= ui.text_edit_singleline(&mut entry.surface).lost_focus();
+                changed |= ui.text_edit_singleline(&mut entry.pronunciation).lost_focus();
+                changed |= ui.add(egui::DragValue::new(&mut entry.accent_type).speed(1).clamp_range(0..=30)).changed();
+                if !entry.is_valid() {
+                    ui.colored_label(egui::Color32::RED, "読みが不正です");
+                }
+                if ui.button("削除").clicked() {
+                    remove = Some(index);
+                }
+                ui.end_row();
+            }
+
+            let entry = &mut self.new_dictionary_entry;
+            ui.text_edit_singleline(&mut entry.surface);
+            ui.text_edit_singleline(&mut entry.pronunciation);
+            ui.add(egui::DragValue::new(&mut entry.accent_type).speed(1).clamp_range(0..=30));
+            if ui.add_enabled(entry.is_valid(), egui::Button::new("追加")).clicked() {
+                let entry = std::mem::replace(entry, DictionaryEntry::new("", "", 0));
+                self.pronunciation.upsert(entry);
+                changed = true;
+            }
+            ui.end_row();
+        });
+        ui.label(egui::RichText::new("アクセントは音が下がる直前のモーラの位置（0 は平板型）").small().weak());
+
+        if let Some(index) = remove {
+            self.pronunciation.entries.remove(index);
+            changed = true;
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("VOICEVOX へ送信").clicked() {
+                self.spawn_dictionary_job(false, |dictionary, client| {
+                    dictionary.push_to_voicevox(client).and_then(|report| sync_status("送信しました", report))
+                });
+            }
+            if ui.button("VOICEVOX から取得").clicked() {
+                self.spawn_dictionary_job(true, |dictionary, client| {
+                    dictionary.pull_from_voicevox(client).and_then(|report| sync_status("取得しました", report))
+                });
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("CSV:");
+            ui.text_edit_singleline(&mut self.dictionary_csv_path);
+            let path = self.dictionary_csv_path.clone();
+            if ui.button("読み込み").clicked() {
+                self.spawn_dictionary_job(true, move |dictionary, _| {
+                    dictionary.import_csv(&path).and_then(|report| sync_status("読み込みました", report))
+                });
+            }
+            let path = self.dictionary_csv_path.clone();
+            if ui.button("書き出し").clicked() {
+                self.spawn_dictionary_job(false, move |dictionary, _| {
+                    dictionary.export_csv(&path).map(|()| format!("{} 語を書き出しました", dictionary.entries.len()))
+                });
+            }
+        });
+
+        if self.dictionary_job.is_some() {
+            ui.label("処理中...");
+        }
+        match &self.dictionary_status {
+            Some(Ok(message)) => { ui.label(message); }
+            Some(Err(e)) => { ui.colored_label(egui::Color32::RED, e); }
+            None => {}
+        }
+
+        if changed {
+            self.save_pronunciation();
+        }
+    }
+
+    /// 読み上げエンジンの構成と稼働状況
+    fn engine_ui(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("VOICEVOX エンドポイント:");
+            changed |= ui.text_edit_singleline(&mut self.engine_settings.voicevox_endpoint).lost_focus();
+        });
+
+        changed |= ui.checkbox(&mut self.engine_settings.fallback_enabled, "VOICEVOX が使えない場合は代替エンジンで読み上げる").changed();
+        if self.engine_settings.fallback_enabled {
+            ui.horizontal(|ui| {
+                ui.label("コマンド:");
+                changed |= ui.text_edit_singleline(&mut self.engine_settings.fallback_command).lost_focus();
+            });
+            ui.label(egui::RichText::new(
+                "{text}: 本文（-- より後のみ） / {input}: 本文を書いたファイル / {output}: WAV の出力先 / {speed}: 速度（{output} がない場合は標準出力を WAV として読む）"
+            ).small().weak());
+        }
+
+        if changed {
+            self.tts.set_settings(self.engine_settings.clone());
+            self.tts.check_health();
+        }
+
+        ui.separator();
+        let health = self.tts.health();
+        if health.is_empty() {
+            ui.label("確認中...");
+        }
+        for engine in health {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}:", engine.name));
+                match &engine.status {
+                    HealthStatus::Healthy => ui.colored_label(egui::Color32::GREEN, "利用可能"),
+                    HealthStatus::Unhealthy(e) => ui.colored_label(egui::Color32::RED, format!("利用不可: {}", e)),
+                };
+            });
+        }
+        if ui.button("今すぐ確認").clicked() {
+            self.tts.check_health();
+        }
+    }
+
+    /// 承認待ちの読み上げの確認・承認
+    fn approval_ui(&mut self, ui: &mut egui::Ui) {
+        let settings = &mut self.approval_settings;
+        if ui.checkbox(&mut settings.enabled, "読み上げ前に承認を求める").changed() && !settings.enabled {
+            // 承認モードを切った時点で待っていたものは読み上げる
+            for item in self.approval_queue.take_expired(Duration::ZERO) {
+                self.tts.speak(item.request);
+            }
+        }
+        if !settings.enabled {
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("自動承認までの時間:");
+            ui.add(egui::DragValue::new(&mut settings.auto_approve_secs)
+                .speed(1)
+                .suffix("秒")
+                .clamp_range(0..=600));
+            ui.label("（0 で自動承認しない）");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("試聴デバイス:");
+            let selected = settings.monitor_device.clone().unwrap_or_else(|| "既定のデバイス".to_string());
+            egui::ComboBox::from_id_source("monitor_device")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut settings.monitor_device, None, "既定のデバイス");
+                    for name in &self.output_devices {
+                        ui.selectable_value(&mut settings.monitor_device, Some(name.clone()), name);
+                    }
+                });
+            if ui.button("再取得").clicked() {
+                self.output_devices = audio_output::output_device_names();
+            }
+        });
+
+        ui.separator();
+        if self.approval_queue.is_empty() {
+            ui.label("承認待ちの読み上げはありません");
+            return;
+        }
+
+        let auto_approve = (settings.auto_approve_secs > 0).then_some(settings.auto_approve_secs as u64);
+        let ids: Vec<u64> = self.approval_queue.items().map(|item| item.id).collect();
+        for id in ids {
+            let Some(item) = self.approval_queue.get_mut(id) else { continue };
+            let mut action = None;
+            ui.group(|ui| {
+                ui.horizontal(|ui| {
+                    ui.strong(&item.author_name);
+                    if let Some(secs) = auto_approve {
+                        let remaining = secs.saturating_sub(item.elapsed().as_secs());
+                        ui.weak(format!("あと {} 秒で自動承認", remaining));
+                    }
+                });
+                ui.text_edit_multiline(&mut item.request.text);
+                ui.horizontal(|ui| {
+                    if ui.button("試聴").clicked() {
+                        action = Some(QueueAction::Preview);
+                    }
+                    if ui.button("承認").clicked() {
+                        action = Some(QueueAction::Approve);
+                    }
+                    if ui.button("却下").clicked() {
+                        action = Some(QueueAction::Reject);
+                    }
+                });
+            });
+
+            match action {
+                Some(QueueAction::Preview) => {
+                    let request = item.request.clone();
+                    self.tts.preview(request, self.approval_settings.monitor_device.clone());
+                }
+                Some(QueueAction::Approve) => {
+                    if let Some(item) = self.approval_queue.remove(id) {
+                        self.tts.speak(item.request);
+                    }
+                }
+                Some(QueueAction::Reject) => {
+                    self.approval_queue.remove(id);
+                }
+                None => {}
+            }
+        }
+
+        if ui.button("すべて却下").clicked() {
+            self.approval_queue.clear();
+        }
+        // 自動承認までの残り時間を更新する
+        ui.ctx().request_repaint_after(Duration::from_secs(1));
+    }
+
+    /// 視聴者・ロールごとの話者設定。変更があった場合は true を返す
+    fn voice_assignment_ui(&mut self, ui: &mut egui::Ui, styles: &[SpeakerStyle]) -> bool {
+        let mut changed = false;
+        let assignments = &mut self.voice_assignments;
+
+        ui.label("ロール別の話者:");
+        for (label, id, speaker) in [
+            ("スーパーチャット", "super_chat_speaker", &mut assignments.super_chat_speaker),
+            ("モデレーター", "moderator_speaker", &mut assignments.moderator_speaker),
+            ("メンバー", "member_speaker", &mut assignments.member_speaker),
+        ] {
+            ui.horizontal(|ui| {
+                let mut enabled = speaker.is_some();
+                if ui.checkbox(&mut enabled, label).changed() {
+                    *speaker = enabled.then_some(self.voice_settings.speaker_id);
+                    changed = true;
+                }
+                if let Some(speaker_id) = speaker {
+                    changed |= speaker_picker(ui, id, speaker_id, styles);
+                }
+            });
+        }
+
+        ui.separator();
+        changed |= ui.checkbox(&mut assignments.hash_by_author, "視聴者ごとに話者を自動で割り当てる").changed();
+        if assignments.hash_by_author {
+            ui.label("自動割り当てから除外する話者:");
+            let mut removed = None;
+            for (index, speaker_id) in assignments.reserved.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(speaker_label(*speaker_id, styles));
+                    if ui.button("削除").clicked() {
+                        removed = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = removed {
+                assignments.reserved.remove(index);
+                changed = true;
+            }
+            ui.horizontal(|ui| {
+                speaker_picker(ui, "new_reserved_speaker", &mut self.new_reserved_speaker, styles);
+                if ui.button("除外に追加").clicked() && !assignments.reserved.contains(&self.new_reserved_speaker) {
+                    assignments.reserved.push(self.new_reserved_speaker);
+                    changed = true;
+                }
+            });
+        }
+
+        ui.separator();
+        ui.label("視聴者ごとの話者:");
+        let mut removed = None;
+        for (author_id, user) in assignments.users.iter_mut() {
+            ui.horizontal(|ui| {
+                ui.label(&user.author_name).on_hover_text(author_id);
+                changed |= speaker_picker(ui, author_id, &mut user.speaker_id, styles);
+                if ui.button("削除").clicked() {
+                    removed = Some(author_id.clone());
+                }
+            });
+        }
+        if let Some(author_id) = removed {
+            assignments.users.remove(&author_id);
+            changed = true;
+        }
+
+        // 最近コメントした視聴者から選んで追加する
+        let recent_authors: Vec<(String, String)> = {
+            let feed = self.comment_feed.lock().unwrap();
+            let mut authors: Vec<(String, String)> = Vec::new();
+            for comment in feed.iter().rev() {
+                let message = &comment.message;
+                if !authors.iter().any(|(id, _)| *id == message.author_id) {
+                    authors.push((message.author_id.clone(), message.author_name.clone()));
+                }
+            }
+            authors
+        };
+        ui.horizontal(|ui| {
+            let selected_name = recent_authors.iter()
+                .find(|(id, _)| *id == self.new_assignment_author)
+                .map(|(_, name)| name.clone())
+                .unwrap_or_else(|| "視聴者を選択".to_string());
+            egui::ComboBox::from_id_source("new_assignment_author")
+                .selected_text(selected_name)
+                .show_ui(ui, |ui| {
+                    for (id, name) in &recent_authors {
+                        ui.selectable_value(&mut self.new_assignment_author, id.clone(), name);
+                    }
+                });
+            speaker_picker(ui, "new_assignment_speaker", &mut self.new_assignment_speaker, styles);
+            if ui.button("割り当てを追加").clicked() {
+                if let Some((id, name)) = recent_authors.iter().find(|(id, _)| *id == self.new_assignment_author) {
+                    assignments.users.insert(id.clone(), UserVoice {
+                        author_name: name.clone(),
+                        speaker_id: self.new_assignment_speaker,
+                    });
+                    changed = true;
+                }
+            }
+        });
+
+        changed
+    }
+}
+
+fn speaker_label(speaker_id: u32, styles: &[SpeakerStyle]) -> String {
+    styles.iter()
+        .find(|style| style.id == speaker_id)
+        .map(|style| style.label.clone())
+        .unwrap_or_else(|| format!("話者ID {}", speaker_id))
+}
+
+/// 話者の選択欄。話者一覧が取得できていない場合は ID を直接入力する
+fn speaker_picker(ui: &mut egui::Ui, id_source: &str, speaker_id: &mut u32, styles: &[SpeakerStyle]) -> bool {
+    if styles.is_empty() {
+        return ui.add(egui::DragValue::new(speaker_id).speed(1)).changed();
     }
-} 
\ No newline at end of file
+
+    let before = *speaker_id;
+    egui::ComboBox::from_id_source(id_source)
+        .selected_text(speaker_label(*speaker_id, styles))
+        .show_ui(ui, |ui| {
+            for style in styles {
+                ui.selectable_value(speaker_id, style.id, &style.label);
+            }
+        });
+    before != *speaker_id
+}
+
+/// 同期結果の表示。登録できなかった語があればエラーとして表示する
+fn sync_status(action: &str, report: SyncReport) -> Result<String, String> {
+    let message = format!("{}: {}", action, report.summary());
+    if report.errors.is_empty() {
+        Ok(message)
+    } else {
+        Err(format!("{}\n{}", message, report.errors.join("\n")))
+    }
+}
@@ -0,0 +1,130 @@
+use eframe::egui;
+use log::error;
+use crate::models::hotkey::{self, BindingStatus, HotkeyAction, HotkeyManager, HotkeySettings, HOTKEYS_PATH};
+use crate::models::settings_store;
+
+pub struct HotkeyTab {
+    pub settings: HotkeySettings,
+    manager: HotkeyManager,
+    /// キーの組み合わせを記録中の操作
+    recording: Option<HotkeyAction>,
+    /// 効果音のホットキー（`AudioTab` から毎フレーム受け取る）
+    effect_bindings: Vec<(HotkeyAction, String, String)>,
+}
+
+impl HotkeyTab {
+    pub fn new() -> Self {
+        let mut settings: HotkeySettings = settings_store::load_json(HOTKEYS_PATH);
+        settings.add_missing_actions();
+        Self {
+            settings,
+            manager: HotkeyManager::new(),
+            recording: None,
+            effect_bindings: Vec::new(),
+        }
+    }
+
+    /// 割り当てを反映し、押されたホットキーの操作を返す。タブの表示状態に関わらず毎フレーム呼び出す
+    ///
+    /// `effects` は効果音ごとの（操作, 効果音名, ホットキー）。`suspend` の間は記録中のキーを横取りしないよう登録を外す
+    pub fn update(&mut self, ctx: &egui::Context, effects: Vec<(HotkeyAction, String, String)>, suspend: bool) -> Vec<HotkeyAction> {
+        self.effect_bindings = effects;
+        if suspend || self.recording.is_some() {
+            self.manager.set_bindings(Vec::new());
+            return Vec::new();
+        }
+
+        let bindings = self
+            .settings
+            .bindings
+            .iter()
+            .cloned()
+            .chain(self.effect_bindings.iter().map(|(action, _, hotkey)| (*action, hotkey.clone())))
+            .collect();
+        self.manager.set_bindings(bindings);
+        self.manager.poll(ctx)
+    }
+
+    /// 割り当てたキーを押したままにしているか（プッシュトゥトーク用）
+    pub fn is_held(&self, action: HotkeyAction) -> bool {
+        self.manager.is_held(action)
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("ホットキー設定");
+
+        match self.manager.backend_name() {
+            Some(name) => ui.label(format!("アプリ外でも有効です（{}）", name)),
+            None => ui.colored_label(egui::Color32::YELLOW, "グローバルホットキーを利用できないため、アプリにフォーカスがあるときのみ有効です"),
+        };
+
+        if let Some(action) = self.recording {
+            ui.label("割り当てるキーを押してください（Esc で取り消し）");
+            if let Some(captured) = hotkey::capture_accelerator(ui.ctx()) {
+                if let Some(accelerator) = captured {
+                    if let Some((_, text)) = self.settings.bindings.iter_mut().find(|(a, _)| *a == action) {
+                        *text = accelerator.to_string();
+                    }
+                    self.save();
+                }
+                self.recording = None;
+            }
+        }
+
+        let mut changed = false;
+        egui::Grid::new("hotkey_grid").striped(true).show(ui, |ui| {
+            ui.strong("操作");
+            ui.strong("キー");
+            ui.strong("");
+            ui.strong("状態");
+            ui.end_row();
+
+            for (action, text) in &mut self.settings.bindings {
+                ui.label(action.label());
+                changed |= ui.text_edit_singleline(text).lost_focus();
+                ui.horizontal(|ui| {
+                    let recording = self.recording == Some(*action);
+                    if ui.selectable_label(recording, "記録").clicked() {
+                        self.recording = if recording { None } else { Some(*action) };
+                    }
+                    if ui.button("クリア").clicked() {
+                        text.clear();
+                        changed = true;
+                    }
+                });
+                status_label(ui, self.manager.status(*action));
+                ui.end_row();
+            }
+
+            // 効果音のホットキーは音声設定タブで変更する
+            for (action, name, hotkey) in &self.effect_bindings {
+                if hotkey.trim().is_empty() {
+                    continue;
+                }
+                ui.label(format!("効果音「{}」を再生", name));
+                ui.label(hotkey);
+                ui.label("音声設定で変更");
+                status_label(ui, self.manager.status(*action));
+                ui.end_row();
+            }
+        });
+
+        if changed {
+            self.save();
+        }
+    }
+
+    fn save(&self) {
+        if let Err(e) = settings_store::save_json(HOTKEYS_PATH, &self.settings) {
+            error!("ホットキー設定を保存できません: {}", e);
+        }
+    }
+}
+
+fn status_label(ui: &mut egui::Ui, status: Option<&BindingStatus>) {
+    match status {
+        Some(status) if status.is_error() => ui.colored_label(egui::Color32::RED, status.label()),
+        Some(status) => ui.label(status.label()),
+        None => ui.label("-"),
+    };
+}
@@ -4,6 +4,8 @@ mod video_tab;
 mod banner_tab;
 mod comment_tab;
 mod status_tab;
+mod hotkey_tab;
+mod widgets;
 
 pub use stream_tab::{StreamTab, StreamStatus};
 pub use audio_tab::AudioTab;
@@ -11,3 +13,4 @@ pub use video_tab::VideoTab;
 pub use banner_tab::BannerTab;
 pub use comment_tab::CommentTab;
 pub use status_tab::StatusTab;
+pub use hotkey_tab::HotkeyTab;
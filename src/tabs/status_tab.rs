@@ -1,16 +1,31 @@
+use std::collections::HashMap;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
 use eframe::egui;
 use crate::tabs::StreamStatus;
+use super::widgets;
 use crate::models::{
+    audio_engine::AudioBlock,
     camera::CameraSettings,
+    comment::{CommentFeed, Platform, ReceivedComment, ViewerBadge},
+    level_meter::LevelMeters,
+    media_clock::{Interleaver, MediaStream, SyncMonitor},
+    mic_control::{MicIndicatorFeed, MicMode},
+    now_playing::NowPlayingFeed,
     video_frame::VideoFrame,
     screen_capture::ScreenCapture
 };
 
+/// 届かないストリームをこれ以上は待たずにパケットを出す
+const SYNC_MAX_WAIT: Duration = Duration::from_millis(500);
+
 pub struct StatusTab {
     #[allow(dead_code)]
     preview_size: egui::Vec2,
     pub is_streaming: bool,
     pub status: StreamStatus,
+    /// 配信開始の要求（開始前の確認は `MainWindow` が行う）
+    start_requested: bool,
     #[allow(dead_code)]
     current_frame: Option<VideoFrame>,
     camera: Option<CameraSettings>,
@@ -18,25 +33,45 @@ pub struct StatusTab {
     screen_capture: Option<ScreenCapture>,
     screen_texture: Option<egui::TextureHandle>,
     is_screen_sharing: bool,
+    /// 映像設定タブの画面キャプチャの遅延補正（ms）
+    screen_sync_offset_ms: i32,
+    comment_feed: CommentFeed,
+    level_meters: LevelMeters,
+    now_playing: NowPlayingFeed,
+    mic_indicator: MicIndicatorFeed,
+    /// 配信中だけ、映像と音声のタイムスタンプを時刻順に並べて同期を確かめる
+    interleaver: Option<Interleaver<()>>,
+    audio_blocks: Option<Receiver<AudioBlock>>,
+    /// 同じフレームを二重に渡さないよう、ストリームごとに最後に渡した時刻を覚えておく
+    last_video_pts: HashMap<MediaStream, Duration>,
+    sync_monitor: SyncMonitor,
 }
 
-impl Default for StatusTab {
-    fn default() -> Self {
+impl StatusTab {
+    pub fn new(comment_feed: CommentFeed, level_meters: LevelMeters, now_playing: NowPlayingFeed, mic_indicator: MicIndicatorFeed) -> Self {
         Self {
             preview_size: egui::Vec2::new(480.0, 270.0), // 16:9 アスペクト比
             is_streaming: false,
             status: StreamStatus::Offline,
+            start_requested: false,
             current_frame: None,
             camera: None,
             camera_texture: None,
             screen_capture: None,
             screen_texture: None,
             is_screen_sharing: false,
+            screen_sync_offset_ms: 0,
+            comment_feed,
+            level_meters,
+            now_playing,
+            mic_indicator,
+            interleaver: None,
+            audio_blocks: None,
+            last_video_pts: HashMap::new(),
+            sync_monitor: SyncMonitor::default(),
         }
     }
-}
 
-impl StatusTab {
     fn initialize_camera(&mut self) {
         if self.camera.is_none() {
             match CameraSettings::new(0) {
@@ -53,11 +88,107 @@ impl StatusTab {
     fn initialize_screen_capture(&mut self) {
         if self.screen_capture.is_none() {
             let mut screen_capture = ScreenCapture::new();
+            screen_capture.set_sync_offset_ms(self.screen_sync_offset_ms);
             screen_capture.start();
             self.screen_capture = Some(screen_capture);
         }
     }
 
+    pub fn set_screen_sync_offset_ms(&mut self, offset_ms: i32) {
+        self.screen_sync_offset_ms = offset_ms;
+        if let Some(screen_capture) = &self.screen_capture {
+            screen_capture.set_sync_offset_ms(offset_ms);
+        }
+    }
+
+    /// 映像設定タブのカメラの遅延補正（ms）を、同じデバイスのプレビュー用カメラへ反映する
+    pub fn set_camera_sync_offset_ms(&mut self, device_id: &str, offset_ms: i32) {
+        if let Some(camera) = self.camera.as_mut().filter(|camera| camera.device_id == device_id) {
+            camera.set_sync_offset_ms(offset_ms);
+        }
+    }
+
+    /// プレビューをカメラと画面共有で切り替える
+    pub fn toggle_screen_share(&mut self) {
+        self.is_screen_sharing = !self.is_screen_sharing;
+        if self.is_screen_sharing {
+            self.initialize_screen_capture();
+        } else if let Some(screen_capture) = &mut self.screen_capture {
+            screen_capture.stop();
+            self.screen_capture = None;
+            if let Some(interleaver) = &mut self.interleaver {
+                interleaver.remove_stream(&MediaStream::Screen);
+            }
+        }
+    }
+
+    /// 配信開始時に呼び出し、マスター出力と映像のタイムスタンプの並べ替えを始める
+    pub fn start_sync(&mut self, audio_blocks: Receiver<AudioBlock>) {
+        self.sync_monitor.clear();
+        self.last_video_pts.clear();
+        let mut interleaver = Interleaver::new(Box::new(self.sync_monitor.clone()), SYNC_MAX_WAIT);
+        interleaver.add_stream(MediaStream::Audio);
+        self.interleaver = Some(interleaver);
+        self.audio_blocks = Some(audio_blocks);
+    }
+
+    /// 配信終了時に呼び出し、待っていたパケットを出し切る
+    pub fn stop_sync(&mut self) {
+        if let Some(mut interleaver) = self.interleaver.take() {
+            interleaver.flush();
+        }
+        self.audio_blocks = None;
+    }
+
+    /// 届いたタイムスタンプを並べる。タブの表示状態に関わらず毎フレーム呼び出す
+    pub fn update(&mut self) {
+        let Some(interleaver) = &mut self.interleaver else {
+            return;
+        };
+        if let Some(audio_blocks) = &self.audio_blocks {
+            while let Ok(block) = audio_blocks.try_recv() {
+                interleaver.push(MediaStream::Audio, block.timestamp, ());
+            }
+        }
+
+        let mut video = Vec::new();
+        if let Some(camera) = &self.camera {
+            video.push((MediaStream::Camera(camera.device_id.clone()), camera.frame_timestamp()));
+        }
+        if let Some(screen_capture) = &self.screen_capture {
+            video.push((MediaStream::Screen, screen_capture.frame_timestamp()));
+        }
+        for (stream, pts) in video {
+            interleaver.add_stream(stream.clone());
+            if let Some(pts) = pts {
+                if self.last_video_pts.get(&stream) != Some(&pts) {
+                    self.last_video_pts.insert(stream.clone(), pts);
+                    interleaver.push(stream, pts, ());
+                }
+            }
+        }
+        interleaver.pump();
+    }
+
+    /// 配信中なら停止し、停止中なら開始を要求する
+    pub fn toggle_streaming(&mut self) {
+        if self.is_streaming {
+            self.set_streaming(false);
+        } else {
+            self.start_requested = true;
+        }
+    }
+
+    pub fn set_streaming(&mut self, streaming: bool) {
+        self.is_streaming = streaming;
+        self.status = if streaming { StreamStatus::Live } else { StreamStatus::Offline };
+    }
+
+    /// 確認していない配信開始の要求を取り出す
+    pub fn take_start_request(&mut self) -> bool {
+        std::mem::take(&mut self.start_requested)
+    }
+
     pub fn ui(&mut self, ui: &mut egui::Ui) {
         // 利用可能な幅を取得
         let available_width = ui.available_width();
@@ -77,13 +208,7 @@ impl StatusTab {
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         // 画面共有ボタンを追加
                         if ui.button(if self.is_screen_sharing { "画面共有停止" } else { "画面共有開始" }).clicked() {
-                            self.is_screen_sharing = !self.is_screen_sharing;
-                            if self.is_screen_sharing {
-                                self.initialize_screen_capture();
-                            } else if let Some(screen_capture) = &mut self.screen_capture {
-                                screen_capture.stop();
-                                self.screen_capture = None;
-                            }
+                            self.toggle_screen_share();
                         }
 
                         // カメラ追加ボタンを追加
@@ -92,16 +217,8 @@ impl StatusTab {
                         }
                         
                         // 既存の配信開始/停止ボタン
-                        if self.is_streaming {
-                            if ui.button("配信停止").clicked() {
-                                self.is_streaming = false;
-                                self.status = StreamStatus::Offline;
-                            }
-                        } else {
-                            if ui.button("配信開始").clicked() {
-                                self.is_streaming = true;
-                                self.status = StreamStatus::Live;
-                            }
+                        if ui.button(if self.is_streaming { "配信停止" } else { "配信開始" }).clicked() {
+                            self.toggle_streaming();
                         }
                     });
                 });
@@ -217,6 +334,11 @@ impl StatusTab {
                             );
                         }
                     }
+
+                    // 配信画面のミュート表示
+                    if let Some(text) = &self.mic_indicator.lock().unwrap().overlay_text {
+                        Self::muted_overlay(ui, preview_rect, text);
+                    }
                 });
 
                 // 配信情報
@@ -228,7 +350,35 @@ impl StatusTab {
                         ui.label("配信時間: 00:00:00");
                         ui.add_space(20.0);
                         ui.label("ビットレート: 0 Mbps");
+                        ui.add_space(20.0);
+                        ui.label("音声:");
+                        if widgets::level_meter(ui, &self.level_meters.reading(None), egui::vec2(160.0, 12.0)) {
+                            self.level_meters.reset_clip(None);
+                        }
+                        ui.add_space(20.0);
+                        let mic = self.mic_indicator.lock().unwrap().clone();
+                        let push_to_talk = if mic.mode == MicMode::PushToTalk { "（PTT）" } else { "" };
+                        if mic.muted {
+                            ui.colored_label(egui::Color32::RED, format!("マイク: ミュート{}", push_to_talk));
+                        } else {
+                            ui.colored_label(egui::Color32::from_rgb(76, 175, 80), format!("マイク: オン{}", push_to_talk));
+                        }
                     });
+                    let skew = self.sync_monitor.skew_ms();
+                    if self.is_streaming && !skew.is_empty() {
+                        let labels: Vec<String> = skew.iter()
+                            .map(|(stream, skew_ms)| format!("{} {:+}ms", stream.label(), skew_ms))
+                            .collect();
+                        ui.label(format!("映像のずれ（音声基準）: {}", labels.join(" / ")));
+                    }
+                    if let Some(track) = self.now_playing.lock().unwrap().as_ref() {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("♪ {}", track.label()));
+                            if !track.license_url.is_empty() {
+                                ui.hyperlink_to("ライセンス", &track.license_url);
+                            }
+                        });
+                    }
                 });
             });
 
@@ -239,17 +389,152 @@ impl StatusTab {
                     ui.heading("コメント");
                     let comment_area = egui::ScrollArea::vertical()
                         .max_height(ui.available_height() - 40.0) // ヘッダー分を引く
-                        .auto_shrink([false; 2]);
+                        .auto_shrink([false; 2])
+                        .stick_to_bottom(true);
                     
                     comment_area.show(ui, |ui| {
-                        // TODO: 実際のコメントを表示
-                        ui.label("まだコメントはありません");
+                        let comments = self.comment_feed.lock().unwrap();
+                        if comments.is_empty() {
+                            ui.label("まだコメントはありません");
+                        }
+                        for comment in comments.iter() {
+                            Self::comment_row(ui, comment);
+                        }
                     });
                 });
             });
         });
     }
 
+    fn comment_row(ui: &mut egui::Ui, comment: &ReceivedComment) {
+        let message = &comment.message;
+        let name_color = if message.is_member {
+            egui::Color32::from_rgb(76, 175, 80)
+        } else {
+            egui::Color32::from_rgb(170, 170, 170)
+        };
+
+        // スパム判定でオーバーレイから外したコメントは目立たないように表示する
+        if comment.spam.hide_from_overlay {
+            ui.horizontal_wrapped(|ui| {
+                ui.colored_label(egui::Color32::from_rgb(120, 120, 120), &message.author_name);
+                ui.colored_label(egui::Color32::from_rgb(120, 120, 120), &message.text);
+            });
+            ui.horizontal_wrapped(|ui| {
+                ui.small(format!("非表示 (スコア {:.1}): {}", comment.spam.score, comment.spam.summary()));
+                if comment.spam.suggest_timeout {
+                    ui.colored_label(egui::Color32::RED, "タイムアウト推奨");
+                }
+            });
+            return;
+        }
+
+        let header = |ui: &mut egui::Ui| {
+            Self::platform_icon(ui, message.platform);
+            match comment.badge {
+                Some(ViewerBadge::FirstTime) => {
+                    ui.colored_label(egui::Color32::from_rgb(100, 181, 246), "初見");
+                }
+                Some(ViewerBadge::Regular) => {
+                    ui.colored_label(egui::Color32::from_rgb(255, 202, 40), "常連");
+                }
+                None => {}
+            }
+            let name = ui.colored_label(name_color, &message.author_name);
+            if let Some(visit) = &comment.visit {
+                name.on_hover_text(format!(
+                    "初参加: {}\n参加配信数: {}\nコメント数: {}\nスーパーチャット合計: {}",
+                    visit.stats.first_seen_label(),
+                    visit.stats.streams_attended,
+                    visit.stats.message_count,
+                    visit.stats.total_super_chat,
+                ));
+            }
+        };
+
+        if let Some(membership) = &message.membership {
+            egui::Frame::none()
+                .fill(egui::Color32::from_rgb(27, 94, 32))
+                .rounding(4.0)
+                .inner_margin(4.0)
+                .show(ui, |ui| {
+                    ui.horizontal_wrapped(|ui| {
+                        header(ui);
+                        let label = match (membership.is_gift, membership.months) {
+                            (true, _) => "メンバーシップ（ギフト）".to_string(),
+                            (false, 1) => "新規メンバー".to_string(),
+                            (false, months) => format!("メンバー {}か月", months),
+                        };
+                        ui.strong(label);
+                    });
+                    if !message.text.is_empty() {
+                        ui.label(&message.text);
+                    }
+                });
+            return;
+        }
+
+        match &message.super_chat {
+            Some(super_chat) => {
+                egui::Frame::none()
+                    .fill(egui::Color32::from_rgb(120, 80, 0))
+                    .rounding(4.0)
+                    .inner_margin(4.0)
+                    .show(ui, |ui| {
+                        ui.horizontal_wrapped(|ui| {
+                            header(ui);
+                            ui.strong(format!("{} {}", super_chat.amount, super_chat.currency));
+                        });
+                        ui.label(&message.text);
+                    });
+            }
+            None => {
+                ui.horizontal_wrapped(|ui| {
+                    header(ui);
+                    ui.label(&message.text);
+                });
+            }
+        }
+    }
+
+    /// プレビューの右上にミュート中の表示を描画する
+    fn muted_overlay(ui: &egui::Ui, preview_rect: egui::Rect, text: &str) {
+        let painter = ui.painter().with_clip_rect(preview_rect);
+        let font = egui::FontId::proportional(16.0);
+        let galley = painter.layout_no_wrap(text.to_string(), font, egui::Color32::WHITE);
+        let size = galley.size() + egui::vec2(32.0, 12.0);
+        let rect = egui::Rect::from_min_size(preview_rect.right_top() + egui::vec2(-size.x - 8.0, 8.0), size);
+        painter.rect_filled(rect, 6.0, egui::Color32::from_rgba_unmultiplied(200, 30, 30, 220));
+
+        // マイクに斜線のアイコン
+        let icon = egui::pos2(rect.left() + 14.0, rect.center().y);
+        let stroke = egui::Stroke::new(2.0, egui::Color32::WHITE);
+        painter.rect_stroke(egui::Rect::from_center_size(icon + egui::vec2(0.0, -2.0), egui::vec2(6.0, 10.0)), 3.0, stroke);
+        painter.line_segment([icon + egui::vec2(0.0, 4.0), icon + egui::vec2(0.0, 7.0)], stroke);
+        painter.line_segment([icon + egui::vec2(-6.0, -7.0), icon + egui::vec2(6.0, 7.0)], stroke);
+
+        painter.galley(egui::pos2(rect.left() + 26.0, rect.center().y - galley.size().y / 2.0), galley);
+    }
+
+    /// 取得元プラットフォームのアイコンを描画する
+    fn platform_icon(ui: &mut egui::Ui, platform: Platform) {
+        let (fill, letter) = match platform {
+            Platform::YouTube => (egui::Color32::from_rgb(255, 0, 0), "Y"),
+            Platform::Twitch => (egui::Color32::from_rgb(145, 70, 255), "T"),
+        };
+        let size = egui::vec2(14.0, 14.0);
+        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+        ui.painter().rect_filled(rect, 3.0, fill);
+        ui.painter().text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            letter,
+            egui::FontId::proportional(10.0),
+            egui::Color32::WHITE,
+        );
+        response.on_hover_text(platform.label());
+    }
+
     #[allow(dead_code)]
     pub fn update_frame(&mut self, frame: VideoFrame) {
         self.current_frame = Some(frame);
@@ -101,6 +101,11 @@ impl VideoTab {
                         ui.label("サイズ:");
                         ui.add(egui::DragValue::new(&mut camera.size.0).speed(1.0).suffix("w"));
                         ui.add(egui::DragValue::new(&mut camera.size.1).speed(1.0).suffix("h"));
+                        ui.label("遅延補正:");
+                        let mut offset = camera.sync_offset_ms();
+                        if ui.add(egui::DragValue::new(&mut offset).speed(1.0).suffix("ms").clamp_range(-1000..=1000)).changed() {
+                            camera.set_sync_offset_ms(offset);
+                        }
 
                         // フレームの表示（テクスチャキャッシュを使用）
                         if let Ok(frame_lock) = camera.frame.lock() {
@@ -163,6 +168,11 @@ impl VideoTab {
                     ui.add(egui::DragValue::new(&mut self.video_config.screen_capture.size.0).speed(1.0).suffix("w"));
                     ui.add(egui::DragValue::new(&mut self.video_config.screen_capture.size.1).speed(1.0).suffix("h"));
                 });
+
+                ui.horizontal(|ui| {
+                    ui.label("遅延補正:");
+                    ui.add(egui::DragValue::new(&mut self.video_config.screen_capture.sync_offset_ms).speed(1.0).suffix("ms").clamp_range(-1000..=1000));
+                });
             }
         });
 
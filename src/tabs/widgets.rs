@@ -0,0 +1,88 @@
+use eframe::egui;
+use crate::models::dsp::gain_to_db;
+use crate::models::level_meter::MeterReading;
+
+/// メーターの表示範囲の下限（dBFS）
+const METER_FLOOR_DB: f32 = -60.0;
+
+/// 振幅を 0.0〜1.0 の表示位置に変換する
+fn meter_position(level: f32) -> f32 {
+    ((gain_to_db(level) - METER_FLOOR_DB) / -METER_FLOOR_DB).clamp(0.0, 1.0)
+}
+
+fn level_color(position: f32) -> egui::Color32 {
+    if position > (-METER_FLOOR_DB - 3.0) / -METER_FLOOR_DB {
+        egui::Color32::from_rgb(220, 60, 60)
+    } else if position > (-METER_FLOOR_DB - 12.0) / -METER_FLOOR_DB {
+        egui::Color32::from_rgb(230, 200, 60)
+    } else {
+        egui::Color32::from_rgb(70, 190, 90)
+    }
+}
+
+/// 左右のレベルメーター（ピーク・RMS・ピークホールド・クリップ表示）
+///
+/// 高さが幅より大きければ縦向き、そうでなければ横向きに描く。クリップ表示をクリックするとリセットできるよう、クリックされたら true を返す
+pub fn level_meter(ui: &mut egui::Ui, reading: &MeterReading, size: egui::Vec2) -> bool {
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+    let painter = ui.painter_at(rect);
+    let vertical = size.y > size.x;
+
+    // クリップ表示を先端に置き、残りを左右のバーに分ける
+    let clip_size = 6.0;
+    let (clip_rect, bars_rect) = if vertical {
+        let clip = egui::Rect::from_min_size(rect.min, egui::vec2(rect.width(), clip_size));
+        (clip, egui::Rect::from_min_max(egui::pos2(rect.min.x, clip.max.y + 1.0), rect.max))
+    } else {
+        let clip = egui::Rect::from_min_size(egui::pos2(rect.max.x - clip_size, rect.min.y), egui::vec2(clip_size, rect.height()));
+        (clip, egui::Rect::from_min_max(rect.min, egui::pos2(clip.min.x - 1.0, rect.max.y)))
+    };
+    let clip_color = if reading.clipped { egui::Color32::RED } else { egui::Color32::from_gray(50) };
+    painter.rect_filled(clip_rect, 1.0, clip_color);
+
+    for channel in 0..2 {
+        let bar = if vertical {
+            let width = bars_rect.width() / 2.0;
+            egui::Rect::from_min_size(
+                egui::pos2(bars_rect.min.x + width * channel as f32, bars_rect.min.y),
+                egui::vec2(width - 1.0, bars_rect.height()),
+            )
+        } else {
+            let height = bars_rect.height() / 2.0;
+            egui::Rect::from_min_size(
+                egui::pos2(bars_rect.min.x, bars_rect.min.y + height * channel as f32),
+                egui::vec2(bars_rect.width(), height - 1.0),
+            )
+        };
+        painter.rect_filled(bar, 0.0, egui::Color32::from_gray(30));
+
+        // 0.0〜1.0 の位置までを塗った矩形
+        let filled = |position: f32| {
+            if vertical {
+                egui::Rect::from_min_max(egui::pos2(bar.min.x, bar.max.y - bar.height() * position), bar.max)
+            } else {
+                egui::Rect::from_min_max(bar.min, egui::pos2(bar.min.x + bar.width() * position, bar.max.y))
+            }
+        };
+        let peak = meter_position(reading.peak[channel]);
+        let rms = meter_position(reading.rms[channel]);
+        painter.rect_filled(filled(peak), 0.0, level_color(peak).gamma_multiply(0.5));
+        painter.rect_filled(filled(rms), 0.0, level_color(rms));
+
+        let hold = meter_position(reading.hold[channel]);
+        if hold > 0.0 {
+            let line = filled(hold);
+            let (from, to) = if vertical {
+                (line.left_top(), line.right_top())
+            } else {
+                (line.right_top(), line.right_bottom())
+            };
+            painter.line_segment([from, to], egui::Stroke::new(1.0, level_color(hold)));
+        }
+    }
+
+    let peak = reading.peak[0].max(reading.peak[1]);
+    response
+        .on_hover_text(format!("ピーク {:.1}dBFS（クリックでクリップ表示をリセット）", gain_to_db(peak)))
+        .clicked()
+}